@@ -0,0 +1,91 @@
+use bulletin_board_common::{ErrorKind, Response};
+use std::fmt::Display;
+
+/// Error returned by the client functions.
+#[derive(Debug)]
+pub enum Error {
+    /// The bulletin or the archive does not exist.
+    NotFound,
+    /// The tag is omitted, but the title has multiple tags. The candidate tags are attached.
+    NotUnique(Vec<String>),
+    /// Failure in the connection to the server.
+    Io(std::io::Error),
+    /// The server or the client failed to understand the messages exchanged.
+    Protocol(String),
+    /// The awaited bulletin did not appear in time.
+    Timeout,
+    /// The server failed to process the operation.
+    Server { kind: ErrorKind, message: String },
+}
+
+impl Error {
+    /// Converts a response other than [`Response::Ok`] into an error.
+    pub(crate) fn from_response(response: Response) -> Self {
+        match response {
+            Response::Ok => Self::Protocol("Unexpected response.".to_string()),
+            Response::NotFound => Self::NotFound,
+            Response::NotUnique(tags) => Self::NotUnique(tags),
+            Response::Error { kind, message } => match kind {
+                ErrorKind::NotFound => Self::NotFound,
+                ErrorKind::Timeout => Self::Timeout,
+                ErrorKind::Protocol | ErrorKind::UnknownOperation => Self::Protocol(message),
+                kind => Self::Server { kind, message },
+            },
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound => write!(f, "Not found."),
+            Self::NotUnique(tags) => write!(f, "Multiple data found: {}", tags.join(", ")),
+            Self::Io(err) => write!(f, "{err}"),
+            Self::Protocol(message) => write!(f, "Protocol error: {message}"),
+            Self::Timeout => write!(f, "Timed out."),
+            Self::Server { kind, message } => write!(f, "Server error ({kind:?}): {message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<Error> for std::io::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Io(err) => err,
+            err => std::io::Error::other(err),
+        }
+    }
+}
+
+impl From<ciborium::de::Error<std::io::Error>> for Error {
+    fn from(err: ciborium::de::Error<std::io::Error>) -> Self {
+        match err {
+            ciborium::de::Error::Io(err) => Self::Io(err),
+            err => Self::Protocol(err.to_string()),
+        }
+    }
+}
+
+impl From<ciborium::ser::Error<std::io::Error>> for Error {
+    fn from(err: ciborium::ser::Error<std::io::Error>) -> Self {
+        match err {
+            ciborium::ser::Error::Io(err) => Self::Io(err),
+            err => Self::Protocol(err.to_string()),
+        }
+    }
+}
@@ -30,11 +30,22 @@
 /// Low-level functions that isolate the opening and closing functions of a socket. These can be used to speed up commucation with the server when you do many operations at the same time.
 pub mod low_level;
 
+mod error;
+
 pub use array_object::{ArrayObject, DataType, Pack, TryConcat, Unpack, adaptor};
+pub use bulletin_board_common::{
+    ArchiveInfo, ArchiveReport, Attributes, Continuation, ErrorKind, Event, Pattern, Query,
+    QueryPage, Retention, RevisionInfo, Selection, SortKey, Status, WatchFilter,
+};
+pub use error::Error;
 
 use low_level::*;
+use rustls::{ClientConfig, RootCertStore, pki_types::CertificateDer, pki_types::pem::PemObject};
+#[cfg(not(feature = "dry_run"))]
+use std::collections::HashMap;
 use std::{
-    sync::{LazyLock, Mutex},
+    io::Read,
+    sync::{Arc, LazyLock, Mutex},
     time::Duration,
 };
 
@@ -45,13 +56,44 @@ static ADDR: LazyLock<Mutex<String>> = LazyLock::new(|| {
 
 static TIMEOUT: LazyLock<Mutex<Option<Duration>>> = LazyLock::new(|| {
     let timeout = std::env::var("BB_TIMEOUT").unwrap_or("".to_string());
-    if timeout == "" {
+    if timeout.is_empty() {
         Mutex::new(None)
     } else {
         Mutex::new(Some(Duration::from_millis(timeout.parse().unwrap())))
     }
 });
 
+static TOKEN: LazyLock<Mutex<Option<String>>> =
+    LazyLock::new(|| Mutex::new(std::env::var("BB_TOKEN").ok()));
+
+static NAMESPACE: LazyLock<Mutex<Option<String>>> = LazyLock::new(|| {
+    let namespace = std::env::var("BB_NAMESPACE").ok();
+    Mutex::new(namespace.filter(|namespace| !namespace.is_empty()))
+});
+
+/// A CA file in `BB_TLS_CA` that cannot be read fails the connections until [`set_tls_ca`] is called.
+static TLS: LazyLock<Mutex<Result<Option<Arc<ClientConfig>>, String>>> = LazyLock::new(|| {
+    let config = std::env::var("BB_TLS_CA")
+        .ok()
+        .map(|ca_file| tls_config(&ca_file))
+        .transpose()
+        .map_err(|err| err.to_string());
+    Mutex::new(config)
+});
+
+static ACKNOWLEDGE: LazyLock<Mutex<bool>> = LazyLock::new(|| {
+    let acknowledge = std::env::var("BB_ACKNOWLEDGE").unwrap_or("".to_string());
+    Mutex::new(!matches!(acknowledge.as_str(), "0" | "false"))
+});
+
+/// Handshakes negotiated with the servers, keyed by the address, the token and the namespace. The handshake of a new connection is not awaited if the server has accepted the same one before.
+#[cfg(not(feature = "dry_run"))]
+static HANDSHAKES: LazyLock<Mutex<HashMap<HandshakeKey, bulletin_board_common::Handshake>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[cfg(not(feature = "dry_run"))]
+type HandshakeKey = (String, Option<String>, Option<String>);
+
 /// Sets the server address.
 ///
 /// Valid formats are "address:port" and "path/to/socket".
@@ -66,19 +108,89 @@ pub fn set_timeout(new_timeout: Option<Duration>) {
     *timeout = new_timeout;
 }
 
+/// Sets the token sent to the server in the handshake. Setting it to None sends no token.
+///
+/// The token is required if the server is configured with `BB_TOKEN` or `BB_TOKEN_FILE` and the client connects over TCP. The default is the value of the environment variable `BB_TOKEN`.
+pub fn set_token(new_token: Option<&str>) {
+    let mut token = TOKEN.lock().unwrap();
+    *token = new_token.map(|token| token.to_string());
+}
+
+/// Selects the namespace, whose board is isolated from those of the other namespaces. Setting it to None or an empty string selects the default namespace.
+///
+/// Titles, [`status`], [`view_board`] and [`reset_server`] apply to the board of the selected namespace only. A namespace consists of up to 64 alphanumeric characters, `-` and `_`. The board of a namespace is created on first use unless the client has the read-only role. The default is the value of the environment variable `BB_NAMESPACE`.
+pub fn set_namespace(new_namespace: Option<&str>) {
+    let mut namespace = NAMESPACE.lock().unwrap();
+    *namespace = new_namespace
+        .filter(|namespace| !namespace.is_empty())
+        .map(|namespace| namespace.to_string());
+}
+
+/// Enables TLS for TCP connections with the certificate of the server verified against the CA certificates in a PEM file. Setting it to None disables TLS.
+///
+/// The certificate has to be valid for the host name or the IP address given by [`set_addr`]. A self-signed certificate of the server can be used as the CA file. The default is the value of the environment variable `BB_TLS_CA`.
+pub fn set_tls_ca(ca_file: Option<&str>) -> Result<(), Error> {
+    let config = ca_file.map(tls_config).transpose()?;
+    let mut tls = TLS.lock().unwrap();
+    *tls = Ok(config);
+    Ok(())
+}
+
+fn tls_config(ca_file: &str) -> Result<Arc<ClientConfig>, Error> {
+    let invalid = |err: String| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("{ca_file}: {err}"),
+        )
+    };
+    let mut roots = RootCertStore::empty();
+    for cert in CertificateDer::pem_file_iter(ca_file).map_err(|err| invalid(err.to_string()))? {
+        let cert = cert.map_err(|err| invalid(err.to_string()))?;
+        roots.add(cert).map_err(|err| invalid(err.to_string()))?;
+    }
+    if roots.is_empty() {
+        return Err(invalid("No certificate is found.".to_string()).into());
+    }
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let config = ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(std::io::Error::other)?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    Ok(Arc::new(config))
+}
+
+/// Enables or disables acknowledgement of operations that modify the server (enabled by default).
+///
+/// When disabled, such operations are sent in a fire-and-forget manner, which is faster in hot loops, but failures are only recorded in the log of the server.
+///
+/// Each function opens its own connection, so an unacknowledged operation may be processed after the operations that follow it. To keep the order, send the operations on one connection of [`low_level::TcpOrUnixStream`]; the acknowledgement of an operation then implies that the preceding ones have been processed.
+pub fn set_acknowledge(acknowledge: bool) {
+    let mut ack = ACKNOWLEDGE.lock().unwrap();
+    *ack = acknowledge;
+}
+
 /// Posts an ArrayObject.
-pub fn post(title: &str, tag: &str, obj: ArrayObject) -> Result<(), Box<dyn std::error::Error>> {
+pub fn post(title: &str, tag: &str, obj: ArrayObject) -> Result<(), Error> {
     let mut stream = TcpOrUnixStream::connect()?;
     stream.post_raw(title, tag, obj.pack())?;
     Ok(())
 }
 
-/// Posts an ArrayObject without compression.
-pub fn post_as_it_is(
+/// Posts an ArrayObject with attributes attached to the revision, such as the units or the source location.
+pub fn post_with_attributes(
     title: &str,
     tag: &str,
     obj: ArrayObject,
-) -> Result<(), Box<dyn std::error::Error>> {
+    attributes: &Attributes,
+) -> Result<(), Error> {
+    let mut stream = TcpOrUnixStream::connect()?;
+    stream.post_raw_with_attributes(title, tag, obj.pack(), attributes)?;
+    Ok(())
+}
+
+/// Posts an ArrayObject without compression.
+pub fn post_as_it_is(title: &str, tag: &str, obj: ArrayObject) -> Result<(), Error> {
     let mut stream = TcpOrUnixStream::connect()?;
     stream.post_raw(title, tag, obj.pack_as_it_is())?;
     Ok(())
@@ -92,30 +204,142 @@ pub fn read(
     title: &str,
     tag: Option<&str>,
     revisions: Vec<u64>,
-) -> Result<Vec<ArrayObject>, Box<dyn std::error::Error>> {
+) -> Result<Vec<ArrayObject>, Error> {
     let mut stream = TcpOrUnixStream::connect()?;
     let list = stream.read_raw(title, tag, revisions)?;
     let mut objs = vec![];
     for data in list {
-        objs.push(ArrayObject::unpack(data)?);
+        objs.push(ArrayObject::unpack(data).map_err(|err| Error::Protocol(err.to_string()))?);
     }
     Ok(objs)
 }
 
+/// Posts binary of ArrayObject read from `reader` in chunks. Large data, e.g. a file containing a packed ArrayObject, is sent without being loaded into memory.
+pub fn post_stream(title: &str, tag: &str, reader: impl Read) -> Result<(), Error> {
+    let mut stream = TcpOrUnixStream::connect()?;
+    stream.post_stream(title, tag, &Attributes::new(), reader)?;
+    Ok(())
+}
+
+/// Posts the arrays in a `.npy`, `.npz` or CSV file. The arrays in an NPZ file are posted as `title/name`.
+///
+/// A CSV file is read as a two-dimensional array of rows and columns, or a one-dimensional array if it has only one column. A header row and lines starting with `#` are skipped.
+pub fn import(title: &str, tag: &str, path: &str) -> Result<(), Error> {
+    let file = std::fs::File::open(path)?;
+    let filename = std::path::Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut stream = TcpOrUnixStream::connect()?;
+    stream.import(title, tag, &filename, file)?;
+    Ok(())
+}
+
+/// Reads binary of ArrayObject in chunks. The data is received as the returned reader is read.
+///
+/// Tag can be None if there is only one tag exists for the title.
+/// When revision is None, the latest revision is returned.
+pub fn read_stream(
+    title: &str,
+    tag: Option<&str>,
+    revision: Option<u64>,
+) -> Result<impl Read, Error> {
+    let mut stream = TcpOrUnixStream::connect()?;
+    stream.request_stream(title, tag, revision)?;
+    Ok(BulletinReader::new(stream))
+}
+
+/// Watches the changes of a bulletin. The returned iterator blocks until the next change and yields the event together with the posted ArrayObject if with_data is true.
+///
+/// All the tags of the title are watched if tag is None.
+pub fn watch(
+    title: &str,
+    tag: Option<&str>,
+    with_data: bool,
+) -> Result<impl Iterator<Item = Result<(Event, Option<ArrayObject>), Error>>, Error> {
+    let filter = WatchFilter {
+        title: title.to_string(),
+        tag: tag.map(|x| x.to_string()),
+        prefix: false,
+    };
+    watch_with(filter, with_data)
+}
+
+/// Watches the changes of the bulletins whose titles start with the prefix. See [`watch`].
+pub fn watch_prefix(
+    prefix: &str,
+    tag: Option<&str>,
+    with_data: bool,
+) -> Result<impl Iterator<Item = Result<(Event, Option<ArrayObject>), Error>>, Error> {
+    let filter = WatchFilter {
+        title: prefix.to_string(),
+        tag: tag.map(|x| x.to_string()),
+        prefix: true,
+    };
+    watch_with(filter, with_data)
+}
+
+fn watch_with(
+    filter: WatchFilter,
+    with_data: bool,
+) -> Result<impl Iterator<Item = Result<(Event, Option<ArrayObject>), Error>>, Error> {
+    let stream = TcpOrUnixStream::connect()?;
+    let watcher = stream.watch(filter, with_data)?;
+    Ok(watcher.map(|item| {
+        let (event, data) = item?;
+        let obj = match data {
+            Some(data) => {
+                Some(ArrayObject::unpack(data).map_err(|err| Error::Protocol(err.to_string()))?)
+            }
+            None => None,
+        };
+        Ok((event, obj))
+    }))
+}
+
+/// Reads the latest revision of a bulletin, waiting up to timeout until the bulletin exists or a revision newer than after_revision is posted. Returns the revision number and the ArrayObject.
+///
+/// Tag can be None if there is only one tag exists for the title.
+/// If nothing is posted in time, [`Error::Timeout`] is returned.
+pub fn wait_read(
+    title: &str,
+    tag: Option<&str>,
+    after_revision: Option<u64>,
+    timeout: Duration,
+) -> Result<(u64, ArrayObject), Error> {
+    let mut stream = TcpOrUnixStream::connect()?;
+    let (revision, data) = stream.wait_read(title, tag, after_revision, timeout)?;
+    let obj = ArrayObject::unpack(data).map_err(|err| Error::Protocol(err.to_string()))?;
+    Ok((revision, obj))
+}
+
+/// Sets the retention policy of a bulletin. Older revisions exceeding the limits are cleared when a revision is posted.
+///
+/// The policy applies to all the tags of the title if the tag is None, and is removed if the retention is None.
+pub fn set_retention(
+    title: &str,
+    tag: Option<&str>,
+    retention: Option<&Retention>,
+) -> Result<(), Error> {
+    let mut stream = TcpOrUnixStream::connect()?;
+    stream.set_retention(title, tag, retention)?;
+    Ok(())
+}
+
 /// Relabels a bulletin.
 pub fn relabel(
     title_from: &str,
     tag_from: Option<&str>,
     title_to: Option<&str>,
     tag_to: Option<&str>,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<(), Error> {
     let mut stream = TcpOrUnixStream::connect()?;
     stream.relabel(title_from, tag_from, title_to, tag_to)?;
     Ok(())
 }
 
 /// Returns the version of the server.
-pub fn server_version() -> Result<String, Box<dyn std::error::Error>> {
+pub fn server_version() -> Result<String, Error> {
     let mut stream = TcpOrUnixStream::connect()?;
     let version = stream.server_version()?;
     Ok(version)
@@ -123,132 +347,205 @@ pub fn server_version() -> Result<String, Box<dyn std::error::Error>> {
 
 /// Returns the version of the client.
 pub fn client_version() -> String {
-    let client_version = env!("CARGO_PKG_VERSION").to_string();
-    client_version
+    env!("CARGO_PKG_VERSION").to_string()
 }
 
 /// Returns the status of the server.
 ///
-/// The return values are (total datasize (bytes), memory used (bytes), memory used (%), the number of objects, the number of objects backed by files, the number of archived objects)
+/// The return values are (total datasize (bytes), memory used (bytes), memory used (%), the number of objects, the number of objects backed by files, the number of archived objects, the number of objects evicted to files, the number of objects promoted back to memory, the number of revisions cleared by the retention policies, the total size of the cleared revisions (bytes))
 ///
 /// The total datasize does not include the size of metadata such as timestamp.
-pub fn status() -> Result<(u64, u64, f64, u64, u64, u64), Box<dyn std::error::Error>> {
+pub fn status() -> Result<Status, Error> {
     let mut stream = TcpOrUnixStream::connect()?;
     let status = stream.status()?;
     Ok(status)
 }
 
 /// Returns the log of the server.
-pub fn log() -> Result<String, Box<dyn std::error::Error>> {
+pub fn log() -> Result<String, Error> {
     let mut stream = TcpOrUnixStream::connect()?;
     let log = stream.log()?;
     Ok(log)
 }
 
 /// Returns the list of the bulletins.
-pub fn view_board() -> Result<Vec<(String, String, u64)>, Box<dyn std::error::Error>> {
+pub fn view_board() -> Result<Vec<(String, String, u64)>, Error> {
     let mut stream = TcpOrUnixStream::connect()?;
     let list = stream.view_board()?;
     Ok(list)
 }
 
-/// Returns the details of a bulletin. The return values are a vector of (revision number, datasize (bytes), timestamp, backend).
-pub fn get_info(
-    title: &str,
-    tag: Option<&str>,
-) -> Result<Vec<(u64, u64, String, String)>, Box<dyn std::error::Error>> {
+/// Returns the bulletins selected by the query. Pass [`QueryPage::next`] as [`Query::after`] to get the next page.
+pub fn query_board(query: &Query) -> Result<QueryPage, Error> {
+    let mut stream = TcpOrUnixStream::connect()?;
+    let page = stream.query_board(query)?;
+    Ok(page)
+}
+
+/// Returns the details of a bulletin. The return values are a vector of (revision number, datasize (bytes), timestamp, backend, attributes).
+pub fn get_info(title: &str, tag: Option<&str>) -> Result<Vec<RevisionInfo>, Error> {
     let mut stream = TcpOrUnixStream::connect()?;
     let list = stream.get_info(title, tag)?;
     Ok(list)
 }
 
-/// Deletes specific revisions from a bulletin.
-pub fn clear_revisions(
-    title: &str,
-    tag: Option<&str>,
-    revisions: Vec<u64>,
-) -> Result<(), Box<dyn std::error::Error>> {
+/// Deletes specific revisions from a bulletin. Revisions loaded from an archive cannot be cleared; see `delete_from_archive`.
+pub fn clear_revisions(title: &str, tag: Option<&str>, revisions: Vec<u64>) -> Result<(), Error> {
     let mut stream = TcpOrUnixStream::connect()?;
     stream.clear_revisions(title, tag, revisions)?;
     Ok(())
 }
 
 /// Removes all the revisions and the database entry of a bulletin.
-pub fn remove(title: &str, tag: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+pub fn remove(title: &str, tag: Option<&str>) -> Result<(), Error> {
     let mut stream = TcpOrUnixStream::connect()?;
     stream.remove(title, tag)?;
     Ok(())
 }
 
 /// Moves a bulletin to a persistent archive.
-pub fn archive(
-    acv_name: &str,
-    title: &str,
-    tag: Option<&str>,
-) -> Result<(), Box<dyn std::error::Error>> {
+pub fn archive(acv_name: &str, title: &str, tag: Option<&str>) -> Result<(), Error> {
     let mut stream = TcpOrUnixStream::connect()?;
     stream.archive(acv_name, title, tag)?;
     Ok(())
 }
 
 /// Loads or reloads an archive. The data is directly read from the archive file and a suffix "acv_name:" is added to the tag.
-pub fn load(acv_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+pub fn load(acv_name: &str) -> Result<(), Error> {
     let mut stream = TcpOrUnixStream::connect()?;
     stream.load(acv_name)?;
     Ok(())
 }
 
+/// Loads the revisions of an archive selected by title, tag and revision range. The loaded keys matching the selection are replaced, and the others are kept.
+pub fn load_selected(acv_name: &str, selection: &Selection) -> Result<(), Error> {
+    let mut stream = TcpOrUnixStream::connect()?;
+    stream.load_selected(acv_name, selection)?;
+    Ok(())
+}
+
+/// Removes the bulletins loaded from an archive. The other bulletins are kept.
+pub fn unload(acv_name: &str) -> Result<(), Error> {
+    let mut stream = TcpOrUnixStream::connect()?;
+    stream.unload(acv_name)?;
+    Ok(())
+}
+
+/// Shows the list of loaded archives.
+pub fn list_loaded() -> Result<Vec<String>, Error> {
+    let mut stream = TcpOrUnixStream::connect()?;
+    let list = stream.list_loaded()?;
+    Ok(list)
+}
+
+/// Reads the metadata of an archive without loading it.
+pub fn archive_info(acv_name: &str) -> Result<ArchiveInfo, Error> {
+    let mut stream = TcpOrUnixStream::connect()?;
+    let info = stream.archive_info(acv_name)?;
+    Ok(info)
+}
+
+/// Exports an archive, or the board if acv_name is None, to `{output}.npz` and `{output}.json`.
+///
+/// Each revision is stored as `title/tag/revision` in the NPZ file, which can be read by `numpy.load`. The JSON manifest lists the revisions with their data type, shape and attributes, and includes the elements of small arrays.
+pub fn export(acv_name: Option<&str>, output: &str) -> Result<(), Error> {
+    let mut stream = TcpOrUnixStream::connect()?;
+    let (manifest, mut reader) = stream.export(acv_name)?;
+    let mut file = std::fs::File::create(format!("{output}.npz"))?;
+    std::io::copy(&mut reader, &mut file).map_err(|err| match err.downcast::<Error>() {
+        Ok(err) => err,
+        Err(err) => Error::Io(err),
+    })?;
+    std::fs::write(format!("{output}.json"), manifest)?;
+    Ok(())
+}
+
 /// Shows the list of archive.
-pub fn list_archive() -> Result<Vec<String>, Box<dyn std::error::Error>> {
+pub fn list_archive() -> Result<Vec<String>, Error> {
     let mut stream = TcpOrUnixStream::connect()?;
     let list = stream.list_archive()?;
     Ok(list)
 }
 
-/// Renames an archive. This will be applied after after calling reset_server.
-pub fn rename_archive(name_from: &str, name_to: &str) -> Result<(), Box<dyn std::error::Error>> {
+/// Renames an archive. It fails if the archive is loaded.
+pub fn rename_archive(name_from: &str, name_to: &str) -> Result<(), Error> {
     let mut stream = TcpOrUnixStream::connect()?;
     stream.rename_archive(name_from, name_to)?;
     Ok(())
 }
 
-/// Deletes an archive. This will be applied after after calling reset_server.
-pub fn delete_archive(acv_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+/// Deletes an archive. It fails if the archive is loaded.
+pub fn delete_archive(acv_name: &str) -> Result<(), Error> {
     let mut stream = TcpOrUnixStream::connect()?;
     stream.delete_archive(acv_name)?;
     Ok(())
 }
 
 /// Dumps all the unarchived data into an archive.
-pub fn dump(acv_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+pub fn dump(acv_name: &str) -> Result<(), Error> {
     let mut stream = TcpOrUnixStream::connect()?;
     stream.dump(acv_name)?;
     Ok(())
 }
 
-/// Delete all the temporary data and restores data from an archive. Each data is copied to memory or a separate file. No suffix is added to the tag.
-pub fn restore(acv_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+/// Delete all the temporary data and restores data from an archive. Each data is copied to memory or a separate file. No suffix is added to the tag. The admin role is required.
+pub fn restore(acv_name: &str) -> Result<(), Error> {
     let mut stream = TcpOrUnixStream::connect()?;
     stream.restore(acv_name)?;
     Ok(())
 }
 
+/// Restores the revisions of an archive selected by title, tag and revision range. If `merge` is true, the revisions are appended to the current board without resetting it. Otherwise, the admin role is required.
+pub fn restore_selected(acv_name: &str, selection: &Selection, merge: bool) -> Result<(), Error> {
+    let mut stream = TcpOrUnixStream::connect()?;
+    stream.restore_selected(acv_name, selection, merge)?;
+    Ok(())
+}
+
+/// Checks an archive against its data. If `repair` is true, the index of a damaged archive is rewritten with the valid revisions. The data is not moved, so that loaded archives stay valid. Repairing requires the admin role.
+pub fn verify_archive(acv_name: &str, repair: bool) -> Result<ArchiveReport, Error> {
+    let mut stream = TcpOrUnixStream::connect()?;
+    let report = stream.verify_archive(acv_name, repair)?;
+    Ok(report)
+}
+
+/// Deletes revisions from an archive. All the tags are selected if `tag` is None, and all the revisions if `revisions` is None. The loaded revisions are cleared as well. The space is reclaimed by [`compact_archive`].
+pub fn delete_from_archive(
+    acv_name: &str,
+    title: &str,
+    tag: Option<&str>,
+    revisions: Option<Vec<u64>>,
+) -> Result<(), Error> {
+    let mut stream = TcpOrUnixStream::connect()?;
+    stream.delete_from_archive(acv_name, title, tag, revisions)?;
+    Ok(())
+}
+
+/// Rewrites an archive without the deleted revisions to reclaim the space. Loaded revisions stay valid.
+pub fn compact_archive(acv_name: &str) -> Result<(), Error> {
+    let mut stream = TcpOrUnixStream::connect()?;
+    stream.compact_archive(acv_name)?;
+    Ok(())
+}
+
 /// Clears the log file of the server.
-pub fn clear_log() -> Result<(), Box<dyn std::error::Error>> {
+pub fn clear_log() -> Result<(), Error> {
     let mut stream = TcpOrUnixStream::connect()?;
     stream.clear_log()?;
     Ok(())
 }
 
 /// Resets and clears the data. The archived data is not affected, but must be loaded before use.
-pub fn reset_server() -> Result<(), Box<dyn std::error::Error>> {
+///
+/// Only the board of the namespace selected by [`set_namespace`] is reset.
+pub fn reset_server() -> Result<(), Error> {
     let mut stream = TcpOrUnixStream::connect()?;
     stream.reset_server()?;
     Ok(())
 }
 
-/// Terminates the server.
-pub fn terminate_server() -> Result<(), Box<dyn std::error::Error>> {
+/// Terminates the server. The boards of all the namespaces are cleared.
+pub fn terminate_server() -> Result<(), Error> {
     let mut stream = TcpOrUnixStream::connect()?;
     stream.terminate_server()?;
     Ok(())
@@ -1,5 +1,7 @@
+use crate::ACKNOWLEDGE;
+use crate::error::Error;
 #[cfg(not(feature = "dry_run"))]
-use crate::{ADDR, TIMEOUT};
+use crate::{ADDR, HANDSHAKES, HandshakeKey, NAMESPACE, TIMEOUT, TLS, TOKEN};
 
 use bulletin_board_common::*;
 use serde::de::DeserializeOwned;
@@ -8,8 +10,11 @@ use serde_bytes::ByteBuf;
 #[cfg(not(feature = "dry_run"))]
 use std::io;
 
-use std::io::Cursor;
+use std::io::{Cursor, Read, Write};
+use std::time::Duration;
 
+#[cfg(not(feature = "dry_run"))]
+use rustls::{ClientConnection, StreamOwned, pki_types::ServerName};
 #[cfg(not(feature = "dry_run"))]
 use std::net::{TcpStream, ToSocketAddrs};
 
@@ -22,69 +27,286 @@ pub enum TcpOrUnixStream {
     TCP(TcpStream),
     #[cfg(target_family = "unix")]
     Unix(UnixStream),
+    Tls(Box<StreamOwned<ClientConnection, TcpStream>>),
 }
 
 #[cfg(feature = "dry_run")]
 pub struct TcpOrUnixStream;
 
+impl Read for TcpOrUnixStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        #[cfg(not(feature = "dry_run"))]
+        match self {
+            TcpOrUnixStream::TCP(stream) => stream.read(buf),
+            #[cfg(target_family = "unix")]
+            TcpOrUnixStream::Unix(stream) => stream.read(buf),
+            TcpOrUnixStream::Tls(stream) => stream.read(buf),
+        }
+
+        #[cfg(feature = "dry_run")]
+        {
+            let _ = buf;
+            Ok(0)
+        }
+    }
+}
+
+impl Write for TcpOrUnixStream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        #[cfg(not(feature = "dry_run"))]
+        match self {
+            TcpOrUnixStream::TCP(stream) => stream.write(buf),
+            #[cfg(target_family = "unix")]
+            TcpOrUnixStream::Unix(stream) => stream.write(buf),
+            TcpOrUnixStream::Tls(stream) => stream.write(buf),
+        }
+
+        #[cfg(feature = "dry_run")]
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        #[cfg(not(feature = "dry_run"))]
+        match self {
+            TcpOrUnixStream::TCP(stream) => stream.flush(),
+            #[cfg(target_family = "unix")]
+            TcpOrUnixStream::Unix(stream) => stream.flush(),
+            TcpOrUnixStream::Tls(stream) => stream.flush(),
+        }
+
+        #[cfg(feature = "dry_run")]
+        Ok(())
+    }
+}
+
+/// Reader of a bulletin streamed from the server, returned by [`TcpOrUnixStream::read_stream`].
+///
+/// The stream cannot be used for other operations until the reader reaches the end of the data.
+pub struct BulletinReader<S: Read> {
+    stream: S,
+    chunk: Vec<u8>,
+    position: usize,
+    finished: bool,
+}
+
+/// Blocking iterator over the changes of the board, returned by [`TcpOrUnixStream::watch`]. Each item is an event and the binary of the posted ArrayObject if requested.
+///
+/// The iteration ends when the server closes the connection.
+pub struct Watcher {
+    stream: TcpOrUnixStream,
+    finished: bool,
+}
+
+impl Iterator for Watcher {
+    type Item = Result<(Event, Option<Vec<u8>>), Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let event: Event = match ciborium::from_reader(&mut self.stream) {
+            Ok(event) => event,
+            Err(ciborium::de::Error::Io(err))
+                if err.kind() == std::io::ErrorKind::UnexpectedEof =>
+            {
+                self.finished = true;
+                return None;
+            }
+            Err(err) => {
+                self.finished = true;
+                return Some(Err(err.into()));
+            }
+        };
+        match ciborium::from_reader::<Option<ByteBuf>, _>(&mut self.stream) {
+            Ok(data) => Some(Ok((event, data.map(|data| data.into_vec())))),
+            Err(err) => {
+                self.finished = true;
+                Some(Err(err.into()))
+            }
+        }
+    }
+}
+
+impl<S: Read> BulletinReader<S> {
+    pub(crate) fn new(stream: S) -> Self {
+        Self {
+            stream,
+            chunk: vec![],
+            position: 0,
+            finished: cfg!(feature = "dry_run"),
+        }
+    }
+}
+
+impl<S: Read> Read for BulletinReader<S> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.position == self.chunk.len() {
+            if self.finished {
+                return Ok(0);
+            }
+            let chunk: ByteBuf = ciborium::from_reader(&mut self.stream).map_err(Error::from)?;
+            if chunk.is_empty() {
+                self.finished = true;
+                match ciborium::from_reader(&mut self.stream).map_err(Error::from)? {
+                    Response::Ok => {}
+                    res => return Err(Error::from_response(res).into()),
+                }
+            }
+            self.chunk = chunk.into_vec();
+            self.position = 0;
+        }
+        let size = buf.len().min(self.chunk.len() - self.position);
+        buf[..size].copy_from_slice(&self.chunk[self.position..self.position + size]);
+        self.position += size;
+        Ok(size)
+    }
+}
+
+#[cfg(not(feature = "dry_run"))]
+fn handshake_key() -> HandshakeKey {
+    (
+        ADDR.lock().unwrap().clone(),
+        TOKEN.lock().unwrap().clone(),
+        NAMESPACE.lock().unwrap().clone(),
+    )
+}
+
+fn forget_handshake() {
+    #[cfg(not(feature = "dry_run"))]
+    HANDSHAKES.lock().unwrap().remove(&handshake_key());
+}
+
 impl TcpOrUnixStream {
-    /// Open a TCP/UNIX socket. This blocks the server until the instance is dropped.
-    pub fn connect() -> Result<Self, Box<dyn std::error::Error>> {
+    /// Open a TCP/UNIX socket. The connection is kept open until the instance is dropped.
+    pub fn connect() -> Result<Self, Error> {
         #[cfg(all(target_family = "unix", not(feature = "dry_run")))]
-        let stream = {
+        let mut stream = {
             let addr = ADDR.lock().unwrap().clone();
             let ip = addr.to_socket_addrs();
             if let Ok(mut iter) = ip {
-                let timeout = TIMEOUT.lock().unwrap().clone();
+                let timeout = *TIMEOUT.lock().unwrap();
                 if let Some(t) = timeout {
                     let mut addr = iter.next().unwrap();
                     if addr.is_ipv6() {
                         addr = iter.next().unwrap();
                     }
-                    TcpOrUnixStream::TCP(TcpStream::connect_timeout(&addr, t)?)
+                    TcpOrUnixStream::tcp(TcpStream::connect_timeout(&addr, t)?)?
                 } else {
-                    TcpOrUnixStream::TCP(TcpStream::connect(&addr)?)
+                    TcpOrUnixStream::tcp(TcpStream::connect(&addr)?)?
                 }
             } else if !addr.contains(":") {
                 TcpOrUnixStream::Unix(UnixStream::connect(&addr)?)
             } else {
-                return Err(Box::new(io::Error::new(
+                return Err(io::Error::new(
                     io::ErrorKind::AddrNotAvailable,
                     "Address is invalid or not available.",
-                )));
+                )
+                .into());
             }
         };
 
         #[cfg(all(not(target_family = "unix"), not(feature = "dry_run")))]
-        let stream = {
+        let mut stream = {
             let addr = ADDR.lock().unwrap().clone();
             let ip = addr.to_socket_addrs();
             if let Ok(mut iter) = ip {
-                let timeout = TIMEOUT.lock().unwrap().clone();
+                let timeout = *TIMEOUT.lock().unwrap();
                 if let Some(t) = timeout {
                     let mut addr = iter.next().unwrap();
                     if addr.is_ipv6() {
                         addr = iter.next().unwrap();
                     }
-                    TcpOrUnixStream::TCP(TcpStream::connect_timeout(&addr, t)?)
+                    TcpOrUnixStream::tcp(TcpStream::connect_timeout(&addr, t)?)?
                 } else {
-                    TcpOrUnixStream::TCP(TcpStream::connect(&addr)?)
+                    TcpOrUnixStream::tcp(TcpStream::connect(&addr)?)?
                 }
             } else {
-                return Err(Box::new(io::Error::new(
+                return Err(io::Error::new(
                     io::ErrorKind::AddrNotAvailable,
                     "Address is invalid or not available.",
-                )));
+                )
+                .into());
             }
         };
 
         #[cfg(feature = "dry_run")]
         let stream = TcpOrUnixStream;
 
+        #[cfg(not(feature = "dry_run"))]
+        stream.handshake().inspect_err(|_| forget_handshake())?;
+
         Ok(stream)
     }
 
-    fn send(&mut self, mut buffer: Cursor<Vec<u8>>) -> Result<(), Box<dyn std::error::Error>> {
+    /// Wraps a TCP stream in TLS if a CA file is set by [`crate::set_tls_ca`]. The certificate of the server is verified for the host name in the address.
+    #[cfg(not(feature = "dry_run"))]
+    fn tcp(stream: TcpStream) -> Result<Self, Error> {
+        // The operation following a handshake that is not awaited is sent without delay.
+        stream.set_nodelay(true)?;
+        let config = TLS
+            .lock()
+            .unwrap()
+            .clone()
+            .map_err(|message| io::Error::new(io::ErrorKind::InvalidInput, message))?;
+        let Some(config) = config else {
+            return Ok(TcpOrUnixStream::TCP(stream));
+        };
+        let addr = ADDR.lock().unwrap().clone();
+        let host = addr
+            .rsplit_once(':')
+            .map_or(addr.as_str(), |(host, _)| host);
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        let name = ServerName::try_from(host.to_string())
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+        let conn = ClientConnection::new(config, name).map_err(io::Error::other)?;
+        Ok(TcpOrUnixStream::Tls(Box::new(StreamOwned::new(
+            conn, stream,
+        ))))
+    }
+
+    /// Negotiates the protocol version and the capabilities with the server.
+    ///
+    /// If the server has accepted the same handshake before, the reply is not awaited and the server answers only if it rejects the client. The rejection is then received in place of the response to the first operation.
+    #[cfg(not(feature = "dry_run"))]
+    fn handshake(&mut self) -> Result<Handshake, Error> {
+        let key = handshake_key();
+        let cached = HANDSHAKES.lock().unwrap().get(&key).cloned();
+        let mut buffer = Cursor::new(vec![]);
+        if cached.is_some() {
+            ciborium::into_writer(&Operation::NoReply, &mut buffer)?;
+        }
+        ciborium::into_writer(&Operation::Handshake, &mut buffer)?;
+        let (_, token, namespace) = key.clone();
+        let handshake = Handshake::new(capability::ACKNOWLEDGE | capability::QUIET_HANDSHAKE)
+            .with_token(token)
+            .with_namespace(namespace.clone());
+        ciborium::into_writer(&handshake, &mut buffer)?;
+        self.send(buffer)?;
+        if let Some(handshake) = cached {
+            return Ok(handshake);
+        }
+        let res = self.receive().map_err(|_| {
+            Error::Protocol(
+                "Handshake failed. The server may be older than the client.".to_string(),
+            )
+        })?;
+        match res {
+            Response::Ok => {
+                let handshake: Handshake = self.receive()?;
+                // Servers without namespaces ignore the request and would use the default board.
+                if handshake.namespace != namespace {
+                    return Err(Error::Protocol(
+                        "The server does not support namespaces.".to_string(),
+                    ));
+                }
+                if handshake.has(capability::QUIET_HANDSHAKE) {
+                    HANDSHAKES.lock().unwrap().insert(key, handshake.clone());
+                }
+                Ok(handshake)
+            }
+            res => Err(Error::from_response(res)),
+        }
+    }
+
+    fn send(&mut self, mut buffer: Cursor<Vec<u8>>) -> Result<(), Error> {
         buffer.set_position(0);
         #[cfg(not(feature = "dry_run"))]
         match self {
@@ -95,35 +317,84 @@ impl TcpOrUnixStream {
             TcpOrUnixStream::Unix(stream) => {
                 io::copy(&mut buffer, stream)?;
             }
+            TcpOrUnixStream::Tls(stream) => {
+                io::copy(&mut buffer, stream)?;
+            }
         }
 
         Ok(())
     }
 
-    fn receive<T: DeserializeOwned + Default>(&mut self) -> Result<T, Box<dyn std::error::Error>> {
+    fn receive<T: DeserializeOwned + Default>(&mut self) -> Result<T, Error> {
         #[cfg(not(feature = "dry_run"))]
         match self {
             TcpOrUnixStream::TCP(stream) => Ok(ciborium::from_reader(stream)?),
             #[cfg(target_family = "unix")]
             TcpOrUnixStream::Unix(stream) => Ok(ciborium::from_reader(stream)?),
+            TcpOrUnixStream::Tls(stream) => Ok(ciborium::from_reader(stream)?),
         }
 
         #[cfg(feature = "dry_run")]
         Ok(Default::default())
     }
 
+    /// Writes the operation to the buffer, preceded by [`Operation::NoReply`] if acknowledgement is disabled. Returns whether a reply is expected.
+    fn request(operation: Operation, buffer: &mut Cursor<Vec<u8>>) -> Result<bool, Error> {
+        let acknowledge = *ACKNOWLEDGE.lock().unwrap();
+        if !acknowledge {
+            ciborium::into_writer(&Operation::NoReply, &mut *buffer)?;
+        }
+        ciborium::into_writer(&operation, buffer)?;
+        Ok(acknowledge)
+    }
+
+    fn acknowledged(&mut self, acknowledge: bool) -> Result<(), Error> {
+        if acknowledge {
+            self.response()?;
+        }
+        Ok(())
+    }
+
+    /// Receives the response preceding the reply of an operation and converts it into an error if the operation failed.
+    ///
+    /// The cached handshake is discarded if the connection is lost or the client is rejected, so that the next connection waits for the handshake again.
+    fn response(&mut self) -> Result<(), Error> {
+        let res = self.receive().inspect_err(|_| forget_handshake())?;
+        match res {
+            Response::Ok => Ok(()),
+            res => {
+                if let Response::Error {
+                    kind:
+                        ErrorKind::Unauthorized | ErrorKind::Protocol | ErrorKind::UnknownOperation,
+                    ..
+                } = res
+                {
+                    forget_handshake();
+                }
+                Err(Error::from_response(res))
+            }
+        }
+    }
+
     /// Posts binary of ArrayObject.
-    pub fn post_raw(
+    pub fn post_raw(&mut self, title: &str, tag: &str, binary: Vec<u8>) -> Result<(), Error> {
+        self.post_raw_with_attributes(title, tag, binary, &Attributes::new())
+    }
+
+    /// Posts binary of ArrayObject with attributes attached to the revision.
+    pub fn post_raw_with_attributes(
         &mut self,
         title: &str,
         tag: &str,
         binary: Vec<u8>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+        attributes: &Attributes,
+    ) -> Result<(), Error> {
         let val = serde_bytes::ByteBuf::from(binary);
         let mut buffer = Cursor::new(vec![]);
-        ciborium::into_writer(&Operation::Post, &mut buffer)?;
-        ciborium::into_writer(&(title.to_string(), tag.to_string(), val), &mut buffer)?;
+        let acknowledge = Self::request(Operation::Post, &mut buffer)?;
+        ciborium::into_writer(&(title, tag, val, attributes), &mut buffer)?;
         self.send(buffer)?;
+        self.acknowledged(acknowledge)?;
         Ok(())
     }
 
@@ -136,7 +407,7 @@ impl TcpOrUnixStream {
         title: &str,
         tag: Option<&str>,
         revisions: Vec<u64>,
-    ) -> Result<Vec<Vec<u8>>, Box<dyn std::error::Error>> {
+    ) -> Result<Vec<Vec<u8>>, Error> {
         let mut list = vec![];
         let mut buffer = Cursor::new(vec![]);
         ciborium::into_writer(&Operation::Read, &mut buffer)?;
@@ -151,30 +422,141 @@ impl TcpOrUnixStream {
         self.send(buffer)?;
 
         for _ in 0..revisions.len().max(1) {
-            let res = self.receive()?;
-            let binary = match res {
-                Response::Ok => {
-                    let val: ByteBuf = self.receive()?;
-                    val.to_vec()
-                }
-                Response::NotFound => {
-                    return Err(Box::new(std::io::Error::new(
-                        std::io::ErrorKind::NotFound,
-                        "Not found.",
-                    )));
-                }
-                Response::NotUnique(list) => {
-                    return Err(Box::new(std::io::Error::new(
-                        std::io::ErrorKind::Other,
-                        format!("Multiple data found: {}", list.join(", ")),
-                    )));
-                }
-            };
+            self.response()?;
+            let val: ByteBuf = self.receive()?;
+            let binary = val.to_vec();
             list.push(binary);
         }
         Ok(list)
     }
 
+    /// Posts binary of ArrayObject read from `reader` in chunks, so that large data is not buffered as a whole. The data is posted when the reader reaches the end.
+    ///
+    /// If the reader fails, the connection is left in the middle of the operation and must be dropped.
+    pub fn post_stream(
+        &mut self,
+        title: &str,
+        tag: &str,
+        attributes: &Attributes,
+        mut reader: impl Read,
+    ) -> Result<(), Error> {
+        let mut buffer = Cursor::new(vec![]);
+        let acknowledge = Self::request(Operation::PostStream, &mut buffer)?;
+        ciborium::into_writer(&(title, tag, attributes), &mut buffer)?;
+        self.send(buffer)?;
+        self.send_chunks(&mut reader)?;
+        self.acknowledged(acknowledge)?;
+        Ok(())
+    }
+
+    /// Posts the arrays in a `.npy`, `.npz` or CSV file read from `reader`. The format is told by the extension of `filename`. The arrays in an NPZ file are posted as `title/name`.
+    ///
+    /// If the reader fails, the connection is left in the middle of the operation and must be dropped.
+    pub fn import(
+        &mut self,
+        title: &str,
+        tag: &str,
+        filename: &str,
+        mut reader: impl Read,
+    ) -> Result<(), Error> {
+        let mut buffer = Cursor::new(vec![]);
+        let acknowledge = Self::request(Operation::Import, &mut buffer)?;
+        ciborium::into_writer(&(title, tag, filename), &mut buffer)?;
+        self.send(buffer)?;
+        self.send_chunks(&mut reader)?;
+        self.acknowledged(acknowledge)?;
+        Ok(())
+    }
+
+    fn send_chunks(&mut self, reader: &mut impl Read) -> Result<(), Error> {
+        let mut chunk = vec![0; CHUNK_SIZE];
+        loop {
+            let size = match reader.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(size) => size,
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into()),
+            };
+            ciborium::into_writer(serde_bytes::Bytes::new(&chunk[..size]), &mut *self)?;
+        }
+        ciborium::into_writer(serde_bytes::Bytes::new(&[]), &mut *self)?;
+        Ok(())
+    }
+
+    /// Reads binary of ArrayObject in chunks. When revision is None, the latest revision is returned.
+    ///
+    /// The data is read from the returned reader, which has to be read to the end before the next operation.
+    pub fn read_stream(
+        &mut self,
+        title: &str,
+        tag: Option<&str>,
+        revision: Option<u64>,
+    ) -> Result<BulletinReader<&mut Self>, Error> {
+        self.request_stream(title, tag, revision)?;
+        Ok(BulletinReader::new(self))
+    }
+
+    pub(crate) fn request_stream(
+        &mut self,
+        title: &str,
+        tag: Option<&str>,
+        revision: Option<u64>,
+    ) -> Result<(), Error> {
+        let mut buffer = Cursor::new(vec![]);
+        ciborium::into_writer(&Operation::ReadStream, &mut buffer)?;
+        ciborium::into_writer(
+            &(title.to_string(), tag.map(|x| x.to_string()), revision),
+            &mut buffer,
+        )?;
+        self.send(buffer)?;
+        self.response()?;
+        Ok(())
+    }
+
+    /// Subscribes to the changes of the board selected by the filter. The connection is dedicated to the returned iterator.
+    ///
+    /// If with_data is true, the data of each posted revision is attached.
+    pub fn watch(mut self, filter: WatchFilter, with_data: bool) -> Result<Watcher, Error> {
+        let mut buffer = Cursor::new(vec![]);
+        ciborium::into_writer(&Operation::Watch, &mut buffer)?;
+        ciborium::into_writer(&(filter, with_data), &mut buffer)?;
+        self.send(buffer)?;
+        self.response()?;
+        Ok(Watcher {
+            stream: self,
+            finished: false,
+        })
+    }
+
+    /// Reads the latest revision of a bulletin, waiting until the bulletin exists or a revision newer than after_revision is posted. Returns the revision number and the binary of ArrayObject.
+    ///
+    /// Tag can be None if there is only one tag exists for the title.
+    pub fn wait_read(
+        &mut self,
+        title: &str,
+        tag: Option<&str>,
+        after_revision: Option<u64>,
+        timeout: Duration,
+    ) -> Result<(u64, Vec<u8>), Error> {
+        let timeout = u64::try_from(timeout.as_millis()).unwrap_or(u64::MAX);
+        let mut buffer = Cursor::new(vec![]);
+        ciborium::into_writer(&Operation::WaitRead, &mut buffer)?;
+        ciborium::into_writer(
+            &(
+                title.to_string(),
+                tag.map(|x| x.to_string()),
+                after_revision,
+                timeout,
+            ),
+            &mut buffer,
+        )?;
+        self.send(buffer)?;
+        self.response()?;
+        let revision: u64 = self.receive()?;
+        let val: ByteBuf = self.receive()?;
+        Ok((revision, val.to_vec()))
+    }
+
     /// Relabels a bulletin.
     pub fn relabel(
         &mut self,
@@ -182,9 +564,9 @@ impl TcpOrUnixStream {
         tag_from: Option<&str>,
         title_to: Option<&str>,
         tag_to: Option<&str>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    ) -> Result<(), Error> {
         let mut buffer = Cursor::new(vec![]);
-        ciborium::into_writer(&Operation::Relabel, &mut buffer)?;
+        let acknowledge = Self::request(Operation::Relabel, &mut buffer)?;
         ciborium::into_writer(
             &(
                 title_from.to_string(),
@@ -195,55 +577,82 @@ impl TcpOrUnixStream {
             &mut buffer,
         )?;
         self.send(buffer)?;
+        self.acknowledged(acknowledge)?;
+        Ok(())
+    }
+
+    /// Sets the retention policy of a bulletin. The policy applies to all the tags of the title if the tag is None, and is removed if the retention is None.
+    pub fn set_retention(
+        &mut self,
+        title: &str,
+        tag: Option<&str>,
+        retention: Option<&Retention>,
+    ) -> Result<(), Error> {
+        let mut buffer = Cursor::new(vec![]);
+        let acknowledge = Self::request(Operation::SetRetention, &mut buffer)?;
+        ciborium::into_writer(&(title, tag, retention), &mut buffer)?;
+        self.send(buffer)?;
+        self.acknowledged(acknowledge)?;
         Ok(())
     }
 
     /// Returns the version of the server.
-    pub fn server_version(&mut self) -> Result<String, Box<dyn std::error::Error>> {
+    pub fn server_version(&mut self) -> Result<String, Error> {
         let mut buffer = Cursor::new(vec![]);
         ciborium::into_writer(&Operation::Version, &mut buffer)?;
         self.send(buffer)?;
+        self.response()?;
         let version: String = self.receive()?;
         Ok(version)
     }
 
     /// Returns the status of the server.
     ///
-    /// The return values are (total datasize (bytes), memory used (bytes), memory used (%), the number of objects, the number of objects backed by files, the number of archived objects)
+    /// The return values are (total datasize (bytes), memory used (bytes), memory used (%), the number of objects, the number of objects backed by files, the number of archived objects, the number of objects evicted to files, the number of objects promoted back to memory, the number of revisions cleared by the retention policies, the total size of the cleared revisions (bytes))
     ///
     /// The total datasize does not include the size of metadata such as timestamp.
-    pub fn status(&mut self) -> Result<(u64, u64, f64, u64, u64, u64), Box<dyn std::error::Error>> {
+    pub fn status(&mut self) -> Result<Status, Error> {
         let mut buffer = Cursor::new(vec![]);
         ciborium::into_writer(&Operation::Status, &mut buffer)?;
         self.send(buffer)?;
-        let status: (u64, u64, f64, u64, u64, u64) = self.receive()?;
+        self.response()?;
+        let status: Status = self.receive()?;
         Ok(status)
     }
 
     /// Returns the log of the server.
-    pub fn log(&mut self) -> Result<String, Box<dyn std::error::Error>> {
+    pub fn log(&mut self) -> Result<String, Error> {
         let mut buffer = Cursor::new(vec![]);
         ciborium::into_writer(&Operation::Log, &mut buffer)?;
         self.send(buffer)?;
+        self.response()?;
         let log: String = self.receive()?;
         Ok(log)
     }
 
     /// Returns the list of the bulletins.
-    pub fn view_board(&mut self) -> Result<Vec<(String, String, u64)>, Box<dyn std::error::Error>> {
+    pub fn view_board(&mut self) -> Result<Vec<(String, String, u64)>, Error> {
         let mut buffer = Cursor::new(vec![]);
         ciborium::into_writer(&Operation::ViewBoard, &mut buffer)?;
         self.send(buffer)?;
+        self.response()?;
         let list: Vec<(String, String, u64)> = self.receive()?;
         Ok(list)
     }
 
-    /// Returns the details of a bulletin. The return values are a vector of (revision number, datasize (bytes), timestamp, backend).
-    pub fn get_info(
-        &mut self,
-        title: &str,
-        tag: Option<&str>,
-    ) -> Result<Vec<(u64, u64, String, String)>, Box<dyn std::error::Error>> {
+    /// Returns the bulletins selected by the query.
+    pub fn query_board(&mut self, query: &Query) -> Result<QueryPage, Error> {
+        let mut buffer = Cursor::new(vec![]);
+        ciborium::into_writer(&Operation::Query, &mut buffer)?;
+        ciborium::into_writer(query, &mut buffer)?;
+        self.send(buffer)?;
+        self.response()?;
+        let page: QueryPage = self.receive()?;
+        Ok(page)
+    }
+
+    /// Returns the details of a bulletin. The return values are a vector of (revision number, datasize (bytes), timestamp, backend, attributes).
+    pub fn get_info(&mut self, title: &str, tag: Option<&str>) -> Result<Vec<RevisionInfo>, Error> {
         let mut buffer = Cursor::new(vec![]);
         ciborium::into_writer(&Operation::GetInfo, &mut buffer)?;
         ciborium::into_writer(
@@ -251,65 +660,46 @@ impl TcpOrUnixStream {
             &mut buffer,
         )?;
         self.send(buffer)?;
-        let res = self.receive()?;
-        match res {
-            Response::Ok => {
-                let list: Vec<(u64, u64, String, String)> = self.receive()?;
-                Ok(list)
-            }
-            Response::NotFound => Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::NotFound,
-                "Not found.",
-            ))),
-            Response::NotUnique(list) => Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("Multiple data found: {}", list.join(", ")),
-            ))),
-        }
+        self.response()?;
+        let list: Vec<RevisionInfo> = self.receive()?;
+        Ok(list)
     }
 
-    /// Deletes specific revisions from a bulletin.
+    /// Deletes specific revisions from a bulletin. Revisions loaded from an archive cannot be cleared; see `delete_from_archive`.
     pub fn clear_revisions(
         &mut self,
         title: &str,
         tag: Option<&str>,
         revisions: Vec<u64>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    ) -> Result<(), Error> {
         let mut buffer = Cursor::new(vec![]);
-        ciborium::into_writer(&Operation::ClearRevisions, &mut buffer)?;
+        let acknowledge = Self::request(Operation::ClearRevisions, &mut buffer)?;
         ciborium::into_writer(
             &(title.to_string(), tag.map(|x| x.to_string()), revisions),
             &mut buffer,
         )?;
         self.send(buffer)?;
+        self.acknowledged(acknowledge)?;
         Ok(())
     }
 
     /// Removes all the revisions and the database entry of a bulletin.
-    pub fn remove(
-        &mut self,
-        title: &str,
-        tag: Option<&str>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn remove(&mut self, title: &str, tag: Option<&str>) -> Result<(), Error> {
         let mut buffer = Cursor::new(vec![]);
-        ciborium::into_writer(&Operation::Remove, &mut buffer)?;
+        let acknowledge = Self::request(Operation::Remove, &mut buffer)?;
         ciborium::into_writer(
             &(title.to_string(), tag.map(|x| x.to_string())),
             &mut buffer,
         )?;
         self.send(buffer)?;
+        self.acknowledged(acknowledge)?;
         Ok(())
     }
 
     /// Moves a bulletin to a persistent archive.
-    pub fn archive(
-        &mut self,
-        acv_name: &str,
-        title: &str,
-        tag: Option<&str>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn archive(&mut self, acv_name: &str, title: &str, tag: Option<&str>) -> Result<(), Error> {
         let mut buffer = Cursor::new(vec![]);
-        ciborium::into_writer(&Operation::Archive, &mut buffer)?;
+        let acknowledge = Self::request(Operation::Archive, &mut buffer)?;
         ciborium::into_writer(
             &(
                 acv_name.to_string(),
@@ -319,88 +709,203 @@ impl TcpOrUnixStream {
             &mut buffer,
         )?;
         self.send(buffer)?;
+        self.acknowledged(acknowledge)?;
         Ok(())
     }
 
     /// Loads or reloads an archive. The data is directly read from the archive file and a suffix "acv_name:" is added to the tag.
-    pub fn load(&mut self, acv_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn load(&mut self, acv_name: &str) -> Result<(), Error> {
+        let mut buffer = Cursor::new(vec![]);
+        let acknowledge = Self::request(Operation::Load, &mut buffer)?;
+        ciborium::into_writer(&acv_name.to_string(), &mut buffer)?;
+        self.send(buffer)?;
+        self.acknowledged(acknowledge)?;
+        Ok(())
+    }
+
+    /// Loads the revisions of an archive selected by title, tag and revision range. The loaded keys matching the selection are replaced, and the others are kept.
+    pub fn load_selected(&mut self, acv_name: &str, selection: &Selection) -> Result<(), Error> {
+        let mut buffer = Cursor::new(vec![]);
+        let acknowledge = Self::request(Operation::LoadSelected, &mut buffer)?;
+        ciborium::into_writer(&(acv_name, selection), &mut buffer)?;
+        self.send(buffer)?;
+        self.acknowledged(acknowledge)?;
+        Ok(())
+    }
+
+    /// Removes the bulletins loaded from an archive. The other bulletins are kept.
+    pub fn unload(&mut self, acv_name: &str) -> Result<(), Error> {
         let mut buffer = Cursor::new(vec![]);
-        ciborium::into_writer(&Operation::Load, &mut buffer)?;
+        let acknowledge = Self::request(Operation::Unload, &mut buffer)?;
         ciborium::into_writer(&acv_name.to_string(), &mut buffer)?;
         self.send(buffer)?;
+        self.acknowledged(acknowledge)?;
         Ok(())
     }
 
+    /// Shows the list of loaded archives.
+    pub fn list_loaded(&mut self) -> Result<Vec<String>, Error> {
+        let mut buffer = Cursor::new(vec![]);
+        ciborium::into_writer(&Operation::ListLoaded, &mut buffer)?;
+        self.send(buffer)?;
+        self.response()?;
+        let list: Vec<String> = self.receive()?;
+        Ok(list)
+    }
+
+    /// Reads the metadata of an archive without loading it.
+    pub fn archive_info(&mut self, acv_name: &str) -> Result<ArchiveInfo, Error> {
+        let mut buffer = Cursor::new(vec![]);
+        ciborium::into_writer(&Operation::ArchiveInfo, &mut buffer)?;
+        ciborium::into_writer(&acv_name, &mut buffer)?;
+        self.send(buffer)?;
+        self.response()?;
+        let info: ArchiveInfo = self.receive()?;
+        Ok(info)
+    }
+
+    /// Exports an archive, or the board if acv_name is None, to NPZ and JSON. The JSON manifest is returned together with a reader of the NPZ file.
+    ///
+    /// The reader has to be read to the end before the next operation.
+    pub fn export(
+        &mut self,
+        acv_name: Option<&str>,
+    ) -> Result<(String, BulletinReader<&mut Self>), Error> {
+        let mut buffer = Cursor::new(vec![]);
+        ciborium::into_writer(&Operation::Export, &mut buffer)?;
+        ciborium::into_writer(&acv_name, &mut buffer)?;
+        self.send(buffer)?;
+        self.response()?;
+        let manifest: String = self.receive()?;
+        Ok((manifest, BulletinReader::new(self)))
+    }
+
     /// Shows the list of archive.
-    pub fn list_archive(&mut self) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    pub fn list_archive(&mut self) -> Result<Vec<String>, Error> {
         let mut buffer = Cursor::new(vec![]);
         ciborium::into_writer(&Operation::ListArchive, &mut buffer)?;
         self.send(buffer)?;
+        self.response()?;
         let list: Vec<String> = self.receive()?;
         Ok(list)
     }
 
-    /// Renames an archive. This will be applied after calling reset_server.
-    pub fn rename_archive(
-        &mut self,
-        name_from: &str,
-        name_to: &str,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    /// Renames an archive. It fails if the archive is loaded.
+    pub fn rename_archive(&mut self, name_from: &str, name_to: &str) -> Result<(), Error> {
         let mut buffer = Cursor::new(vec![]);
-        ciborium::into_writer(&Operation::RenameArchive, &mut buffer)?;
+        let acknowledge = Self::request(Operation::RenameArchive, &mut buffer)?;
         ciborium::into_writer(&(name_from.to_string(), name_to.to_string()), &mut buffer)?;
         self.send(buffer)?;
+        self.acknowledged(acknowledge)?;
         Ok(())
     }
 
-    /// Deletes an archive. This will be applied after after calling reset_server.
-    pub fn delete_archive(&mut self, acv_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    /// Deletes an archive. It fails if the archive is loaded.
+    pub fn delete_archive(&mut self, acv_name: &str) -> Result<(), Error> {
         let mut buffer = Cursor::new(vec![]);
-        ciborium::into_writer(&Operation::DeleteArchive, &mut buffer)?;
+        let acknowledge = Self::request(Operation::DeleteArchive, &mut buffer)?;
         ciborium::into_writer(&acv_name.to_string(), &mut buffer)?;
         self.send(buffer)?;
+        self.acknowledged(acknowledge)?;
         Ok(())
     }
 
     /// Dumps all the unarchived data into an archive.
-    pub fn dump(&mut self, acv_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn dump(&mut self, acv_name: &str) -> Result<(), Error> {
         let mut buffer = Cursor::new(vec![]);
-        ciborium::into_writer(&Operation::Dump, &mut buffer)?;
+        let acknowledge = Self::request(Operation::Dump, &mut buffer)?;
         ciborium::into_writer(&acv_name.to_string(), &mut buffer)?;
         self.send(buffer)?;
+        self.acknowledged(acknowledge)?;
         Ok(())
     }
 
     /// Delete all the temporary data and restores data from an archive. Each data is copied to memory or a separate file. No suffix is added to the tag.
-    pub fn restore(&mut self, acv_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn restore(&mut self, acv_name: &str) -> Result<(), Error> {
+        let mut buffer = Cursor::new(vec![]);
+        let acknowledge = Self::request(Operation::Restore, &mut buffer)?;
+        ciborium::into_writer(&acv_name.to_string(), &mut buffer)?;
+        self.send(buffer)?;
+        self.acknowledged(acknowledge)?;
+        Ok(())
+    }
+
+    /// Restores the revisions of an archive selected by title, tag and revision range. If `merge` is true, the revisions are appended to the current board without resetting it.
+    pub fn restore_selected(
+        &mut self,
+        acv_name: &str,
+        selection: &Selection,
+        merge: bool,
+    ) -> Result<(), Error> {
+        let mut buffer = Cursor::new(vec![]);
+        let acknowledge = Self::request(Operation::RestoreSelected, &mut buffer)?;
+        ciborium::into_writer(&(acv_name, selection, merge), &mut buffer)?;
+        self.send(buffer)?;
+        self.acknowledged(acknowledge)?;
+        Ok(())
+    }
+
+    /// Checks an archive against its data. If `repair` is true, the index of a damaged archive is rewritten with the valid revisions.
+    pub fn verify_archive(&mut self, acv_name: &str, repair: bool) -> Result<ArchiveReport, Error> {
+        let mut buffer = Cursor::new(vec![]);
+        ciborium::into_writer(&Operation::VerifyArchive, &mut buffer)?;
+        ciborium::into_writer(&(acv_name, repair), &mut buffer)?;
+        self.send(buffer)?;
+        self.response()?;
+        let report: ArchiveReport = self.receive()?;
+        Ok(report)
+    }
+
+    /// Deletes revisions from an archive. All the tags are selected if `tag` is None, and all the revisions if `revisions` is None. The space is reclaimed by compact_archive.
+    pub fn delete_from_archive(
+        &mut self,
+        acv_name: &str,
+        title: &str,
+        tag: Option<&str>,
+        revisions: Option<Vec<u64>>,
+    ) -> Result<(), Error> {
+        let mut buffer = Cursor::new(vec![]);
+        let acknowledge = Self::request(Operation::DeleteFromArchive, &mut buffer)?;
+        ciborium::into_writer(&(acv_name, title, tag, revisions), &mut buffer)?;
+        self.send(buffer)?;
+        self.acknowledged(acknowledge)?;
+        Ok(())
+    }
+
+    /// Rewrites an archive without the deleted revisions to reclaim the space. Loaded revisions stay valid.
+    pub fn compact_archive(&mut self, acv_name: &str) -> Result<(), Error> {
         let mut buffer = Cursor::new(vec![]);
-        ciborium::into_writer(&Operation::Restore, &mut buffer)?;
+        let acknowledge = Self::request(Operation::CompactArchive, &mut buffer)?;
         ciborium::into_writer(&acv_name.to_string(), &mut buffer)?;
         self.send(buffer)?;
+        self.acknowledged(acknowledge)?;
         Ok(())
     }
 
     /// Clears the log file of the server.
-    pub fn clear_log(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn clear_log(&mut self) -> Result<(), Error> {
         let mut buffer = Cursor::new(vec![]);
-        ciborium::into_writer(&Operation::ClearLog, &mut buffer)?;
+        let acknowledge = Self::request(Operation::ClearLog, &mut buffer)?;
         self.send(buffer)?;
+        self.acknowledged(acknowledge)?;
         Ok(())
     }
 
     /// Resets and clears the data. The archived data is not affected, but must be loaded before use.
-    pub fn reset_server(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn reset_server(&mut self) -> Result<(), Error> {
         let mut buffer = Cursor::new(vec![]);
-        ciborium::into_writer(&Operation::Reset, &mut buffer)?;
+        let acknowledge = Self::request(Operation::Reset, &mut buffer)?;
         self.send(buffer)?;
+        self.acknowledged(acknowledge)?;
         Ok(())
     }
 
     /// Terminates the server.
-    pub fn terminate_server(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn terminate_server(&mut self) -> Result<(), Error> {
         let mut buffer = Cursor::new(vec![]);
-        ciborium::into_writer(&Operation::Terminate, &mut buffer)?;
+        let acknowledge = Self::request(Operation::Terminate, &mut buffer)?;
         self.send(buffer)?;
+        self.acknowledged(acknowledge)?;
         Ok(())
     }
 }
@@ -0,0 +1,80 @@
+#![cfg(not(feature = "dry_run"))]
+use std::time::Duration;
+
+use bulletin_board_client as bbclient;
+use bulletin_board_server::{BBServer, ServerOptions};
+
+fn read(title: &str, tag: &str) -> Result<Vec<f64>, bbclient::Error> {
+    Ok(bbclient::read(title, Some(tag), vec![])?
+        .pop()
+        .unwrap()
+        .try_into()
+        .unwrap())
+}
+
+#[test]
+fn test_archive() {
+    let dir = std::env::temp_dir().join(format!("bb_archive_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+
+    let mut opt = ServerOptions::new();
+    opt.set_listen_addr("127.0.0.1:7583".to_string());
+    opt.set_tmp_dir(path("tmp"));
+    opt.set_acv_dir(path("acv"));
+    opt.set_log_file(path("bulletin-board.log"));
+    opt.load_options();
+    let mut server = BBServer::new().unwrap();
+    let handle = std::thread::spawn(move || server.listen().unwrap());
+    std::thread::sleep(Duration::from_millis(200));
+
+    bbclient::set_addr("127.0.0.1:7583");
+    bbclient::set_timeout(Some(Duration::from_millis(1000)));
+    let data = vec![1f64, 2.];
+
+    bbclient::post("x", "tag", data.clone().into()).unwrap();
+    bbclient::archive("acv", "x", None).unwrap();
+    let bytes = std::fs::read(path("acv/acv/data.bin")).unwrap();
+
+    // An archive of version 1 is read without conversion.
+    std::fs::create_dir_all(path("acv/v1")).unwrap();
+    std::fs::write(path("acv/v1/data.bin"), &bytes).unwrap();
+    std::fs::write(path("acv/v1/version.txt"), "0.3.3").unwrap();
+    let mut meta = vec![];
+    ciborium::into_writer(&("x", "tag", 1u64), &mut meta).unwrap();
+    ciborium::into_writer(&(0u64, bytes.len() as u64, 0i64), &mut meta).unwrap();
+    std::fs::write(path("acv/v1/meta.bin"), meta).unwrap();
+    let report = bbclient::verify_archive("v1", false).unwrap();
+    assert_eq!(report.format_version, 1);
+    assert!(report.is_intact());
+    bbclient::load("v1").unwrap();
+    assert_eq!(read("x", "v1:tag").unwrap(), data);
+    bbclient::unload("v1").unwrap();
+
+    // A damaged archive is refused and the board is left as it is.
+    bbclient::post("y", "tag", data.clone().into()).unwrap();
+    let mut corrupted = bytes.clone();
+    *corrupted.last_mut().unwrap() ^= 0xff;
+    std::fs::write(path("acv/acv/data.bin"), corrupted).unwrap();
+    assert_eq!(
+        bbclient::verify_archive("acv", false)
+            .unwrap()
+            .corrupted
+            .len(),
+        1
+    );
+    assert!(bbclient::restore("acv").is_err());
+    assert_eq!(read("y", "tag").unwrap(), data);
+    std::fs::write(path("acv/acv/data.bin"), &bytes[..bytes.len() - 1]).unwrap();
+    assert!(bbclient::restore("acv").is_err());
+    assert_eq!(read("y", "tag").unwrap(), data);
+
+    std::fs::write(path("acv/acv/data.bin"), &bytes).unwrap();
+    bbclient::restore("acv").unwrap();
+    assert_eq!(read("x", "tag").unwrap(), data);
+    assert!(read("y", "tag").is_err());
+
+    bbclient::terminate_server().unwrap();
+    handle.join().unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+}
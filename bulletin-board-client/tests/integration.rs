@@ -1,5 +1,6 @@
 use std::time::Duration;
 
+use bbclient::Pack;
 use bulletin_board_client as bbclient;
 
 #[test]
@@ -21,14 +22,315 @@ fn test_all() {
         let restored: Vec<f64> = recv.try_into().unwrap();
         assert_eq!(data, restored);
     }
-    bbclient::clear_revisions("title", None, vec![0]).unwrap();
-    bbclient::remove("title", None).unwrap();
-    bbclient::post("title", "tag", data.clone().into()).unwrap();
+    bbclient::clear_revisions("new_title", None, vec![0]).unwrap();
+    bbclient::remove("new_title", None).unwrap();
+    #[cfg(not(feature = "dry_run"))]
+    {
+        assert!(matches!(
+            bbclient::remove("new_title", None),
+            Err(bbclient::Error::NotFound)
+        ));
+        assert!(matches!(
+            bbclient::read("new_title", Some("new_tag"), vec![]),
+            Err(bbclient::Error::NotFound)
+        ));
+        bbclient::post("title", "tag1", data.clone().into()).unwrap();
+        bbclient::post("title", "tag2", data.clone().into()).unwrap();
+        assert!(matches!(
+            bbclient::get_info("title", None),
+            Err(bbclient::Error::NotUnique(tags)) if tags.len() == 2
+        ));
+        bbclient::remove("title", Some("tag1")).unwrap();
+        bbclient::remove("title", Some("tag2")).unwrap();
+    }
+    let large: Vec<f64> = (0..1 << 18).map(|x| x as f64).collect();
+    let binary = bbclient::ArrayObject::from(large.clone()).pack_as_it_is();
+    bbclient::post_stream("large", "tag", binary.as_slice()).unwrap();
+    #[cfg(not(feature = "dry_run"))]
+    {
+        use std::io::Read;
+        let mut recv = vec![];
+        bbclient::read_stream("large", None, None)
+            .unwrap()
+            .read_to_end(&mut recv)
+            .unwrap();
+        assert_eq!(binary, recv);
+        let restored: Vec<f64> = bbclient::read("large", None, vec![])
+            .unwrap()
+            .pop()
+            .unwrap()
+            .try_into()
+            .unwrap();
+        assert_eq!(large, restored);
+    }
+    bbclient::remove("large", None).unwrap();
+    #[cfg(not(feature = "dry_run"))]
+    {
+        let mut watcher = bbclient::watch_prefix("watched", None, true).unwrap();
+        bbclient::post("watched", "tag", data.clone().into()).unwrap();
+        bbclient::post("ignored", "tag", data.clone().into()).unwrap();
+        bbclient::relabel("watched", None, None, Some("new_tag")).unwrap();
+        bbclient::remove("watched", None).unwrap();
+        bbclient::remove("ignored", None).unwrap();
+        let (event, obj) = watcher.next().unwrap().unwrap();
+        assert!(matches!(event, bbclient::Event::Posted { revision: 0, .. }));
+        let restored: Vec<f64> = obj.unwrap().try_into().unwrap();
+        assert_eq!(data, restored);
+        let (event, _) = watcher.next().unwrap().unwrap();
+        assert!(matches!(event, bbclient::Event::Relabeled { tag, .. } if tag == "new_tag"));
+        let (event, _) = watcher.next().unwrap().unwrap();
+        assert!(matches!(event, bbclient::Event::Removed { .. }));
+    }
+    #[cfg(not(feature = "dry_run"))]
+    {
+        let producer = std::thread::spawn({
+            let data = data.clone();
+            move || {
+                std::thread::sleep(Duration::from_millis(50));
+                bbclient::post("awaited", "tag", data.into()).unwrap();
+            }
+        });
+        let (revision, obj) =
+            bbclient::wait_read("awaited", None, None, Duration::from_secs(5)).unwrap();
+        producer.join().unwrap();
+        assert_eq!(revision, 0);
+        let restored: Vec<f64> = obj.try_into().unwrap();
+        assert_eq!(data, restored);
+        assert!(matches!(
+            bbclient::wait_read("awaited", None, Some(0), Duration::from_millis(50)),
+            Err(bbclient::Error::Timeout)
+        ));
+        bbclient::remove("awaited", None).unwrap();
+    }
+    #[cfg(not(feature = "dry_run"))]
+    {
+        use bbclient::{Pattern, Query, SortKey};
+        for (i, title) in ["query_b", "query_a", "query_c", "other"]
+            .iter()
+            .enumerate()
+        {
+            let values: Vec<f64> = vec![0.; i + 1];
+            bbclient::post(title, "tag", values.into()).unwrap();
+        }
+        let mut query = Query {
+            title: Some(Pattern::Glob("query_*".to_string())),
+            limit: Some(2),
+            ..Default::default()
+        };
+        let page = bbclient::query_board(&query).unwrap();
+        let titles: Vec<_> = page.bulletins.iter().map(|x| x.0.as_str()).collect();
+        assert_eq!(titles, ["query_a", "query_b"]);
+        query.after = page.next;
+        let page = bbclient::query_board(&query).unwrap();
+        assert_eq!(page.bulletins[0].0, "query_c");
+        assert!(page.next.is_none());
+        let query = Query {
+            title: Some(Pattern::Regex("query_[ab]|other".to_string())),
+            sort: SortKey::Size,
+            descending: true,
+            ..Default::default()
+        };
+        let page = bbclient::query_board(&query).unwrap();
+        let titles: Vec<_> = page.bulletins.iter().map(|x| x.0.as_str()).collect();
+        assert_eq!(titles, ["other", "query_a", "query_b"]);
+        let query = Query {
+            title: Some(Pattern::Regex("(".to_string())),
+            ..Default::default()
+        };
+        assert!(matches!(
+            bbclient::query_board(&query),
+            Err(bbclient::Error::Server {
+                kind: bbclient::ErrorKind::InvalidArgument,
+                ..
+            })
+        ));
+        for title in ["query_b", "query_a", "query_c", "other"] {
+            bbclient::remove(title, None).unwrap();
+        }
+    }
+    #[cfg(not(feature = "dry_run"))]
+    {
+        use bbclient::{Attributes, Pattern, Query};
+        let attributes = Attributes::from([
+            ("units".to_string(), "GeV".to_string()),
+            ("source".to_string(), "main.rs:10".to_string()),
+        ]);
+        bbclient::post_with_attributes("energy", "tag", data.clone().into(), &attributes).unwrap();
+        bbclient::post("energy", "tag", data.clone().into()).unwrap();
+        let info = bbclient::get_info("energy", None).unwrap();
+        assert_eq!(info[0].4, attributes);
+        assert!(info[1].4.is_empty());
+        let query = Query {
+            attributes: [("units".to_string(), Pattern::Glob("*eV".to_string()))].into(),
+            ..Default::default()
+        };
+        let page = bbclient::query_board(&query).unwrap();
+        assert_eq!(
+            page.bulletins,
+            [("energy".to_string(), "tag".to_string(), 2)]
+        );
+        bbclient::archive("acv_attributes", "energy", None).unwrap();
+        bbclient::load("acv_attributes").unwrap();
+        let info = bbclient::get_info("energy", Some("acv_attributes:tag")).unwrap();
+        assert_eq!(info[0].4, attributes);
+        let report = bbclient::verify_archive("acv_attributes", false).unwrap();
+        assert!(report.is_intact());
+        assert_eq!(report.valid, 2);
+        assert_eq!(report.orphaned_bytes, 0);
+        assert!(bbclient::clear_revisions("energy", Some("acv_attributes:tag"), vec![0]).is_err());
+        bbclient::delete_from_archive("acv_attributes", "energy", Some("tag"), Some(vec![0]))
+            .unwrap();
+        bbclient::compact_archive("acv_attributes").unwrap();
+        let report = bbclient::verify_archive("acv_attributes", false).unwrap();
+        assert!(report.is_intact());
+        assert_eq!(report.valid, 1);
+        assert_eq!(report.orphaned_bytes, 0);
+        let info = bbclient::get_info("energy", Some("acv_attributes:tag")).unwrap();
+        assert_eq!(info[0].3, "deleted");
+        let recv = bbclient::read("energy", Some("acv_attributes:tag"), vec![1])
+            .unwrap()
+            .pop()
+            .unwrap();
+        let restored: Vec<f64> = recv.try_into().unwrap();
+        assert_eq!(restored, data);
+        let acv_info = bbclient::archive_info("acv_attributes").unwrap();
+        assert_eq!(acv_info.n_keys, 1);
+        assert_eq!(acv_info.n_revisions, 1);
+        assert_eq!(acv_info.keys[0].0, "energy");
+        assert_eq!(acv_info.keys[0].2, 1);
+        assert_eq!(bbclient::list_loaded().unwrap(), ["acv_attributes"]);
+        assert!(bbclient::delete_archive("acv_attributes").is_err());
+        bbclient::unload("acv_attributes").unwrap();
+        assert!(bbclient::list_loaded().unwrap().is_empty());
+        assert!(bbclient::get_info("energy", Some("acv_attributes:tag")).is_err());
+        bbclient::delete_archive("acv_attributes").unwrap();
+    }
+    #[cfg(not(feature = "dry_run"))]
+    {
+        bbclient::post("relabeled", "tag", data.clone().into()).unwrap();
+        bbclient::archive("acv_relabeled", "relabeled", None).unwrap();
+        bbclient::load("acv_relabeled").unwrap();
+        bbclient::relabel("relabeled", Some("acv_relabeled:tag"), None, Some("moved")).unwrap();
+        assert!(bbclient::delete_archive("acv_relabeled").is_err());
+        assert!(bbclient::rename_archive("acv_relabeled", "acv_renamed").is_err());
+        assert_eq!(bbclient::list_loaded().unwrap(), ["acv_relabeled"]);
+        bbclient::unload("acv_relabeled").unwrap();
+        assert!(bbclient::get_info("relabeled", Some("moved")).is_err());
+        bbclient::delete_archive("acv_relabeled").unwrap();
+    }
+    #[cfg(not(feature = "dry_run"))]
+    {
+        let retention = bbclient::Retention {
+            max_revisions: Some(2),
+            ..Default::default()
+        };
+        bbclient::set_retention("retained", None, Some(&retention)).unwrap();
+        let status = bbclient::status().unwrap();
+        for _ in 0..4 {
+            bbclient::post("retained", "tag", data.clone().into()).unwrap();
+        }
+        let revisions: Vec<_> = bbclient::get_info("retained", None)
+            .unwrap()
+            .into_iter()
+            .map(|info| (info.0, info.3))
+            .collect();
+        assert_eq!(
+            revisions,
+            [(2, "memory".to_string()), (3, "memory".to_string())]
+        );
+        let count = bbclient::view_board()
+            .unwrap()
+            .into_iter()
+            .find_map(|(title, _, count)| (title == "retained").then_some(count));
+        assert_eq!(count, Some(4));
+        let retained: Vec<f64> = bbclient::read("retained", None, vec![3])
+            .unwrap()
+            .pop()
+            .unwrap()
+            .try_into()
+            .unwrap();
+        assert_eq!(retained, data);
+        let current = bbclient::status().unwrap();
+        assert_eq!(current.3, status.3 + 2);
+        assert_eq!(current.8, status.8 + 2);
+        // The cleared revisions are as large as the kept ones.
+        assert_eq!(current.9 - status.9, current.0 - status.0);
+        bbclient::set_retention("retained", None, None).unwrap();
+        bbclient::remove("retained", None).unwrap();
+    }
+    #[cfg(not(feature = "dry_run"))]
+    {
+        use bbclient::{Pattern, Selection};
+        bbclient::post("selected_a", "tag", data.clone().into()).unwrap();
+        bbclient::post("selected_a", "tag", data.clone().into()).unwrap();
+        bbclient::post("selected_b", "tag", data.clone().into()).unwrap();
+        bbclient::archive("acv_selected", "selected_a", None).unwrap();
+        bbclient::archive("acv_selected", "selected_b", None).unwrap();
+        let selection = Selection {
+            title: Some(Pattern::Glob("*_a".to_string())),
+            revisions: Some(1..2),
+            ..Default::default()
+        };
+        bbclient::load_selected("acv_selected", &selection).unwrap();
+        let info = bbclient::get_info("selected_a", Some("acv_selected:tag")).unwrap();
+        assert_eq!(info.len(), 1);
+        assert!(bbclient::get_info("selected_b", Some("acv_selected:tag")).is_err());
+        bbclient::post("current", "tag", data.clone().into()).unwrap();
+        let selection = Selection {
+            title: Some(Pattern::Glob("selected_b".to_string())),
+            ..Default::default()
+        };
+        bbclient::restore_selected("acv_selected", &selection, true).unwrap();
+        assert_eq!(bbclient::get_info("selected_b", None).unwrap().len(), 1);
+        assert_eq!(bbclient::get_info("current", None).unwrap().len(), 1);
+        bbclient::unload("acv_selected").unwrap();
+        bbclient::remove("selected_b", None).unwrap();
+        bbclient::remove("current", None).unwrap();
+        bbclient::delete_archive("acv_selected").unwrap();
+    }
+    {
+        // Unacknowledged operations are ordered only within a connection.
+        let mut stream = bbclient::low_level::TcpOrUnixStream::connect().unwrap();
+        let obj: bbclient::ArrayObject = data.clone().into();
+        bbclient::set_acknowledge(false);
+        stream.post_raw("title", "tag", obj.clone().pack()).unwrap();
+        stream.remove("title", None).unwrap();
+        bbclient::set_acknowledge(true);
+        stream.post_raw("title", "tag", obj.pack()).unwrap();
+    }
     bbclient::archive("acv", "title", None).unwrap();
-    bbclient::load("acv").unwrap();
     bbclient::rename_archive("acv", "acv2").unwrap();
+    bbclient::load("acv2").unwrap();
+    #[cfg(not(feature = "dry_run"))]
+    assert!(bbclient::rename_archive("acv2", "acv3").is_err());
     bbclient::reset_server().unwrap();
     bbclient::restore("acv2").unwrap();
+    #[cfg(not(feature = "dry_run"))]
+    {
+        let output = std::env::temp_dir().join("bb_export");
+        let output = output.to_str().unwrap();
+        for acv_name in [None, Some("acv2")] {
+            bbclient::export(acv_name, output).unwrap();
+            let manifest = std::fs::read_to_string(format!("{output}.json")).unwrap();
+            assert!(manifest.contains("\"key\": \"title/tag/0\""));
+            let npz = std::fs::read(format!("{output}.npz")).unwrap();
+            assert!(npz.starts_with(b"PK\x03\x04"));
+        }
+        std::fs::remove_file(format!("{output}.json")).unwrap();
+        std::fs::remove_file(format!("{output}.npz")).unwrap();
+        let csv = std::env::temp_dir().join("bb_import.csv");
+        std::fs::write(&csv, "# energy\nx\n1\n2.5\n\n3\n").unwrap();
+        bbclient::import("imported", "tag", csv.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&csv).unwrap();
+        let restored: Vec<f64> = bbclient::read("imported", None, vec![])
+            .unwrap()
+            .pop()
+            .unwrap()
+            .try_into()
+            .unwrap();
+        assert_eq!(restored, [1., 2.5, 3.]);
+        bbclient::remove("imported", None).unwrap();
+    }
     bbclient::delete_archive("acv2").unwrap();
     bbclient::reset_server().unwrap();
     dbg!(bbclient::client_version());
@@ -0,0 +1,125 @@
+#![cfg(not(feature = "dry_run"))]
+use std::path::Path;
+use std::process::{Child, Command};
+use std::time::Duration;
+
+use bulletin_board_client as bbclient;
+use bulletin_board_server::{BBServer, ServerOptions};
+
+/// Directory of the server, set when the test runs itself as the server to be killed.
+const SERVER_DIR: &str = "BB_JOURNAL_TEST_DIR";
+
+fn serve(dir: &Path) {
+    let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+    let mut opt = ServerOptions::new();
+    opt.set_listen_addr("127.0.0.1:7584".to_string());
+    opt.set_tmp_dir(path("tmp"));
+    opt.set_acv_dir(path("acv"));
+    opt.set_log_file(path("bulletin-board.log"));
+    opt.set_journal();
+    opt.load_options();
+    BBServer::new().unwrap().listen().unwrap();
+}
+
+/// Server process, which is killed when dropped.
+struct Server(Child);
+
+impl Server {
+    fn start(dir: &Path) -> Self {
+        let server = Self(
+            Command::new(std::env::current_exe().unwrap())
+                .args(["test_journal", "--exact", "--nocapture"])
+                .env(SERVER_DIR, dir)
+                .spawn()
+                .unwrap(),
+        );
+        for _ in 0..50 {
+            if bbclient::server_version().is_ok() {
+                return server;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        panic!("The server did not start.");
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+fn read(title: &str) -> Vec<f64> {
+    bbclient::read(title, None, vec![])
+        .unwrap()
+        .pop()
+        .unwrap()
+        .try_into()
+        .unwrap()
+}
+
+fn revisions(title: &str) -> Vec<u64> {
+    bbclient::get_info(title, None)
+        .unwrap()
+        .into_iter()
+        .map(|info| info.0)
+        .collect()
+}
+
+#[test]
+fn test_journal() {
+    if let Some(dir) = std::env::var_os(SERVER_DIR) {
+        serve(Path::new(&dir));
+        return;
+    }
+    let dir = std::env::temp_dir().join(format!("bb_journal_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    bbclient::set_addr("127.0.0.1:7584");
+    bbclient::set_timeout(Some(Duration::from_millis(1000)));
+
+    let server = Server::start(&dir);
+    bbclient::post("x", "tag", vec![1f64, 2.].into()).unwrap();
+    bbclient::post("y", "tag", vec![3f64].into()).unwrap();
+    bbclient::relabel("y", Some("tag"), Some("z"), None).unwrap();
+    assert!(bbclient::relabel("y", Some("tag"), Some("w"), None).is_err());
+    let retention = bbclient::Retention {
+        max_revisions: Some(2),
+        ..Default::default()
+    };
+    bbclient::set_retention("r", None, Some(&retention)).unwrap();
+    for i in 0..3 {
+        bbclient::post("r", "tag", vec![i as f64].into()).unwrap();
+    }
+    assert_eq!(revisions("r"), [1, 2]);
+    let expiring = bbclient::Retention {
+        max_age: Some(1),
+        ..Default::default()
+    };
+    bbclient::set_retention("a", None, Some(&expiring)).unwrap();
+    bbclient::post("a", "tag", vec![0f64].into()).unwrap();
+    bbclient::post("a", "tag", vec![1f64].into()).unwrap();
+    std::thread::sleep(Duration::from_millis(2500));
+
+    // The server is killed without terminating, and the board is recovered from the journal.
+    drop(server);
+    let server = Server::start(&dir);
+    assert_eq!(read("x"), [1., 2.]);
+    assert_eq!(read("z"), [3.]);
+    assert!(bbclient::get_info("y", None).is_err());
+    assert!(bbclient::get_info("w", None).is_err());
+    assert_eq!(read("r"), [2.]);
+    assert_eq!(revisions("r"), [1, 2]);
+    // The age of the revisions is not checked while recovering.
+    assert_eq!(revisions("a"), [0, 1]);
+
+    // The retention policy is recovered as well.
+    bbclient::post("r", "tag", vec![3f64].into()).unwrap();
+    assert_eq!(revisions("r"), [2, 3]);
+    bbclient::post("a", "tag", vec![2f64].into()).unwrap();
+    assert_eq!(revisions("a"), [2]);
+
+    bbclient::terminate_server().unwrap();
+    drop(server);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,65 @@
+#![cfg(not(feature = "dry_run"))]
+use std::time::Duration;
+
+use bulletin_board_client as bbclient;
+use bulletin_board_server::{BBServer, ServerOptions};
+
+fn read(title: &str) -> Vec<f64> {
+    bbclient::read(title, None, vec![])
+        .unwrap()
+        .pop()
+        .unwrap()
+        .try_into()
+        .unwrap()
+}
+
+#[test]
+fn test_memory() {
+    let dir = std::env::temp_dir().join(format!("bb_memory_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+
+    let mut opt = ServerOptions::new();
+    opt.set_listen_addr("127.0.0.1:7585".to_string());
+    opt.set_tmp_dir(path("tmp"));
+    opt.set_acv_dir(path("acv"));
+    opt.set_log_file(path("bulletin-board.log"));
+    opt.set_tot_mem_limit("1KiB".to_string());
+    opt.set_promote_reads(2);
+    opt.load_options();
+    let mut server = BBServer::new().unwrap();
+    let handle = std::thread::spawn(move || server.listen().unwrap());
+    std::thread::sleep(Duration::from_millis(200));
+
+    bbclient::set_addr("127.0.0.1:7585");
+    bbclient::set_timeout(Some(Duration::from_millis(1000)));
+
+    // Data that does not compress, so that two of them exceed the memory limit.
+    let large: Vec<f64> = (0..80).map(|i| (i as f64).sqrt()).collect();
+
+    // The least recently read bulletin is saved to a file to make room.
+    bbclient::post("a", "tag", large.clone().into()).unwrap();
+    assert_eq!(read("a"), large);
+    bbclient::post("b", "tag", large.clone().into()).unwrap();
+    let status = bbclient::status().unwrap();
+    assert_eq!((status.4, status.6, status.7), (1, 1, 0));
+    let info = bbclient::get_info("a", None).unwrap();
+    assert!(info[0].3.starts_with("file:"));
+
+    // A bulletin read often enough is moved back once there is room.
+    assert_eq!(read("a"), large);
+    assert_eq!(read("a"), large);
+    assert_eq!(bbclient::status().unwrap().7, 0);
+    bbclient::remove("b", None).unwrap();
+    assert_eq!(read("a"), large);
+    assert_eq!(read("a"), large);
+    let status = bbclient::status().unwrap();
+    assert_eq!((status.4, status.6, status.7), (0, 1, 1));
+    assert!(status.1 > 0);
+    assert_eq!(bbclient::get_info("a", None).unwrap()[0].3, "memory");
+    assert_eq!(read("a"), large);
+
+    bbclient::terminate_server().unwrap();
+    handle.join().unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,79 @@
+#![cfg(not(feature = "dry_run"))]
+use std::time::Duration;
+
+use bulletin_board_client as bbclient;
+use bulletin_board_server::{BBServer, ServerOptions};
+
+fn read(title: &str) -> Vec<f64> {
+    bbclient::read(title, None, vec![])
+        .unwrap()
+        .pop()
+        .unwrap()
+        .try_into()
+        .unwrap()
+}
+
+#[test]
+fn test_namespace() {
+    let dir = std::env::temp_dir().join(format!("bb_namespace_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+
+    let mut opt = ServerOptions::new();
+    opt.set_listen_addr("127.0.0.1:7581".to_string());
+    opt.set_tmp_dir(path("tmp"));
+    opt.set_acv_dir(path("acv"));
+    opt.set_log_file(path("bulletin-board.log"));
+    opt.set_tot_mem_limit("1KiB".to_string());
+    opt.load_options();
+    let mut server = BBServer::new().unwrap();
+    let handle = std::thread::spawn(move || server.listen().unwrap());
+    std::thread::sleep(Duration::from_millis(200));
+
+    bbclient::set_addr("127.0.0.1:7581");
+    bbclient::set_timeout(Some(Duration::from_millis(1000)));
+
+    bbclient::set_namespace(None);
+    bbclient::post("x", "tag", vec![1f64, 2.].into()).unwrap();
+
+    // Data that does not compress, so that two of them exceed the memory limit.
+    let large: Vec<f64> = (0..80).map(|i| (i as f64).sqrt()).collect();
+
+    bbclient::set_namespace(Some("alice"));
+    assert!(bbclient::view_board().unwrap().is_empty());
+    bbclient::post("x", "tag", vec![3f64].into()).unwrap();
+    assert_eq!(read("x"), [3.]);
+    bbclient::post("large", "tag", large.clone().into()).unwrap();
+    assert_eq!(bbclient::status().unwrap().4, 0);
+
+    // The memory limit is shared with alice, whose bulletins are not evicted.
+    bbclient::set_namespace(Some("bob"));
+    bbclient::post("x", "tag", large.into()).unwrap();
+    assert_eq!(bbclient::status().unwrap().4, 1);
+    bbclient::reset_server().unwrap();
+    assert!(bbclient::view_board().unwrap().is_empty());
+
+    bbclient::set_namespace(Some("alice"));
+    assert_eq!(read("x"), [3.]);
+    bbclient::dump("acv").unwrap();
+    let info = bbclient::archive_info("acv").unwrap();
+    assert_eq!(info.namespace.as_deref(), Some("alice"));
+
+    bbclient::set_namespace(None);
+    assert_eq!(read("x"), [1., 2.]);
+    assert_eq!(bbclient::status().unwrap().3, 1);
+
+    bbclient::set_namespace(Some("../x"));
+    assert!(matches!(
+        bbclient::view_board(),
+        Err(bbclient::Error::Server {
+            kind: bbclient::ErrorKind::InvalidArgument,
+            ..
+        })
+    ));
+
+    bbclient::set_namespace(None);
+    bbclient::terminate_server().unwrap();
+    handle.join().unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+}
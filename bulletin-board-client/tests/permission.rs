@@ -0,0 +1,87 @@
+#![cfg(not(feature = "dry_run"))]
+use std::time::Duration;
+
+use bulletin_board_client as bbclient;
+use bulletin_board_server::{BBServer, ServerOptions};
+
+fn denied<T>(result: Result<T, bbclient::Error>) -> bool {
+    matches!(
+        result,
+        Err(bbclient::Error::Server {
+            kind: bbclient::ErrorKind::PermissionDenied,
+            ..
+        })
+    )
+}
+
+#[test]
+fn test_permission() {
+    let dir = std::env::temp_dir().join(format!("bb_permission_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+    std::fs::write(
+        path("tokens"),
+        "# user:token:role\nreader:r-token:read-only\nwriter:w-token:writer\nadmin:a-token\n",
+    )
+    .unwrap();
+
+    let mut opt = ServerOptions::new();
+    opt.set_listen_addr("127.0.0.1:7580".to_string());
+    opt.set_tmp_dir(path("tmp"));
+    opt.set_acv_dir(path("acv"));
+    opt.set_log_file(path("bulletin-board.log"));
+    opt.set_token_file(path("tokens"));
+    opt.load_options();
+    let mut server = BBServer::new().unwrap();
+    let handle = std::thread::spawn(move || server.listen().unwrap());
+    std::thread::sleep(Duration::from_millis(200));
+
+    bbclient::set_addr("127.0.0.1:7580");
+    bbclient::set_timeout(Some(Duration::from_millis(1000)));
+    let data = vec![1f64, 2.];
+
+    bbclient::set_token(Some("w-token"));
+    bbclient::post("x", "tag", data.clone().into()).unwrap();
+    bbclient::post("y", "tag", data.clone().into()).unwrap();
+    bbclient::archive("acv", "y", None).unwrap();
+    assert!(denied(bbclient::delete_archive("acv")));
+    assert!(denied(bbclient::reset_server()));
+    assert!(denied(bbclient::restore("acv")));
+    let selection = bbclient::Selection::default();
+    assert!(denied(bbclient::restore_selected("acv", &selection, false)));
+    bbclient::restore_selected("acv", &selection, true).unwrap();
+    assert!(denied(bbclient::verify_archive("acv", true)));
+    assert!(bbclient::verify_archive("acv", false).unwrap().is_intact());
+    bbclient::load("acv").unwrap();
+    assert!(bbclient::clear_revisions("y", Some("acv:tag"), vec![0]).is_err());
+    bbclient::unload("acv").unwrap();
+
+    bbclient::set_token(Some("r-token"));
+    let restored: Vec<f64> = bbclient::read("x", None, vec![])
+        .unwrap()
+        .pop()
+        .unwrap()
+        .try_into()
+        .unwrap();
+    assert_eq!(data, restored);
+    assert_eq!(bbclient::list_archive().unwrap(), ["acv"]);
+    assert!(denied(bbclient::post("x", "tag", data.clone().into())));
+    assert!(denied(bbclient::remove("x", None)));
+    assert!(denied(bbclient::terminate_server()));
+    bbclient::set_namespace(Some("team"));
+    assert!(matches!(
+        bbclient::view_board(),
+        Err(bbclient::Error::NotFound)
+    ));
+    bbclient::set_token(Some("w-token"));
+    bbclient::post("x", "tag", data.clone().into()).unwrap();
+    bbclient::set_token(Some("r-token"));
+    assert_eq!(bbclient::view_board().unwrap().len(), 1);
+    bbclient::set_namespace(None);
+
+    bbclient::set_token(Some("a-token"));
+    bbclient::delete_archive("acv").unwrap();
+    bbclient::terminate_server().unwrap();
+    handle.join().unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+}
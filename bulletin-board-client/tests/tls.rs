@@ -0,0 +1,64 @@
+#![cfg(not(feature = "dry_run"))]
+use std::time::Duration;
+
+use bulletin_board_client as bbclient;
+use bulletin_board_server::{BBServer, ServerOptions};
+
+#[test]
+fn test_tls() {
+    let dir = std::env::temp_dir().join(format!("bb_tls_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+
+    let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    std::fs::write(path("cert.pem"), cert.cert.pem()).unwrap();
+    std::fs::write(path("key.pem"), cert.key_pair.serialize_pem()).unwrap();
+    let other = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    std::fs::write(path("other.pem"), other.cert.pem()).unwrap();
+
+    let mut opt = ServerOptions::new();
+    opt.set_listen_addr("127.0.0.1:7579".to_string());
+    opt.set_tmp_dir(path("tmp"));
+    opt.set_acv_dir(path("acv"));
+    opt.set_log_file(path("bulletin-board.log"));
+    opt.set_tls_cert(path("cert.pem"));
+    opt.set_tls_key(path("key.pem"));
+    opt.load_options();
+    let mut server = BBServer::new().unwrap();
+    let handle = std::thread::spawn(move || server.listen().unwrap());
+    std::thread::sleep(Duration::from_millis(200));
+
+    // A missing CA file is reported by the connection instead of a panic.
+    unsafe {
+        std::env::set_var("BB_TLS_CA", path("missing.pem"));
+    }
+    bbclient::set_addr("localhost:7579");
+    bbclient::set_timeout(Some(Duration::from_millis(1000)));
+    assert!(matches!(
+        bbclient::server_version(),
+        Err(bbclient::Error::Io(_))
+    ));
+
+    bbclient::set_tls_ca(None).unwrap();
+    assert!(bbclient::server_version().is_err());
+    bbclient::set_tls_ca(Some(&path("other.pem"))).unwrap();
+    assert!(matches!(
+        bbclient::server_version(),
+        Err(bbclient::Error::Io(_))
+    ));
+
+    bbclient::set_tls_ca(Some(&path("cert.pem"))).unwrap();
+    let data = vec![1f64, 2.];
+    bbclient::post("tls", "tag", data.clone().into()).unwrap();
+    let restored: Vec<f64> = bbclient::read("tls", None, vec![])
+        .unwrap()
+        .pop()
+        .unwrap()
+        .try_into()
+        .unwrap();
+    assert_eq!(data, restored);
+
+    bbclient::terminate_server().unwrap();
+    handle.join().unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+}
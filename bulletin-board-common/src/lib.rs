@@ -1,8 +1,82 @@
 //! # Common code for Bulletin Board
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+/// Version of the wire protocol. It is incremented when the format of the messages changes incompatibly.
+pub const PROTOCOL_VERSION: u32 = 4;
+
+/// The oldest protocol version that the server accepts in a handshake.
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
+/// Capability flags exchanged in [`Handshake`].
+pub mod capability {
+    /// Operations that modify data are acknowledged by [`crate::Response`].
+    pub const ACKNOWLEDGE: u64 = 1 << 0;
+    /// The handshake can be preceded by [`crate::Operation::NoReply`], in which case the server replies to it only if the client is rejected. A client that has negotiated with the server before can send its first operation without waiting for the handshake.
+    pub const QUIET_HANDSHAKE: u64 = 1 << 1;
+}
+
+/// First message exchanged on connect. The client sends [`Operation::Handshake`] followed by its handshake, and the server answers [`Response::Ok`] followed by the negotiated handshake, or [`Response::Error`] if the client is incompatible.
+///
+/// From protocol version 1, the reply to every operation starts with a [`Response`]. Operations that modify data are replied only if [`capability::ACKNOWLEDGE`] is negotiated.
+///
+/// From protocol version 2, [`Operation::Post`] and [`Operation::PostStream`] take the [`Attributes`] after the tag, and [`Operation::GetInfo`] replies them as the last element of each revision.
+///
+/// From protocol version 3, [`Operation::Status`] replies the numbers of bulletins evicted to files and promoted back to memory as well.
+///
+/// From protocol version 4, [`Operation::Status`] replies the number and the total size of revisions cleared by the retention policies as well.
+///
+/// Clients that do not send a handshake are treated as legacy clients without any capabilities.
+///
+/// If the server requires authentication, the client has to send a valid `token` in the handshake before any other operation. The server never sends the token back.
+///
+/// The `namespace` selects the board the connection works on. Without it, the connection uses the default namespace, which is the only one known to legacy clients.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Handshake {
+    pub protocol_version: u32,
+    pub capabilities: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+}
+
+impl Handshake {
+    pub fn new(capabilities: u64) -> Self {
+        Self {
+            protocol_version: PROTOCOL_VERSION,
+            capabilities,
+            token: None,
+            namespace: None,
+        }
+    }
+    /// Attaches the token authenticating the client.
+    pub fn with_token(mut self, token: Option<String>) -> Self {
+        self.token = token;
+        self
+    }
+    /// Selects the namespace of the board. `None` selects the default namespace.
+    pub fn with_namespace(mut self, namespace: Option<String>) -> Self {
+        self.namespace = namespace;
+        self
+    }
+    pub fn has(&self, capability: u64) -> bool {
+        self.capabilities & capability == capability
+    }
+}
+
+/// Key-value attributes attached to a revision at post time, such as the units, a description or the source location.
+pub type Attributes = BTreeMap<String, String>;
+
+/// Revision number, datasize (bytes), timestamp, backend and attributes of a revision, replied by [`Operation::GetInfo`].
+pub type RevisionInfo = (u64, u64, String, String, Attributes);
+
+/// Total datasize (bytes), memory used (bytes), memory used (%), number of objects, number of files, number of archived objects, numbers of bulletins evicted to files and promoted back to memory, and number and total size (bytes) of revisions cleared by the retention policies, replied by [`Operation::Status`].
+pub type Status = (u64, u64, f64, u64, u64, u64, u64, u64, u64, u64);
 
 /// Command sent to the server.
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Debug)]
 pub enum Operation {
     Post,
     Read,
@@ -24,18 +98,263 @@ pub enum Operation {
     ClearLog,
     Reset,
     Terminate,
+    /// Suppresses the acknowledgement of the next operation (fire-and-forget).
+    NoReply,
+    Handshake,
+    /// Posts data of unknown size. The title, the tag and the attributes are followed by chunks of the data, each of which is a byte string of at most [`CHUNK_SIZE`] bytes, and an empty byte string marks the end.
+    PostStream,
+    /// Reads a revision in chunks. After [`Response::Ok`], the data is sent in the same format as [`Operation::PostStream`], followed by a [`Response`] reporting whether the whole data was sent.
+    ReadStream,
+    /// Subscribes to the changes of the board. The [`WatchFilter`] and whether to attach the posted data are replied by [`Response::Ok`]. Then the server sends an [`Event`] followed by the posted data (an optional byte string) on every change until the connection is closed. The connection cannot be used for other operations.
+    Watch,
+    /// Reads the latest revision, waiting until the bulletin exists or a revision newer than the given one is posted. The title, the tag, the revision and the timeout in milliseconds are replied by [`Response::Ok`], the revision number and the data.
+    WaitRead,
+    /// Lists the bulletins selected by a [`Query`]. The query is replied by [`Response::Ok`] and a [`QueryPage`].
+    Query,
+    /// Sets the retention policy of a bulletin. The title, the tag (None for all the tags of the title) and the [`Retention`] (None to remove the policy) are acknowledged.
+    SetRetention,
+    /// Checks the revisions of an archive against its data. The archive name and whether to repair the archive are replied by [`Response::Ok`] and an [`ArchiveReport`]. Repairing rewrites the index so that it only contains the valid revisions.
+    VerifyArchive,
+    /// Deletes revisions from an archive. The archive name, the title, the tag (None for all the tags) and the revisions (None for all the revisions) are acknowledged. Only the index is updated, and the data is reclaimed by [`Operation::CompactArchive`].
+    DeleteFromArchive,
+    /// Rewrites the data of an archive without the deleted revisions. The archive name is acknowledged.
+    CompactArchive,
+    /// Removes the bulletins loaded from an archive, keeping the others. The archive name is acknowledged.
+    Unload,
+    /// Lists the loaded archives. The names are replied after [`Response::Ok`].
+    ListLoaded,
+    /// Loads the revisions of an archive selected by a [`Selection`]. The archive name and the selection are acknowledged. Only the loaded keys matching the selection are replaced.
+    LoadSelected,
+    /// Restores the revisions of an archive selected by a [`Selection`]. The archive name, the selection and whether to merge into the current board are acknowledged. Unless merged, the board is reset first as in [`Operation::Restore`].
+    RestoreSelected,
+    /// Reads the metadata of an archive without loading it. The archive name is replied by [`Response::Ok`] and an [`ArchiveInfo`].
+    ArchiveInfo,
+    /// Exports an archive, or the board if the archive name is None, to NPZ and JSON. [`Response::Ok`] is followed by the JSON manifest and the NPZ file in chunks as in [`Operation::ReadStream`].
+    Export,
+    /// Posts the arrays in a `.npy`, `.npz` or CSV file. The title, the tag and the file name, whose extension tells the format, are followed by the content of the file in chunks as in [`Operation::PostStream`]. The arrays in an NPZ file are posted with their names appended to the title as `title/name`. The title and the tag are acknowledged.
+    Import,
 }
 
+/// Maximum size of a chunk in [`Operation::PostStream`], [`Operation::ReadStream`] and [`Operation::Export`].
+pub const CHUNK_SIZE: usize = 1 << 16;
+
 /// Response from the server.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum Response {
     Ok,
     NotFound,
     NotUnique(Vec<String>),
+    Error { kind: ErrorKind, message: String },
 }
 
+/// Category of [`Response::Error`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// The request could not be parsed or the protocol is incompatible.
+    Protocol,
+    /// The operation is not known to the server.
+    UnknownOperation,
+    /// The bulletin or the archive does not exist.
+    NotFound,
+    /// The arguments of the operation are invalid.
+    InvalidArgument,
+    /// I/O failure on the server.
+    Io,
+    /// The awaited bulletin did not appear in time.
+    Timeout,
+    /// The client did not present a valid token.
+    Unauthorized,
+    /// The role of the client does not allow the operation.
+    PermissionDenied,
+    /// Any other failure.
+    Other,
+}
+
+#[allow(clippy::derivable_impls)]
 impl Default for Response {
     fn default() -> Self {
         Response::Ok
     }
 }
+
+/// Selects the bulletins notified by [`Operation::Watch`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WatchFilter {
+    /// Title, or the prefix of titles if `prefix` is true.
+    pub title: String,
+    /// Tag. All the tags are watched if it is None.
+    pub tag: Option<String>,
+    pub prefix: bool,
+}
+
+impl WatchFilter {
+    pub fn matches(&self, title: &str, tag: &str) -> bool {
+        let title_matches = if self.prefix {
+            title.starts_with(&self.title)
+        } else {
+            title == self.title
+        };
+        title_matches && self.tag.as_ref().is_none_or(|val| val == tag)
+    }
+    /// Relabeling is notified if either the source or the destination matches.
+    pub fn accepts(&self, event: &Event) -> bool {
+        match event {
+            Event::Posted { title, tag, .. } | Event::Removed { title, tag } => {
+                self.matches(title, tag)
+            }
+            Event::Relabeled {
+                title_from,
+                tag_from,
+                title,
+                tag,
+            } => self.matches(title_from, tag_from) || self.matches(title, tag),
+        }
+    }
+}
+
+/// Change of the board notified by [`Operation::Watch`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Event {
+    Posted {
+        title: String,
+        tag: String,
+        revision: u64,
+    },
+    Relabeled {
+        title_from: String,
+        tag_from: String,
+        title: String,
+        tag: String,
+    },
+    Removed {
+        title: String,
+        tag: String,
+    },
+}
+
+/// Limits on the revisions kept for a bulletin, set by [`Operation::SetRetention`] or globally on the server. When a revision is posted, older revisions exceeding any of the limits are cleared. The latest revision is always kept, and the revisions in archives are never cleared.
+///
+/// The policy of a title and a tag takes precedence over that of the title, which takes precedence over the global one.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Retention {
+    /// Maximum number of revisions.
+    pub max_revisions: Option<u64>,
+    /// Maximum age of revisions in seconds.
+    pub max_age: Option<u64>,
+    /// Maximum total size of revisions in bytes.
+    pub max_bytes: Option<u64>,
+}
+
+/// Pattern matched against the whole title or tag in [`Query`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Pattern {
+    /// Shell-style wildcard, where `*` matches any string, `?` matches any character and `[...]` matches a character class.
+    Glob(String),
+    /// Regular expression in the syntax of the `regex` crate.
+    Regex(String),
+}
+
+/// Sort key of [`Query`]. Ties are broken by the title and the tag.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortKey {
+    /// Title, then tag.
+    #[default]
+    Name,
+    /// Timestamp of the latest revision.
+    LastModified,
+    /// Total size of all the revisions.
+    Size,
+}
+
+/// Position in the listing right after the last bulletin of a [`QueryPage`]. Passing it back in [`Query::after`] returns the following page even if bulletins are added or removed in between.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Continuation {
+    pub key: u64,
+    pub title: String,
+    pub tag: String,
+}
+
+/// Request of [`Operation::Query`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Query {
+    /// Filter on titles. All the titles match if it is None.
+    pub title: Option<Pattern>,
+    /// Filter on tags. All the tags match if it is None.
+    pub tag: Option<Pattern>,
+    /// Filters on attributes. A bulletin matches if any of its revisions has all the attributes matching the patterns.
+    #[serde(default)]
+    pub attributes: BTreeMap<String, Pattern>,
+    pub sort: SortKey,
+    pub descending: bool,
+    /// Number of bulletins skipped, counted after [`Query::after`].
+    pub offset: u64,
+    /// Maximum number of bulletins returned. The server caps it at its own limit.
+    pub limit: Option<u64>,
+    /// Continuation token returned by the previous page.
+    pub after: Option<Continuation>,
+}
+
+/// Selects the revisions of an archive in [`Operation::LoadSelected`] and [`Operation::RestoreSelected`]. The default selects everything.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Selection {
+    /// Filter on titles. All the titles match if it is None.
+    pub title: Option<Pattern>,
+    /// Filter on tags as recorded in the archive, without the archive name. All the tags match if it is None.
+    pub tag: Option<Pattern>,
+    /// Range of revisions in the archive. The selected revisions are numbered from 0 on the board.
+    pub revisions: Option<Range<u64>>,
+}
+
+/// Reply of [`Operation::Query`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct QueryPage {
+    /// List of (title, tag, revisions).
+    pub bulletins: Vec<(String, String, u64)>,
+    /// Continuation token of the next page. It is None if this is the last page.
+    pub next: Option<Continuation>,
+}
+
+/// Reply of [`Operation::VerifyArchive`]. Revisions are listed as (title, tag, revision).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ArchiveReport {
+    /// Format version of the archive before repairing.
+    pub format_version: u32,
+    /// Number of revisions whose data is intact.
+    pub valid: u64,
+    /// Revisions whose data runs past the end of the data file.
+    pub truncated: Vec<(String, String, u64)>,
+    /// Revisions whose data does not match the checksum.
+    pub corrupted: Vec<(String, String, u64)>,
+    /// Number of bytes in the data file not used by any valid revision.
+    pub orphaned_bytes: u64,
+    /// Description of the damage if the index could not be read to the end. The revisions after the damage are lost.
+    pub broken_index: Option<String>,
+    /// Whether the index was rewritten.
+    pub repaired: bool,
+}
+
+/// Reply of [`Operation::ArchiveInfo`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ArchiveInfo {
+    pub format_version: u32,
+    /// Creation time of the archive. For format version 1, the time of the last update.
+    pub created: String,
+    /// Version of the server that created the archive.
+    pub server_version: String,
+    /// Namespace of the board the archive was created from. `None` for the default namespace.
+    #[serde(default)]
+    pub namespace: Option<String>,
+    /// Size of the data file in bytes, including the data of deleted revisions.
+    pub size: u64,
+    pub n_keys: u64,
+    pub n_revisions: u64,
+    /// List of (title, tag, revisions, bytes).
+    pub keys: Vec<(String, String, u64, u64)>,
+}
+
+impl ArchiveReport {
+    /// Whether the archive has no damage. Orphaned bytes do not count as damage.
+    pub fn is_intact(&self) -> bool {
+        self.truncated.is_empty() && self.corrupted.is_empty() && self.broken_index.is_none()
+    }
+}
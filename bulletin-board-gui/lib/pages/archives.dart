@@ -1,6 +1,7 @@
 import 'dart:io';
 import 'package:flutter/material.dart';
 import 'package:hive/hive.dart';
+import 'package:human_file_size/human_file_size.dart';
 import 'package:bulletin_board/messages/all.dart';
 import 'package:bulletin_board/common/enums.dart';
 
@@ -111,6 +112,18 @@ class ArchiveItem extends StatelessWidget {
                 style: nameStyle,
               ),
             ),
+            Tooltip(
+              message: "Info",
+              child: OutlinedButton(
+                onPressed: () => showDialog(
+                    context: context,
+                    builder: (context) => ArchiveDetails(name: name)),
+                child: const Icon(Icons.article),
+              ),
+            ),
+            const SizedBox(
+              width: 5,
+            ),
             Tooltip(
               message: "Load",
               child: OutlinedButton(
@@ -163,6 +176,75 @@ class ArchiveItem extends StatelessWidget {
   }
 }
 
+class ArchiveDetails extends StatelessWidget {
+  const ArchiveDetails({
+    super.key,
+    required this.name,
+  });
+
+  final String name;
+
+  @override
+  Widget build(BuildContext context) {
+    ReqArchiveInfo(acvName: name).sendSignalToRust();
+
+    return StreamBuilder(
+      stream: ResArchiveInfo.rustSignalStream,
+      builder: (context, snapshot) {
+        final received = snapshot.data;
+        String summary = '';
+        List<DataRow> table = [];
+        if (received != null && received.message.acvName == name) {
+          final info = received.message;
+          summary =
+              'Created: ${info.created}\nServer version: ${info.serverVersion} (format ${info.formatVersion})\nSize: ${humanFileSize(info.size.toInt())}, ${info.nKeys} keys, ${info.nRevisions} revisions';
+          table = info.keys.map((key) {
+            return DataRow(cells: [
+              DataCell(Text(key.title)),
+              DataCell(Text(key.tag)),
+              DataCell(Text('${key.revisions}')),
+              DataCell(Text(humanFileSize(key.bytes.toInt()))),
+            ]);
+          }).toList();
+        }
+
+        return Card(
+          child: Column(
+            children: [
+              const SizedBox(
+                height: 10,
+              ),
+              OutlinedButton(
+                  onPressed: () {
+                    Navigator.pop(context);
+                  },
+                  child: const Icon(Icons.close)),
+              Padding(
+                padding: const EdgeInsets.all(8.0),
+                child: Text(summary),
+              ),
+              Expanded(
+                child: SingleChildScrollView(
+                  scrollDirection: Axis.horizontal,
+                  child: SingleChildScrollView(
+                    scrollDirection: Axis.vertical,
+                    child: DataTable(columns: const [
+                      DataColumn(label: Text('Title')),
+                      DataColumn(label: Text('Tag')),
+                      DataColumn(label: Text('Revisions')),
+                      DataColumn(label: Text('Data size')),
+                    ], rows: table),
+                  ),
+                ),
+              )
+            ],
+          ),
+        );
+      },
+    );
+  }
+}
+
 class ArchiveRestore extends StatelessWidget {
   const ArchiveRestore({
     super.key,
@@ -214,7 +296,7 @@ class ArchiveRename extends StatelessWidget {
           mainAxisSize: MainAxisSize.min,
           children: [
             const Text(
-                'Choose a new name for the archive. The change will be applied after resetting the server.'),
+                'Choose a new name for the archive. A loaded archive cannot be renamed until the server is reset.'),
             TextField(
               decoration: const InputDecoration(hintText: 'New name'),
               controller: newName,
@@ -255,7 +337,7 @@ class ArchiveDelete extends StatelessWidget {
     return AlertDialog(
         title: Text('Delete: $name'),
         content: const Text(
-            'Pressing Ok will delete the archive from the server. The change will be applied after resetting the server.'),
+            'Pressing Ok will delete the archive from the server. A loaded archive cannot be deleted until the server is reset.'),
         actions: [
           TextButton(
               onPressed: () {
@@ -9,6 +9,15 @@ import 'package:human_file_size/human_file_size.dart';
 import 'package:bulletin_board/common/enums.dart';
 import 'package:bulletin_board/messages/all.dart';
 
+void requestBoard() {
+  final settings = Hive.box('settings');
+  ReqViewBoard(
+    titlePattern: settings.get('boardTitleFilter') ?? '',
+    tagPattern: settings.get('boardTagFilter') ?? '',
+    regex: settings.get('boardFilterRegex') ?? false,
+  ).sendSignalToRust();
+}
+
 class BoardPage extends StatelessWidget {
   const BoardPage({
     super.key,
@@ -16,19 +25,22 @@ class BoardPage extends StatelessWidget {
 
   @override
   Widget build(BuildContext context) {
-    ReqViewBoard().sendSignalToRust();
+    requestBoard();
     return StreamBuilder(
       stream: ResViewBoard.rustSignalStream,
       builder: (context, snapshot) {
         final received = snapshot.data;
         final List<ResBulletinItem> bulletins;
+        final bool truncated;
         if (received == null) {
           bulletins = [];
+          truncated = false;
         } else {
           bulletins = received.message.bulletins;
+          truncated = received.message.truncated;
         }
 
-        return BoardContents(bulletins: bulletins);
+        return BoardContents(bulletins: bulletins, truncated: truncated);
       },
     );
   }
@@ -38,9 +50,11 @@ class BoardContents extends StatefulWidget {
   const BoardContents({
     super.key,
     required this.bulletins,
+    required this.truncated,
   });
 
   final List<ResBulletinItem> bulletins;
+  final bool truncated;
 
   @override
   State<BoardContents> createState() => _BoardContentsState();
@@ -54,6 +68,11 @@ class _BoardContentsState extends State<BoardContents> {
         Expanded(
           child: BoardListing(bulletins: widget.bulletins),
         ),
+        if (widget.truncated)
+          const Padding(
+            padding: EdgeInsets.all(5),
+            child: Text('The list is truncated. Refine the filter.'),
+          ),
         BoardController(setState: () => setState(() {}))
       ],
     );
@@ -324,6 +343,9 @@ class BulletinInfo extends StatelessWidget {
               DataCell(Text(datasize)),
               DataCell(Text(i.timestamp)),
               DataCell(Text(i.backend)),
+              DataCell(Text(i.attributes.entries
+                  .map((e) => '${e.key}: ${e.value}')
+                  .join(', '))),
             ]);
           }).toList();
         }
@@ -350,6 +372,7 @@ class BulletinInfo extends StatelessWidget {
                       DataColumn(label: Text('Data size')),
                       DataColumn(label: Text('Timestamp')),
                       DataColumn(label: Text('Backend')),
+                      DataColumn(label: Text('Attributes')),
                     ], rows: table),
                   ),
                 ),
@@ -415,7 +438,7 @@ class BulletinRelabel extends StatelessWidget {
                   Navigator.pop(context);
                   Navigator.pop(context);
                   sleep(const Duration(milliseconds: 10));
-                  ReqViewBoard().sendSignalToRust();
+                  requestBoard();
                 }
               },
               child: const Text('Ok')),
@@ -469,7 +492,7 @@ class BulletinArchive extends StatelessWidget {
                   Navigator.pop(context);
                   Navigator.pop(context);
                   sleep(const Duration(milliseconds: 10));
-                  ReqViewBoard().sendSignalToRust();
+                  requestBoard();
                 }
               },
               child: const Text('Ok')),
@@ -509,7 +532,85 @@ class BulletinRemove extends StatelessWidget {
                   Navigator.pop(context);
                   Navigator.pop(context);
                   sleep(const Duration(milliseconds: 10));
-                  ReqViewBoard().sendSignalToRust();
+                  requestBoard();
+                }
+              },
+              child: const Text('Ok')),
+        ]);
+  }
+}
+
+class BoardFilter extends StatefulWidget {
+  const BoardFilter({super.key});
+
+  @override
+  State<BoardFilter> createState() => _BoardFilterState();
+}
+
+class _BoardFilterState extends State<BoardFilter> {
+  final settings = Hive.box('settings');
+  late final titleFilter =
+      TextEditingController(text: settings.get('boardTitleFilter') ?? '');
+  late final tagFilter =
+      TextEditingController(text: settings.get('boardTagFilter') ?? '');
+  late bool regex = settings.get('boardFilterRegex') ?? false;
+
+  @override
+  void dispose() {
+    titleFilter.dispose();
+    tagFilter.dispose();
+    super.dispose();
+  }
+
+  @override
+  Widget build(BuildContext context) {
+    return AlertDialog(
+        title: const Text('Filter'),
+        content: Column(
+          mainAxisSize: MainAxisSize.min,
+          children: [
+            const Text(
+                'Show only the bulletins matching the patterns, e.g. loss_*. Leave blank to show all.'),
+            TextField(
+              decoration: const InputDecoration(hintText: 'Title pattern'),
+              controller: titleFilter,
+            ),
+            TextField(
+              decoration: const InputDecoration(hintText: 'Tag pattern'),
+              controller: tagFilter,
+            ),
+            CheckboxListTile(
+              title: const Text('Regular expression'),
+              value: regex,
+              onChanged: (value) => setState(() => regex = value ?? false),
+            ),
+          ],
+        ),
+        actions: [
+          TextButton(
+              onPressed: () {
+                {
+                  Navigator.pop(context);
+                }
+              },
+              child: const Text('Cancel')),
+          TextButton(
+              onPressed: () {
+                {
+                  titleFilter.clear();
+                  tagFilter.clear();
+                  setState(() => regex = false);
+                }
+              },
+              child: const Text('Clear')),
+          TextButton(
+              onPressed: () {
+                {
+                  settings.put('boardTitleFilter', titleFilter.text);
+                  settings.put('boardTagFilter', tagFilter.text);
+                  settings.put('boardFilterRegex', regex);
+                  Navigator.pop(context);
+                  requestBoard();
                 }
               },
               child: const Text('Ok')),
@@ -587,10 +688,18 @@ class BoardController extends StatelessWidget {
             child: const Icon(Icons.remove),
           ),
           const SizedBox(width: 20),
+          FloatingActionButton(
+            tooltip: 'Filter',
+            onPressed: () => showDialog(
+                context: context,
+                builder: (context) => const BoardFilter()),
+            child: const Icon(Icons.filter_alt),
+          ),
+          const SizedBox(width: 3),
           FloatingActionButton(
             tooltip: 'Refresh',
             onPressed: () {
-              ReqViewBoard().sendSignalToRust();
+              requestBoard();
             },
             child: const Icon(Icons.refresh),
           ),
@@ -629,7 +738,7 @@ class BoardController extends StatelessWidget {
                                   ).sendSignalToRust();
                                   Navigator.pop(context);
                                   sleep(const Duration(milliseconds: 10));
-                                  ReqViewBoard().sendSignalToRust();
+                                  requestBoard();
                                 }
                               },
                               child: const Text('Ok')),
@@ -659,7 +768,7 @@ class BoardController extends StatelessWidget {
                                   ReqReset().sendSignalToRust();
                                   Navigator.pop(context);
                                   sleep(const Duration(milliseconds: 10));
-                                  ReqViewBoard().sendSignalToRust();
+                                  requestBoard();
                                 }
                               },
                               child: const Text('Ok')),
@@ -47,6 +47,8 @@ class ServerStatus extends StatelessWidget {
           final Int64 bulletins;
           final Int64 files;
           final Int64 archives;
+          final Int64 evicted;
+          final Int64 promoted;
 
           if (received == null) {
             totalDatasize = "-";
@@ -55,6 +57,8 @@ class ServerStatus extends StatelessWidget {
             bulletins = Int64.ZERO;
             files = Int64.ZERO;
             archives = Int64.ZERO;
+            evicted = Int64.ZERO;
+            promoted = Int64.ZERO;
           } else {
             totalDatasize =
                 humanFileSize(received.message.totalDatasize.toInt());
@@ -64,6 +68,8 @@ class ServerStatus extends StatelessWidget {
             bulletins = received.message.bulletins;
             files = received.message.files;
             archives = received.message.archives;
+            evicted = received.message.evicted;
+            promoted = received.message.promoted;
           }
 
           final sectionStyle = Theme.of(context).textTheme.bodyLarge!.copyWith(
@@ -90,6 +96,8 @@ class ServerStatus extends StatelessWidget {
                         DataColumn(label: Text('#items')),
                         DataColumn(label: Text('#files')),
                         DataColumn(label: Text('#archived items')),
+                        DataColumn(label: Text('#evicted')),
+                        DataColumn(label: Text('#promoted')),
                       ],
                       rows: [
                         DataRow(cells: [
@@ -99,6 +107,8 @@ class ServerStatus extends StatelessWidget {
                           DataCell(Text('$bulletins')),
                           DataCell(Text('$files')),
                           DataCell(Text('$archives')),
+                          DataCell(Text('$evicted')),
+                          DataCell(Text('$promoted')),
                         ]),
                       ]),
                 ),
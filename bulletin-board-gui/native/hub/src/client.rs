@@ -1,4 +1,5 @@
 use crate::messages::*;
+use bbclient::{Pattern, Query};
 use bulletin_board_client as bbclient;
 use bulletin_board_server::*;
 // use rinf::debug_print;
@@ -81,6 +82,9 @@ pub async fn status() {
             bulletins,
             files,
             archives,
+            evicted,
+            promoted,
+            ..,
         )) = bbclient::status()
         {
             ResStatus {
@@ -90,6 +94,8 @@ pub async fn status() {
                 bulletins,
                 files,
                 archives,
+                evicted,
+                promoted,
             }
             .send_signal_to_dart();
         }
@@ -107,11 +113,34 @@ pub async fn log() {
 
 pub async fn view_board() {
     let receiver = ReqViewBoard::get_dart_signal_receiver();
-    while let Some(_) = receiver.recv().await {
-        if let Ok(list) = bbclient::view_board() {
+    while let Some(req) = receiver.recv().await {
+        let ReqViewBoard {
+            title_pattern,
+            tag_pattern,
+            regex,
+        } = req.message;
+        let pattern = |val: String| {
+            if val.is_empty() {
+                None
+            } else if regex {
+                Some(Pattern::Regex(val))
+            } else {
+                Some(Pattern::Glob(val))
+            }
+        };
+        // Other tags of the same title may be hidden by the tag filter.
+        let tag_filtered = !tag_pattern.is_empty();
+        let query = Query {
+            title: pattern(title_pattern),
+            tag: pattern(tag_pattern),
+            ..Default::default()
+        };
+        if let Ok(page) = bbclient::query_board(&query) {
+            let list = page.bulletins;
             let mut require_tag = vec![];
             for elem in &list {
-                require_tag.push(list.iter().any(|x| x.0 == elem.0 && x.1 != elem.1))
+                require_tag
+                    .push(tag_filtered || list.iter().any(|x| x.0 == elem.0 && x.1 != elem.1))
             }
             let bulletins = list
                 .into_iter()
@@ -123,7 +152,11 @@ pub async fn view_board() {
                     require_tag: require_tag[i],
                 })
                 .collect();
-            ResViewBoard { bulletins }.send_signal_to_dart();
+            ResViewBoard {
+                bulletins,
+                truncated: page.next.is_some(),
+            }
+            .send_signal_to_dart();
         }
     }
 }
@@ -134,12 +167,15 @@ pub async fn get_info() {
         if let Ok(list) = bbclient::get_info(&req.message.title, Some(&req.message.tag)) {
             let info = list
                 .into_iter()
-                .map(|(revision, datasize, timestamp, backend)| ResBulletinInfo {
-                    revision,
-                    datasize,
-                    timestamp,
-                    backend,
-                })
+                .map(
+                    |(revision, datasize, timestamp, backend, attributes)| ResBulletinInfo {
+                        revision,
+                        datasize,
+                        timestamp,
+                        backend,
+                        attributes: attributes.into_iter().collect(),
+                    },
+                )
                 .collect();
             ResGetInfo { info }.send_signal_to_dart();
         }
@@ -181,6 +217,35 @@ pub async fn list_archive() {
     }
 }
 
+pub async fn archive_info() {
+    let receiver = ReqArchiveInfo::get_dart_signal_receiver();
+    while let Some(req) = receiver.recv().await {
+        if let Ok(info) = bbclient::archive_info(&req.message.acv_name) {
+            let keys = info
+                .keys
+                .into_iter()
+                .map(|(title, tag, revisions, bytes)| ResArchiveKey {
+                    title,
+                    tag,
+                    revisions,
+                    bytes,
+                })
+                .collect();
+            ResArchiveInfo {
+                acv_name: req.message.acv_name,
+                format_version: info.format_version,
+                created: info.created,
+                server_version: info.server_version,
+                size: info.size,
+                n_keys: info.n_keys,
+                n_revisions: info.n_revisions,
+                keys,
+            }
+            .send_signal_to_dart();
+        }
+    }
+}
+
 pub async fn rename_archive() {
     let receiver = ReqRenameArchive::get_dart_signal_receiver();
     while let Some(req) = receiver.recv().await {
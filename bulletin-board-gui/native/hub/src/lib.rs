@@ -31,6 +31,7 @@ async fn main() {
     tokio::task::spawn(client::archive());
     tokio::task::spawn(client::load());
     tokio::task::spawn(client::list_archive());
+    tokio::task::spawn(client::archive_info());
     tokio::task::spawn(client::rename_archive());
     tokio::task::spawn(client::delete_archive());
     tokio::task::spawn(client::dump());
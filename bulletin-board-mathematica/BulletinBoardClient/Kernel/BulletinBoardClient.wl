@@ -10,6 +10,10 @@ BeginPackage["Yshojihep`BulletinBoardClient`"];
 BBBuildLibrary::usage = "BBBuildLibrary[] builds the client library.";
 BBSetAddr::usage = "BBSetAddr[address] sets the address of the server.";
 BBSetTimeout::usage = "BBSetTimeout[timeout] sets timeout for TCP connections.";
+BBSetToken::usage = "BBSetToken[token] sets the token sent to the server on connect.";
+BBSetNamespace::usage = "BBSetNamespace[namespace] selects the namespace of the board.";
+BBSetTLSCA::usage = "BBSetTLSCA[caFile] enables TLS with the certificate of the server verified against the CA file.";
+BBSetAcknowledge::usage = "BBSetAcknowledge[True|False] enables or disables acknowledgement of operations by the server.";
 BBPost::usage = "BBPost[title, tag(optional), data] sends data to the server.";
 BBRead::usage = "BBRead[title, tag(optional), revisions(optional)] retrives data from the server.";
 BBRelabel::usage = "BBRelabel[titleFrom, tagFrom, titleTo, tagTo] relabels a bulletin.";
@@ -23,7 +27,10 @@ BBClearRevisions::usage = "BBClearRevisions[title, tag(optional), revisions] del
 BBRemove::usage = "BBRemove[title, tag(optional)] removes a bulletin.";
 BBArchive::usage = "BBArchive[title, tag(optional), archiveName] saves a bulletin into an archive.";
 BBLoad::usage = "BBLoad[archiveName] loads bulletins from an archive.";
+BBUnload::usage = "BBUnload[archiveName] removes the bulletins loaded from an archive.";
+BBListLoaded::usage = "BBListLoaded[] returns the list of loaded archives.";
 BBListArchive::usage = "BBListArchive[] returns the list of archives.";
+BBArchiveInfo::usage = "BBArchiveInfo[archiveName] returns the metadata of an archive without loading it.";
 BBRenameArchive::usage = "BBRenameArchive[archiveFrom, archiveTo] renames an archive.";
 BBDeleteArchive::usage = "BBDeleteArchive[archiveName] deletes an archive.";
 BBDump::usage = "BBDump[archiveName] saves all the bulletins into an archive.";
@@ -52,6 +59,10 @@ loadFuncitons:=Module[{lib,loader},
 	loader=lib[libraryPath];
 	BBSetAddr=loader["set_addr"];
 	BBSetTimeout=loader["set_timeout"];
+	BBSetToken=loader["set_token"];
+	BBSetNamespace=loader["set_namespace"];
+	BBSetTLSCA=loader["set_tls_ca"];
+	BBSetAcknowledgeRaw=loader["set_acknowledge"];
 	BBPostInteger=loader["post_integer"];
 	BBPostReal=loader["post_real"];
 	BBPostComplex=loader["post_complex"];
@@ -72,7 +83,10 @@ loadFuncitons:=Module[{lib,loader},
 	BBRemove=loader["remove"];
 	BBArchive=loader["archive"];
 	BBLoad=loader["load"];
+	BBUnload=loader["unload"];
+	BBListLoaded=loader["list_loaded"];
 	BBListArchive=loader["list_archive"];
+	BBArchiveInfoRaw=loader["archive_info"];
 	BBRenameArchive=loader["rename_archive"];
 	BBDeleteArchive=loader["delete_archive"];
 	BBDump=loader["dump"];
@@ -177,11 +191,16 @@ BBViewBoard[]:=Enclose[Module[{result=Confirm[BBViewBoardRaw[]]},
 
 
 BBGetInfo[input__]:=Enclose[Module[{result=Confirm[BBGetInfoRaw[input]]},
-<|"revision"->#[[1]],"datasize"->#[[2]],"timestamp"->#[[3]],"backend"->#[[4]]|>&/@result]]
+<|"revision"->#[[1]],"datasize"->#[[2]],"timestamp"->#[[3]],"backend"->#[[4]],"attributes"->#[[5]]|>&/@result]]
+
+
+BBArchiveInfo[archiveName_]:=Enclose[Module[{result=Confirm[BBArchiveInfoRaw[archiveName]]},
+<|"format_version"->result[[1]],"created"->result[[2]],"server_version"->result[[3]],"size"->result[[4]],"keys"->result[[5]],"revisions"->result[[6]],
+"contents"->(<|"title"->#[[1]],"tag"->#[[2]],"revisions"->#[[3]],"bytes"->#[[4]]|>&/@result[[7]]),"namespace"->result[[8]]|>]]
 
 
 BBStatus[]:=Enclose[Module[{result=Confirm[BBStatusRaw[]]},
-<|"datasize"->result[[1]],"memory_used"->result[[2]],"memory_used(%)"->result[[3]],"objects"->result[[4]],"files"->result[[5]],"archived"->result[[6]]|>]]
+<|"datasize"->result[[1]],"memory_used"->result[[2]],"memory_used(%)"->result[[3]],"objects"->result[[4]],"files"->result[[5]],"archived"->result[[6]],"evicted"->result[[7]],"promoted"->result[[8]],"retired"->result[[9]],"retired_bytes"->result[[10]]|>]]
 
 
 BBPost[title_,tag_,data_]:=Enclose[Switch[Head[data],
@@ -203,6 +222,9 @@ BBPost[title_,tag_,data_]:=Enclose[Switch[Head[data],
 BBPost[title_,data_]:=BBPost[title,"Mathematica",data]
 
 
+BBSetAcknowledge[acknowledge_]:=BBSetAcknowledgeRaw[Boole[acknowledge]]
+
+
 (* ::Section:: *)
 (*Package Footer*)
 
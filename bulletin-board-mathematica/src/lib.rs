@@ -31,6 +31,78 @@ fn set_timeout(link: &mut wstp::Link) {
     link.put_str("Server address updated").unwrap();
 }
 
+#[wll::export(wstp)]
+fn set_token(link: &mut wstp::Link) {
+    let argc = link.test_head("System`List").unwrap();
+    match argc {
+        0 => bulletin_board_client::set_token(None),
+        1 => {
+            let token = link.get_string().unwrap();
+            bulletin_board_client::set_token(Some(&token));
+        }
+        _ => panic!(),
+    };
+
+    link.put_str("Token updated").unwrap();
+}
+
+#[wll::export(wstp)]
+fn set_namespace(link: &mut wstp::Link) {
+    let argc = link.test_head("System`List").unwrap();
+    match argc {
+        0 => bulletin_board_client::set_namespace(None),
+        1 => {
+            let namespace = link.get_string().unwrap();
+            bulletin_board_client::set_namespace(Some(&namespace));
+        }
+        _ => panic!(),
+    };
+
+    link.put_str("Namespace updated").unwrap();
+}
+
+#[wll::export(wstp)]
+fn set_tls_ca(link: &mut wstp::Link) {
+    let argc = link.test_head("System`List").unwrap();
+    let result = match argc {
+        0 => bulletin_board_client::set_tls_ca(None),
+        1 => {
+            let ca_file = link.get_string().unwrap();
+            bulletin_board_client::set_tls_ca(Some(&ca_file))
+        }
+        _ => panic!(),
+    };
+
+    match result {
+        Ok(()) => link.put_str("TLS updated").unwrap(),
+        Err(err) => put_failure(link, err.to_string()),
+    }
+}
+
+#[wll::export(wstp)]
+fn set_acknowledge(link: &mut wstp::Link) {
+    assert_eq!(link.test_head("System`List").unwrap(), 1);
+    let acknowledge = link.get_i64().unwrap() != 0;
+    bulletin_board_client::set_acknowledge(acknowledge);
+    link.put_str("Acknowledgement updated").unwrap();
+}
+
+fn put_result(link: &mut wstp::Link, result: Result<(), bulletin_board_client::Error>) {
+    match result {
+        Ok(()) => link.put_str("Sent").unwrap(),
+        Err(err) => put_failure(link, err.to_string()),
+    }
+}
+
+fn put_failure(link: &mut wstp::Link, message: String) {
+    link.put_function("System`Failure", 2).unwrap();
+    link.put_str("BulletinBoardError").unwrap();
+    link.put_function("System`Association", 1).unwrap();
+    link.put_function("System`Rule", 2).unwrap();
+    link.put_str("MessageTemplate").unwrap();
+    link.put_str(&message).unwrap();
+}
+
 #[wll::export(wstp)]
 fn post_integer(link: &mut wstp::Link) {
     assert_eq!(link.test_head("System`List").unwrap(), 3);
@@ -38,8 +110,8 @@ fn post_integer(link: &mut wstp::Link) {
     let tag = link.get_string().unwrap();
     let val = link.get_i64().unwrap();
     let obj = val.try_into().unwrap();
-    bulletin_board_client::post(&title, &tag, obj).unwrap();
-    link.put_str("Sent").unwrap();
+    let result = bulletin_board_client::post(&title, &tag, obj);
+    put_result(link, result);
 }
 
 #[wll::export(wstp)]
@@ -49,8 +121,8 @@ fn post_real(link: &mut wstp::Link) {
     let tag = link.get_string().unwrap();
     let val = link.get_f64().unwrap();
     let obj = val.try_into().unwrap();
-    bulletin_board_client::post(&title, &tag, obj).unwrap();
-    link.put_str("Sent").unwrap();
+    let result = bulletin_board_client::post(&title, &tag, obj);
+    put_result(link, result);
 }
 
 #[wll::export(wstp)]
@@ -61,8 +133,8 @@ fn post_complex(link: &mut wstp::Link) {
     let re = link.get_f64().unwrap();
     let im = link.get_f64().unwrap();
     let obj = Pair(re, im).try_into().unwrap();
-    bulletin_board_client::post(&title, &tag, obj).unwrap();
-    link.put_str("Sent").unwrap();
+    let result = bulletin_board_client::post(&title, &tag, obj);
+    put_result(link, result);
 }
 
 #[wll::export(wstp)]
@@ -72,44 +144,44 @@ fn post_string(link: &mut wstp::Link) {
     let tag = link.get_string().unwrap();
     let val = link.get_string().unwrap();
     let obj = val.try_into().unwrap();
-    bulletin_board_client::post(&title, &tag, obj).unwrap();
-    link.put_str("Sent").unwrap();
+    let result = bulletin_board_client::post(&title, &tag, obj);
+    put_result(link, result);
 }
 
 #[wll::export(wstp)]
 fn post_integer_array(link: &mut wstp::Link) {
     assert_eq!(link.test_head("System`List").unwrap(), 3);
-    {
+    let result = {
         let title = link.get_string().unwrap();
         let tag = link.get_string().unwrap();
         let arr = link.get_i64_array().unwrap();
         let shape = arr.dimensions().into_iter().map(|&x| x as u64).collect();
         let data = arr.data().into_iter().copied().collect();
         let obj = VecShape(data, shape).try_into().unwrap();
-        bulletin_board_client::post(&title, &tag, obj).unwrap();
-    }
-    link.put_str("Sent").unwrap();
+        bulletin_board_client::post(&title, &tag, obj)
+    };
+    put_result(link, result);
 }
 
 #[wll::export(wstp)]
 fn post_real_array(link: &mut wstp::Link) {
     assert_eq!(link.test_head("System`List").unwrap(), 3);
-    {
+    let result = {
         let title = link.get_string().unwrap();
         let tag = link.get_string().unwrap();
         let arr = link.get_f64_array().unwrap();
         let shape = arr.dimensions().into_iter().map(|&x| x as u64).collect();
         let data = arr.data().into_iter().copied().collect();
         let obj = VecShape(data, shape).try_into().unwrap();
-        bulletin_board_client::post(&title, &tag, obj).unwrap();
-    }
-    link.put_str("Sent").unwrap();
+        bulletin_board_client::post(&title, &tag, obj)
+    };
+    put_result(link, result);
 }
 
 #[wll::export(wstp)]
 fn post_complex_array(link: &mut wstp::Link) {
     assert_eq!(link.test_head("System`List").unwrap(), 4);
-    {
+    let result = {
         let title = link.get_string().unwrap();
         let tag = link.get_string().unwrap();
         let (re, shape) = {
@@ -123,15 +195,15 @@ fn post_complex_array(link: &mut wstp::Link) {
             im_arr.data().into_iter().copied().collect()
         };
         let obj = VecVecShape(re, im, shape).try_into().unwrap();
-        bulletin_board_client::post(&title, &tag, obj).unwrap();
-    }
-    link.put_str("Sent").unwrap();
+        bulletin_board_client::post(&title, &tag, obj)
+    };
+    put_result(link, result);
 }
 
 #[wll::export(wstp)]
 fn post_string_array(link: &mut wstp::Link) {
     assert_eq!(link.test_head("System`List").unwrap(), 4);
-    {
+    let result = {
         let title = link.get_string().unwrap();
         let tag = link.get_string().unwrap();
         let len = link.test_head("System`List").unwrap();
@@ -148,9 +220,9 @@ fn post_string_array(link: &mut wstp::Link) {
             .map(|&x| x.try_into().unwrap())
             .collect();
         let obj = VecShape(data, shape).try_into().unwrap();
-        bulletin_board_client::post(&title, &tag, obj).unwrap();
-    }
-    link.put_str("Sent").unwrap();
+        bulletin_board_client::post(&title, &tag, obj)
+    };
+    put_result(link, result);
 }
 
 #[wll::export(wstp)]
@@ -207,7 +279,10 @@ fn read(link: &mut wstp::Link) {
         }
         _ => panic!(),
     };
-    let list = bulletin_board_client::read(&title, tag.as_deref(), revisions).unwrap();
+    let list = match bulletin_board_client::read(&title, tag.as_deref(), revisions) {
+        Ok(list) => list,
+        Err(err) => return put_failure(link, err.to_string()),
+    };
     if list.len() > 1 {
         link.put_function("System`List", list.len()).unwrap();
     }
@@ -298,8 +373,8 @@ fn relabel(link: &mut wstp::Link) {
         "" => None,
         _ => Some(tag_to.as_str()),
     };
-    bulletin_board_client::relabel(&title_from, tag_from, title_to, tag_to).unwrap();
-    link.put_str("Sent").unwrap();
+    let result = bulletin_board_client::relabel(&title_from, tag_from, title_to, tag_to);
+    put_result(link, result);
 }
 
 #[wll::export(wstp)]
@@ -319,15 +394,29 @@ fn server_version(link: &mut wstp::Link) {
 #[wll::export(wstp)]
 fn status(link: &mut wstp::Link) {
     assert_eq!(link.test_head("System`List").unwrap(), 0);
-    let (datasize, memory_used, memory_used_percent, n_bulletins, n_files, n_archives) =
-        bulletin_board_client::status().unwrap();
-    link.put_function("System`List", 6).unwrap();
+    let (
+        datasize,
+        memory_used,
+        memory_used_percent,
+        n_bulletins,
+        n_files,
+        n_archives,
+        n_evicted,
+        n_promoted,
+        n_retired,
+        bytes_retired,
+    ) = bulletin_board_client::status().unwrap();
+    link.put_function("System`List", 10).unwrap();
     link.put_i64(datasize.try_into().unwrap()).unwrap();
     link.put_i64(memory_used.try_into().unwrap()).unwrap();
     link.put_f64(memory_used_percent).unwrap();
     link.put_i64(n_bulletins.try_into().unwrap()).unwrap();
     link.put_i64(n_files.try_into().unwrap()).unwrap();
     link.put_i64(n_archives.try_into().unwrap()).unwrap();
+    link.put_i64(n_evicted.try_into().unwrap()).unwrap();
+    link.put_i64(n_promoted.try_into().unwrap()).unwrap();
+    link.put_i64(n_retired.try_into().unwrap()).unwrap();
+    link.put_i64(bytes_retired.try_into().unwrap()).unwrap();
 }
 
 #[wll::export(wstp)]
@@ -358,14 +447,24 @@ fn get_info(link: &mut wstp::Link) {
         2 => (link.get_string().unwrap(), Some(link.get_string().unwrap())),
         _ => panic!(),
     };
-    let info = bulletin_board_client::get_info(&title, tag.as_deref()).unwrap();
+    let info = match bulletin_board_client::get_info(&title, tag.as_deref()) {
+        Ok(info) => info,
+        Err(err) => return put_failure(link, err.to_string()),
+    };
     link.put_function("System`List", info.len()).unwrap();
-    for (revision, datasize, timestamp, backend) in info {
-        link.put_function("System`List", 4).unwrap();
+    for (revision, datasize, timestamp, backend, attributes) in info {
+        link.put_function("System`List", 5).unwrap();
         link.put_i64(revision.try_into().unwrap()).unwrap();
         link.put_i64(datasize.try_into().unwrap()).unwrap();
         link.put_str(&timestamp).unwrap();
         link.put_str(&backend).unwrap();
+        link.put_function("System`Association", attributes.len())
+            .unwrap();
+        for (key, val) in attributes {
+            link.put_function("System`Rule", 2).unwrap();
+            link.put_str(&key).unwrap();
+            link.put_str(&val).unwrap();
+        }
     }
 }
 
@@ -377,9 +476,9 @@ fn clear_revisions(link: &mut wstp::Link) {
         3 => (link.get_string().unwrap(), Some(link.get_string().unwrap())),
         _ => panic!(),
     };
-    if link.get_type().unwrap() == wstp::TokenType::Integer {
+    let result = if link.get_type().unwrap() == wstp::TokenType::Integer {
         let revision = link.get_i64().unwrap().try_into().unwrap();
-        bulletin_board_client::clear_revisions(&title, tag.as_deref(), vec![revision]).unwrap();
+        bulletin_board_client::clear_revisions(&title, tag.as_deref(), vec![revision])
     } else {
         let revisions = link
             .get_i64_array()
@@ -388,9 +487,9 @@ fn clear_revisions(link: &mut wstp::Link) {
             .into_iter()
             .map(|&x| x.try_into().unwrap())
             .collect();
-        bulletin_board_client::clear_revisions(&title, tag.as_deref(), revisions).unwrap();
-    }
-    link.put_str("Sent").unwrap();
+        bulletin_board_client::clear_revisions(&title, tag.as_deref(), revisions)
+    };
+    put_result(link, result);
 }
 
 #[wll::export(wstp)]
@@ -401,8 +500,8 @@ fn remove(link: &mut wstp::Link) {
         2 => (link.get_string().unwrap(), Some(link.get_string().unwrap())),
         _ => panic!(),
     };
-    bulletin_board_client::remove(&title, tag.as_deref()).unwrap();
-    link.put_str("Sent").unwrap();
+    let result = bulletin_board_client::remove(&title, tag.as_deref());
+    put_result(link, result);
 }
 
 #[wll::export(wstp)]
@@ -414,16 +513,34 @@ fn archive(link: &mut wstp::Link) {
         3 => (link.get_string().unwrap(), Some(link.get_string().unwrap())),
         _ => panic!(),
     };
-    bulletin_board_client::archive(&acv_name, &title, tag.as_deref()).unwrap();
-    link.put_str("Sent").unwrap();
+    let result = bulletin_board_client::archive(&acv_name, &title, tag.as_deref());
+    put_result(link, result);
 }
 
 #[wll::export(wstp)]
 fn load(link: &mut wstp::Link) {
     assert_eq!(link.test_head("System`List").unwrap(), 1);
     let acv_name = link.get_string().unwrap();
-    bulletin_board_client::load(&acv_name).unwrap();
-    link.put_str("Sent").unwrap();
+    let result = bulletin_board_client::load(&acv_name);
+    put_result(link, result);
+}
+
+#[wll::export(wstp)]
+fn unload(link: &mut wstp::Link) {
+    assert_eq!(link.test_head("System`List").unwrap(), 1);
+    let acv_name = link.get_string().unwrap();
+    let result = bulletin_board_client::unload(&acv_name);
+    put_result(link, result);
+}
+
+#[wll::export(wstp)]
+fn list_loaded(link: &mut wstp::Link) {
+    assert_eq!(link.test_head("System`List").unwrap(), 0);
+    let list = bulletin_board_client::list_loaded().unwrap();
+    link.put_function("System`List", list.len()).unwrap();
+    for name in list {
+        link.put_str(&name).unwrap();
+    }
 }
 
 #[wll::export(wstp)]
@@ -436,56 +553,82 @@ fn list_archive(link: &mut wstp::Link) {
     }
 }
 
+#[wll::export(wstp)]
+fn archive_info(link: &mut wstp::Link) {
+    assert_eq!(link.test_head("System`List").unwrap(), 1);
+    let acv_name = link.get_string().unwrap();
+    let info = match bulletin_board_client::archive_info(&acv_name) {
+        Ok(info) => info,
+        Err(err) => return put_failure(link, err.to_string()),
+    };
+    link.put_function("System`List", 8).unwrap();
+    link.put_i64(info.format_version.into()).unwrap();
+    link.put_str(&info.created).unwrap();
+    link.put_str(&info.server_version).unwrap();
+    link.put_i64(info.size.try_into().unwrap()).unwrap();
+    link.put_i64(info.n_keys.try_into().unwrap()).unwrap();
+    link.put_i64(info.n_revisions.try_into().unwrap()).unwrap();
+    link.put_function("System`List", info.keys.len()).unwrap();
+    for (title, tag, revisions, bytes) in info.keys {
+        link.put_function("System`List", 4).unwrap();
+        link.put_str(&title).unwrap();
+        link.put_str(&tag).unwrap();
+        link.put_i64(revisions.try_into().unwrap()).unwrap();
+        link.put_i64(bytes.try_into().unwrap()).unwrap();
+    }
+    link.put_str(&info.namespace.unwrap_or_default()).unwrap();
+}
+
 #[wll::export(wstp)]
 fn rename_archive(link: &mut wstp::Link) {
     assert_eq!(link.test_head("System`List").unwrap(), 2);
     let name_from = link.get_string().unwrap();
     let name_to = link.get_string().unwrap();
-    bulletin_board_client::rename_archive(&name_from, &name_to).unwrap();
-    link.put_str("Sent").unwrap();
+    let result = bulletin_board_client::rename_archive(&name_from, &name_to);
+    put_result(link, result);
 }
 
 #[wll::export(wstp)]
 fn delete_archive(link: &mut wstp::Link) {
     assert_eq!(link.test_head("System`List").unwrap(), 1);
     let acv_name = link.get_string().unwrap();
-    bulletin_board_client::delete_archive(&acv_name).unwrap();
-    link.put_str("Sent").unwrap();
+    let result = bulletin_board_client::delete_archive(&acv_name);
+    put_result(link, result);
 }
 
 #[wll::export(wstp)]
 fn dump(link: &mut wstp::Link) {
     assert_eq!(link.test_head("System`List").unwrap(), 1);
     let acv_name = link.get_string().unwrap();
-    bulletin_board_client::dump(&acv_name).unwrap();
-    link.put_str("Sent").unwrap();
+    let result = bulletin_board_client::dump(&acv_name);
+    put_result(link, result);
 }
 
 #[wll::export(wstp)]
 fn restore(link: &mut wstp::Link) {
     assert_eq!(link.test_head("System`List").unwrap(), 1);
     let acv_name = link.get_string().unwrap();
-    bulletin_board_client::restore(&acv_name).unwrap();
-    link.put_str("Sent").unwrap();
+    let result = bulletin_board_client::restore(&acv_name);
+    put_result(link, result);
 }
 
 #[wll::export(wstp)]
 fn clear_log(link: &mut wstp::Link) {
     assert_eq!(link.test_head("System`List").unwrap(), 0);
-    bulletin_board_client::clear_log().unwrap();
-    link.put_str("Sent").unwrap();
+    let result = bulletin_board_client::clear_log();
+    put_result(link, result);
 }
 
 #[wll::export(wstp)]
 fn reset_server(link: &mut wstp::Link) {
     assert_eq!(link.test_head("System`List").unwrap(), 0);
-    bulletin_board_client::reset_server().unwrap();
-    link.put_str("Sent").unwrap();
+    let result = bulletin_board_client::reset_server();
+    put_result(link, result);
 }
 
 #[wll::export(wstp)]
 fn terminate_server(link: &mut wstp::Link) {
     assert_eq!(link.test_head("System`List").unwrap(), 0);
-    bulletin_board_client::terminate_server().unwrap();
-    link.put_str("Sent").unwrap();
+    let result = bulletin_board_client::terminate_server();
+    put_result(link, result);
 }
@@ -1 +1 @@
-from .helper import set_addr, set_timeout, post, read, relabel, client_version, server_version, status, log, view_board, get_info, clear_revisions, remove, archive, load, list_archive, rename_archive, delete_archive, delete_archive, dump, restore, reset_server, terminate_server
\ No newline at end of file
+from .helper import set_addr, set_timeout, set_token, set_namespace, set_tls_ca, set_acknowledge, post, read, watch, set_retention, relabel, client_version, server_version, status, log, view_board, get_info, clear_revisions, remove, archive, load, unload, list_loaded, list_archive, archive_info, rename_archive, delete_archive, delete_archive, dump, restore, verify_archive, delete_from_archive, compact_archive, export, import_file, reset_server, terminate_server
\ No newline at end of file
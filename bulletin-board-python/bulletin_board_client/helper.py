@@ -2,8 +2,8 @@ from .bulletin_board_client import *
 import numpy as np
 import os
 
-def post(title, second, third=None):
-    '''Posts an ArrayObject.'''
+def post(title, second, third=None, attributes=None):
+    '''Posts an ArrayObject. Attributes are a dict of str attached to the revision.'''
     match third:
         case None:
             val=second
@@ -13,15 +13,15 @@ def post(title, second, third=None):
             tag=second
     match val:
         case int():
-            post_integer(title, tag, val)
+            post_integer(title, tag, val, attributes)
         case float():
-            post_real(title, tag, val)
+            post_real(title, tag, val, attributes)
         case complex():
-            post_complex(title, tag, val)
+            post_complex(title, tag, val, attributes)
         case str():
-            post_string(title, tag, val)
+            post_string(title, tag, val, attributes)
         case list():
-            post(title, tag, np.array(val))
+            post(title, tag, np.array(val), attributes)
         case np.ndarray():
             if val.size == 0 :
                 raise Exception("Array size cannot be zero")
@@ -29,13 +29,13 @@ def post(title, second, third=None):
             data = val.flatten(order='C')
             match data[0]:
                 case np.int64():
-                    post_integer_array(title, tag, data, shape)
+                    post_integer_array(title, tag, data, shape, attributes)
                 case np.float64():
-                    post_real_array(title, tag, data, shape)
+                    post_real_array(title, tag, data, shape, attributes)
                 case np.complex128():
-                    post_complex_array(title, tag, data, shape)
+                    post_complex_array(title, tag, data, shape, attributes)
                 case np.str_():
-                    post_string_array(title, tag, data, shape)
+                    post_string_array(title, tag, data, shape, attributes)
                 case _:
                     raise Exception("Wrong type")
         case _:
@@ -62,6 +62,24 @@ def read(title, tag=None, revisions=None):
     else:
         return converted
 
+def watch(title, callback, tag=None, prefix=False, with_data=False):
+    '''Calls the callback with a dict describing each change of the bulletins. Blocks until the callback returns False.'''
+    def raw_callback(event, title, tag, revision, title_from, tag_from, data):
+        info = {
+            "event": event,
+            "title": title,
+            "tag": tag
+        }
+        if revision is not None:
+            info["revision"] = revision
+        if title_from is not None:
+            info["title_from"] = title_from
+            info["tag_from"] = tag_from
+        if data is not None:
+            info["data"] = to_array(data)
+        return callback(info)
+    watch_raw(raw_callback, title, tag, prefix, with_data)
+
 def status():
     '''Returns the status of the server.'''
     data = status_raw()
@@ -71,7 +89,38 @@ def status():
         "memory_used(%)": data[2],
         "objects": data[3],
         "files": data[4],
-        "archived": data[5]
+        "archived": data[5],
+        "evicted": data[6],
+        "promoted": data[7],
+        "retired": data[8],
+        "retired_bytes": data[9]
+    }
+
+def archive_info(archive_name):
+    '''Returns the metadata of an archive without loading it.'''
+    data = archive_info_raw(archive_name)
+    return {
+        "format_version": data[0],
+        "created": data[1],
+        "server_version": data[2],
+        "size": data[3],
+        "keys": data[4],
+        "revisions": data[5],
+        "contents": [{"title": title, "tag": tag, "revisions": revisions, "bytes": size} for title, tag, revisions, size in data[6]],
+        "namespace": data[7]
+    }
+
+def verify_archive(archive_name, repair=False):
+    '''Checks an archive against its data. If repair is True, the index of a damaged archive is rewritten with the valid revisions.'''
+    data = verify_archive_raw(archive_name, repair)
+    return {
+        "format_version": data[0],
+        "valid": data[1],
+        "truncated": data[2],
+        "corrupted": data[3],
+        "orphaned_bytes": data[4],
+        "broken_index": data[5],
+        "repaired": data[6]
     }
 
 def board_listing(data):
@@ -81,17 +130,25 @@ def board_listing(data):
         "revisions": data[2]
     }
 
-def view_board():
-    '''Returns the list of the bulletins.'''
-    data = view_board_raw()
-    return list(map(board_listing, data))
+def view_board(title=None, tag=None, attributes=None, regex=False, sort=None, descending=False, offset=0, limit=None, after=None):
+    '''Returns the list of the bulletins. Titles, tags and attributes (a dict of patterns) are filtered by glob patterns, or regular expressions if regex is True. If limit is given, returns a tuple of the list and the token to be passed as after for the next page (None on the last page).'''
+    if (title, tag, attributes, sort, limit, after) == (None, None, None, None, None, None) and not descending and offset == 0:
+        data = view_board_raw()
+        return list(map(board_listing, data))
+    data, token = query_board_raw(title, tag, attributes, regex, sort or "name", descending, offset, limit, after)
+    listing = list(map(board_listing, data))
+    if limit is None:
+        return listing
+    else:
+        return listing, token
 
 def bulletin_listing(data):
     return {
         "revision": data[0],
         "datasize": data[1],
         "timestamp": data[2],
-        "backend": data[3]
+        "backend": data[3],
+        "attributes": data[4]
     }
 
 def get_info(title, tag=None):
@@ -1,8 +1,40 @@
-use bbclient::{DataType, adaptor::VecShape};
+use bbclient::{
+    Attributes, Continuation, DataType, Event, Pattern, Query, Retention, Selection, SortKey,
+    adaptor::VecShape,
+};
 use num_complex::Complex64;
+use pyo3::exceptions::{
+    PyConnectionError, PyKeyError, PyPermissionError, PyRuntimeError, PyValueError,
+};
 use pyo3::prelude::*;
 use std::time::Duration;
 
+fn to_py_err(err: bbclient::Error) -> PyErr {
+    match err {
+        bbclient::Error::NotFound => PyKeyError::new_err(err.to_string()),
+        bbclient::Error::NotUnique(_) => PyValueError::new_err(err.to_string()),
+        bbclient::Error::Io(_) => PyConnectionError::new_err(err.to_string()),
+        bbclient::Error::Server {
+            kind: bbclient::ErrorKind::Unauthorized | bbclient::ErrorKind::PermissionDenied,
+            ..
+        } => PyPermissionError::new_err(err.to_string()),
+        _ => PyRuntimeError::new_err(err.to_string()),
+    }
+}
+
+fn post_object(
+    title: &str,
+    tag: &str,
+    obj: bbclient::ArrayObject,
+    attributes: Option<Attributes>,
+) -> PyResult<()> {
+    match attributes {
+        Some(attributes) => bbclient::post_with_attributes(title, tag, obj, &attributes),
+        None => bbclient::post(title, tag, obj),
+    }
+    .map_err(to_py_err)
+}
+
 /// Sets the server address.
 #[pyfunction]
 fn set_addr(addr: String) -> PyResult<()> {
@@ -14,76 +46,196 @@ fn set_addr(addr: String) -> PyResult<()> {
 #[pyfunction]
 #[pyo3(signature = (timeout=None))]
 fn set_timeout(timeout: Option<u64>) -> PyResult<()> {
-    bbclient::set_timeout(timeout.map(|t| Duration::from_millis(t)));
+    bbclient::set_timeout(timeout.map(Duration::from_millis));
     Ok(())
 }
 
+/// Sets the token sent to the server on connect. If the argument is None, no token is sent. The default is the environment variable BB_TOKEN.
 #[pyfunction]
-fn post_integer(title: String, tag: String, val: i128) -> PyResult<()> {
-    let obj = val.try_into().unwrap();
-    bbclient::post(&title, &tag, obj).unwrap();
+#[pyo3(signature = (token=None))]
+fn set_token(token: Option<String>) -> PyResult<()> {
+    bbclient::set_token(token.as_deref());
     Ok(())
 }
 
+/// Selects the namespace, whose board is isolated from those of the other namespaces. If the argument is None, the default namespace is selected. The default is the environment variable BB_NAMESPACE.
 #[pyfunction]
-fn post_real(title: String, tag: String, val: f64) -> PyResult<()> {
-    let obj = val.try_into().unwrap();
-    bbclient::post(&title, &tag, obj).unwrap();
+#[pyo3(signature = (namespace=None))]
+fn set_namespace(namespace: Option<String>) -> PyResult<()> {
+    bbclient::set_namespace(namespace.as_deref());
     Ok(())
 }
 
+/// Enables TLS for TCP connections with the certificate of the server verified against the CA certificates in a PEM file. If the argument is None, TLS is disabled. The default is the environment variable BB_TLS_CA.
+#[pyfunction]
+#[pyo3(signature = (ca_file=None))]
+fn set_tls_ca(ca_file: Option<String>) -> PyResult<()> {
+    bbclient::set_tls_ca(ca_file.as_deref()).map_err(to_py_err)
+}
+
+/// Enables or disables acknowledgement of operations that modify the server (enabled by default). When disabled, failures are only recorded in the log of the server.
 #[pyfunction]
-fn post_complex(title: String, tag: String, val: Complex64) -> PyResult<()> {
-    let obj = val.try_into().unwrap();
-    bbclient::post(&title, &tag, obj).unwrap();
+fn set_acknowledge(acknowledge: bool) -> PyResult<()> {
+    bbclient::set_acknowledge(acknowledge);
     Ok(())
 }
 
 #[pyfunction]
-fn post_string(title: String, tag: String, val: String) -> PyResult<()> {
-    let obj = val.try_into().unwrap();
-    bbclient::post(&title, &tag, obj).unwrap();
+#[pyo3(signature = (title, tag, val, attributes=None))]
+fn post_integer(
+    title: String,
+    tag: String,
+    val: i128,
+    attributes: Option<Attributes>,
+) -> PyResult<()> {
+    let obj = val.into();
+    post_object(&title, &tag, obj, attributes)?;
     Ok(())
 }
 
 #[pyfunction]
-fn post_integer_array(title: String, tag: String, val: Vec<i128>, shape: Vec<u64>) -> PyResult<()> {
+#[pyo3(signature = (title, tag, val, attributes=None))]
+fn post_real(title: String, tag: String, val: f64, attributes: Option<Attributes>) -> PyResult<()> {
+    let obj = val.into();
+    post_object(&title, &tag, obj, attributes)?;
+    Ok(())
+}
+
+#[pyfunction]
+#[pyo3(signature = (title, tag, val, attributes=None))]
+fn post_complex(
+    title: String,
+    tag: String,
+    val: Complex64,
+    attributes: Option<Attributes>,
+) -> PyResult<()> {
+    let obj = val.into();
+    post_object(&title, &tag, obj, attributes)?;
+    Ok(())
+}
+
+#[pyfunction]
+#[pyo3(signature = (title, tag, val, attributes=None))]
+fn post_string(
+    title: String,
+    tag: String,
+    val: String,
+    attributes: Option<Attributes>,
+) -> PyResult<()> {
+    let obj = val.into();
+    post_object(&title, &tag, obj, attributes)?;
+    Ok(())
+}
+
+#[pyfunction]
+#[pyo3(signature = (title, tag, val, shape, attributes=None))]
+fn post_integer_array(
+    title: String,
+    tag: String,
+    val: Vec<i128>,
+    shape: Vec<u64>,
+    attributes: Option<Attributes>,
+) -> PyResult<()> {
     let obj = VecShape(val, shape).try_into().unwrap();
-    bbclient::post(&title, &tag, obj).unwrap();
+    post_object(&title, &tag, obj, attributes)?;
     Ok(())
 }
 
 #[pyfunction]
-fn post_real_array(title: String, tag: String, val: Vec<f64>, shape: Vec<u64>) -> PyResult<()> {
+#[pyo3(signature = (title, tag, val, shape, attributes=None))]
+fn post_real_array(
+    title: String,
+    tag: String,
+    val: Vec<f64>,
+    shape: Vec<u64>,
+    attributes: Option<Attributes>,
+) -> PyResult<()> {
     let obj = VecShape(val, shape).try_into().unwrap();
-    bbclient::post(&title, &tag, obj).unwrap();
+    post_object(&title, &tag, obj, attributes)?;
     Ok(())
 }
 
 #[pyfunction]
+#[pyo3(signature = (title, tag, val, shape, attributes=None))]
 fn post_complex_array(
     title: String,
     tag: String,
     val: Vec<Complex64>,
     shape: Vec<u64>,
+    attributes: Option<Attributes>,
 ) -> PyResult<()> {
     let obj = VecShape(val, shape).try_into().unwrap();
-    bbclient::post(&title, &tag, obj).unwrap();
+    post_object(&title, &tag, obj, attributes)?;
     Ok(())
 }
 
 #[pyfunction]
+#[pyo3(signature = (title, tag, val, shape, attributes=None))]
 fn post_string_array(
     title: String,
     tag: String,
     val: Vec<String>,
     shape: Vec<u64>,
+    attributes: Option<Attributes>,
 ) -> PyResult<()> {
     let obj = VecShape(val, shape).try_into().unwrap();
-    bbclient::post(&title, &tag, obj).unwrap();
+    post_object(&title, &tag, obj, attributes)?;
     Ok(())
 }
 
+fn to_py_object(py: Python<'_>, elem: bbclient::ArrayObject) -> PyObject {
+    match elem.datatype() {
+        DataType::UnsignedInteger => {
+            if elem.dimension() == 0 {
+                let val: u128 = elem.try_into().unwrap();
+                val.into_pyobject(py).unwrap().into()
+            } else {
+                let VecShape::<u128>(val, shape) = elem.try_into().unwrap();
+                let shape: Vec<usize> = shape.into_iter().map(|x| x.try_into().unwrap()).collect();
+                (val, shape).into_pyobject(py).unwrap().into()
+            }
+        }
+        DataType::SignedInteger => {
+            if elem.dimension() == 0 {
+                let val: i128 = elem.try_into().unwrap();
+                val.into_pyobject(py).unwrap().into()
+            } else {
+                let VecShape::<i128>(val, shape) = elem.try_into().unwrap();
+                let shape: Vec<usize> = shape.into_iter().map(|x| x.try_into().unwrap()).collect();
+                (val, shape).into_pyobject(py).unwrap().into()
+            }
+        }
+        DataType::Real => {
+            if elem.dimension() == 0 {
+                let val: f64 = elem.try_into().unwrap();
+                val.into_pyobject(py).unwrap().into()
+            } else {
+                let VecShape::<f64>(val, shape) = elem.try_into().unwrap();
+                let shape: Vec<usize> = shape.into_iter().map(|x| x.try_into().unwrap()).collect();
+                (val, shape).into_pyobject(py).unwrap().into()
+            }
+        }
+        DataType::Complex => {
+            if elem.dimension() == 0 {
+                let val: Complex64 = elem.try_into().unwrap();
+                val.into_pyobject(py).unwrap().into()
+            } else {
+                let VecShape::<Complex64>(val, shape) = elem.try_into().unwrap();
+                (val, shape).into_pyobject(py).unwrap().into()
+            }
+        }
+        DataType::String => {
+            if elem.dimension() == 0 {
+                let val: String = elem.try_into().unwrap();
+                val.into_pyobject(py).unwrap().into()
+            } else {
+                let VecShape::<String>(val, shape) = elem.try_into().unwrap();
+                (val, shape).into_pyobject(py).unwrap().into()
+            }
+        }
+    }
+}
+
 #[pyfunction]
 #[pyo3(signature = (title, tag=None, revisions=None))]
 fn read_raw(
@@ -92,69 +244,83 @@ fn read_raw(
     tag: Option<String>,
     revisions: Option<Vec<u64>>,
 ) -> PyResult<PyObject> {
-    let revisions = match revisions {
-        Some(rev) => rev,
-        None => vec![],
-    };
+    let revisions = revisions.unwrap_or_default();
 
-    let list = bbclient::read(&title, tag.as_deref(), revisions).unwrap();
-    let mut res: Vec<PyObject> = vec![];
-    for elem in list {
-        match elem.datatype() {
-            DataType::UnsignedInteger => {
-                if elem.dimension() == 0 {
-                    let val: u128 = elem.try_into().unwrap();
-                    res.push(val.into_pyobject(py).unwrap().into());
-                } else {
-                    let VecShape::<u128>(val, shape) = elem.try_into().unwrap();
-                    let shape: Vec<usize> =
-                        shape.into_iter().map(|x| x.try_into().unwrap()).collect();
-                    res.push((val, shape).into_pyobject(py).unwrap().into());
-                }
-            }
-            DataType::SignedInteger => {
-                if elem.dimension() == 0 {
-                    let val: i128 = elem.try_into().unwrap();
-                    res.push(val.into_pyobject(py).unwrap().into());
-                } else {
-                    let VecShape::<i128>(val, shape) = elem.try_into().unwrap();
-                    let shape: Vec<usize> =
-                        shape.into_iter().map(|x| x.try_into().unwrap()).collect();
-                    res.push((val, shape).into_pyobject(py).unwrap().into());
-                }
-            }
-            DataType::Real => {
-                if elem.dimension() == 0 {
-                    let val: f64 = elem.try_into().unwrap();
-                    res.push(val.into_pyobject(py).unwrap().into());
-                } else {
-                    let VecShape::<f64>(val, shape) = elem.try_into().unwrap();
-                    let shape: Vec<usize> =
-                        shape.into_iter().map(|x| x.try_into().unwrap()).collect();
-                    res.push((val, shape).into_pyobject(py).unwrap().into());
-                }
-            }
-            DataType::Complex => {
-                if elem.dimension() == 0 {
-                    let val: Complex64 = elem.try_into().unwrap();
-                    res.push(val.into_pyobject(py).unwrap().into());
-                } else {
-                    let VecShape::<Complex64>(val, shape) = elem.try_into().unwrap();
-                    res.push((val, shape).into_pyobject(py).unwrap().into());
-                }
-            }
-            DataType::String => {
-                if elem.dimension() == 0 {
-                    let val: String = elem.try_into().unwrap();
-                    res.push(val.into_pyobject(py).unwrap().into());
-                } else {
-                    let VecShape::<String>(val, shape) = elem.try_into().unwrap();
-                    res.push((val, shape).into_pyobject(py).unwrap().into());
-                }
-            }
+    let list = bbclient::read(&title, tag.as_deref(), revisions).map_err(to_py_err)?;
+    let res: Vec<PyObject> = list
+        .into_iter()
+        .map(|elem| to_py_object(py, elem))
+        .collect();
+    Ok(res.into_pyobject(py).unwrap().into())
+}
+
+/// Calls the callback on every change of the bulletins. Blocks until the callback returns False or the server closes the connection.
+#[pyfunction]
+#[pyo3(signature = (callback, title, tag=None, prefix=false, with_data=false))]
+fn watch_raw(
+    py: Python<'_>,
+    callback: PyObject,
+    title: String,
+    tag: Option<String>,
+    prefix: bool,
+    with_data: bool,
+) -> PyResult<()> {
+    let mut watcher: Box<dyn Iterator<Item = _> + Send> = if prefix {
+        Box::new(bbclient::watch_prefix(&title, tag.as_deref(), with_data).map_err(to_py_err)?)
+    } else {
+        Box::new(bbclient::watch(&title, tag.as_deref(), with_data).map_err(to_py_err)?)
+    };
+    while let Some(item) = py.allow_threads(|| watcher.next()) {
+        let (event, obj) = item.map_err(to_py_err)?;
+        let data = obj.map(|obj| to_py_object(py, obj));
+        let args = match event {
+            Event::Posted {
+                title,
+                tag,
+                revision,
+            } => ("posted", title, tag, Some(revision), None, None, data),
+            Event::Relabeled {
+                title_from,
+                tag_from,
+                title,
+                tag,
+            } => (
+                "relabeled",
+                title,
+                tag,
+                None,
+                Some(title_from),
+                Some(tag_from),
+                data,
+            ),
+            Event::Removed { title, tag } => ("removed", title, tag, None, None, None, data),
+        };
+        let ret = callback.call1(py, args)?;
+        if let Ok(false) = ret.extract::<bool>(py) {
+            break;
         }
     }
-    Ok(res.into_pyobject(py).unwrap().into())
+    Ok(())
+}
+
+/// Sets the retention policy of a bulletin. If all the limits are None, the policy is removed. The policy applies to all the tags of the title if the tag is None.
+#[pyfunction]
+#[pyo3(signature = (title, tag=None, max_revisions=None, max_age=None, max_bytes=None))]
+fn set_retention(
+    title: String,
+    tag: Option<String>,
+    max_revisions: Option<u64>,
+    max_age: Option<u64>,
+    max_bytes: Option<u64>,
+) -> PyResult<()> {
+    let retention = Retention {
+        max_revisions,
+        max_age,
+        max_bytes,
+    };
+    let retention = (retention != Retention::default()).then_some(retention);
+    bbclient::set_retention(&title, tag.as_deref(), retention.as_ref()).map_err(to_py_err)?;
+    Ok(())
 }
 
 /// Relabels a bulletin.
@@ -172,7 +338,7 @@ fn relabel(
         title_to.as_deref(),
         tag_to.as_deref(),
     )
-    .unwrap();
+    .map_err(to_py_err)?;
     Ok(())
 }
 
@@ -186,7 +352,7 @@ fn client_version(py: Python<'_>) -> PyResult<PyObject> {
 /// Returns the version of the server.
 #[pyfunction]
 fn server_version(py: Python<'_>) -> PyResult<PyObject> {
-    let server_version = bbclient::server_version().unwrap();
+    let server_version = bbclient::server_version().map_err(to_py_err)?;
     Ok(server_version.into_pyobject(py).unwrap().into())
 }
 
@@ -194,7 +360,7 @@ fn server_version(py: Python<'_>) -> PyResult<PyObject> {
 #[pyfunction]
 fn status_raw(py: Python<'_>) -> PyResult<PyObject> {
     Ok(bbclient::status()
-        .unwrap()
+        .map_err(to_py_err)?
         .into_pyobject(py)
         .unwrap()
         .into())
@@ -203,23 +369,78 @@ fn status_raw(py: Python<'_>) -> PyResult<PyObject> {
 /// Returns the log of the server.
 #[pyfunction]
 fn log(py: Python<'_>) -> PyResult<PyObject> {
-    Ok(bbclient::log().unwrap().into_pyobject(py).unwrap().into())
+    Ok(bbclient::log()
+        .map_err(to_py_err)?
+        .into_pyobject(py)
+        .unwrap()
+        .into())
 }
 
 #[pyfunction]
 fn view_board_raw(py: Python<'_>) -> PyResult<PyObject> {
     Ok(bbclient::view_board()
-        .unwrap()
+        .map_err(to_py_err)?
         .into_pyobject(py)
         .unwrap()
         .into())
 }
 
+#[pyfunction]
+#[pyo3(signature = (title=None, tag=None, attributes=None, regex=false, sort="name", descending=false, offset=0, limit=None, after=None))]
+#[allow(clippy::too_many_arguments)]
+fn query_board_raw(
+    py: Python<'_>,
+    title: Option<String>,
+    tag: Option<String>,
+    attributes: Option<Attributes>,
+    regex: bool,
+    sort: &str,
+    descending: bool,
+    offset: u64,
+    limit: Option<u64>,
+    after: Option<(u64, String, String)>,
+) -> PyResult<PyObject> {
+    let pattern = |val: String| {
+        if regex {
+            Pattern::Regex(val)
+        } else {
+            Pattern::Glob(val)
+        }
+    };
+    let sort = match sort {
+        "name" => SortKey::Name,
+        "last_modified" => SortKey::LastModified,
+        "size" => SortKey::Size,
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "Unknown sort key: {sort}. Use name, last_modified or size."
+            )));
+        }
+    };
+    let query = Query {
+        title: title.map(pattern),
+        tag: tag.map(pattern),
+        attributes: attributes
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(key, val)| (key, pattern(val)))
+            .collect(),
+        sort,
+        descending,
+        offset,
+        limit,
+        after: after.map(|(key, title, tag)| Continuation { key, title, tag }),
+    };
+    let page = bbclient::query_board(&query).map_err(to_py_err)?;
+    let next = page.next.map(|next| (next.key, next.title, next.tag));
+    Ok((page.bulletins, next).into_pyobject(py).unwrap().into())
+}
+
 #[pyfunction]
 #[pyo3(signature = (title, tag=None))]
 fn get_info_raw(py: Python<'_>, title: String, tag: Option<String>) -> PyResult<PyObject> {
     Ok(bbclient::get_info(&title, tag.as_deref())
-        .unwrap()
+        .map_err(to_py_err)?
         .into_pyobject(py)
         .unwrap()
         .into())
@@ -228,7 +449,7 @@ fn get_info_raw(py: Python<'_>, title: String, tag: Option<String>) -> PyResult<
 #[pyfunction]
 #[pyo3(signature = (title, revisions, tag=None))]
 fn clear_revisions_raw(title: String, revisions: Vec<u64>, tag: Option<String>) -> PyResult<()> {
-    bbclient::clear_revisions(&title, tag.as_deref(), revisions).unwrap();
+    bbclient::clear_revisions(&title, tag.as_deref(), revisions).map_err(to_py_err)?;
     Ok(())
 }
 
@@ -236,7 +457,7 @@ fn clear_revisions_raw(title: String, revisions: Vec<u64>, tag: Option<String>)
 #[pyfunction]
 #[pyo3(signature = (title, tag=None))]
 fn remove(title: String, tag: Option<String>) -> PyResult<()> {
-    bbclient::remove(&title, tag.as_deref()).unwrap();
+    bbclient::remove(&title, tag.as_deref()).map_err(to_py_err)?;
     Ok(())
 }
 
@@ -244,73 +465,206 @@ fn remove(title: String, tag: Option<String>) -> PyResult<()> {
 #[pyfunction]
 #[pyo3(signature = (acv_name, title, tag=None))]
 fn archive(acv_name: String, title: String, tag: Option<String>) -> PyResult<()> {
-    bbclient::archive(&acv_name, &title, tag.as_deref()).unwrap();
+    bbclient::archive(&acv_name, &title, tag.as_deref()).map_err(to_py_err)?;
     Ok(())
 }
 
-/// Loads or reloads an archive. The data is directly read from the archive file and a suffix "acv_name:" is added to the tag.
+/// Loads or reloads an archive. The data is directly read from the archive file and a suffix "acv_name:" is added to the tag. Titles and tags are selected by glob patterns, or regular expressions if regex is True, and revisions by a range (start, end).
 #[pyfunction]
-fn load(acv_name: String) -> PyResult<()> {
-    bbclient::load(&acv_name).unwrap();
+#[pyo3(signature = (acv_name, title=None, tag=None, regex=false, revisions=None))]
+fn load(
+    acv_name: String,
+    title: Option<String>,
+    tag: Option<String>,
+    regex: bool,
+    revisions: Option<(u64, u64)>,
+) -> PyResult<()> {
+    let selection = selection(title, tag, regex, revisions);
+    if selection == Selection::default() {
+        bbclient::load(&acv_name).map_err(to_py_err)?;
+    } else {
+        bbclient::load_selected(&acv_name, &selection).map_err(to_py_err)?;
+    }
     Ok(())
 }
 
+fn selection(
+    title: Option<String>,
+    tag: Option<String>,
+    regex: bool,
+    revisions: Option<(u64, u64)>,
+) -> Selection {
+    let pattern = |val: String| {
+        if regex {
+            Pattern::Regex(val)
+        } else {
+            Pattern::Glob(val)
+        }
+    };
+    Selection {
+        title: title.map(pattern),
+        tag: tag.map(pattern),
+        revisions: revisions.map(|(start, end)| start..end),
+    }
+}
+
 /// Shows the list of archive.
 #[pyfunction]
 fn list_archive(py: Python<'_>) -> PyResult<PyObject> {
     Ok(bbclient::list_archive()
-        .unwrap()
+        .map_err(to_py_err)?
         .into_pyobject(py)
         .unwrap()
         .into())
 }
 
-/// Renames an archive. This will be applied after after calling reset_server.
+/// Removes the bulletins loaded from an archive. The other bulletins are kept.
+#[pyfunction]
+fn unload(acv_name: String) -> PyResult<()> {
+    bbclient::unload(&acv_name).map_err(to_py_err)?;
+    Ok(())
+}
+
+/// Shows the list of loaded archives.
+#[pyfunction]
+fn list_loaded() -> PyResult<Vec<String>> {
+    let list = bbclient::list_loaded().map_err(to_py_err)?;
+    Ok(list)
+}
+
+/// Renames an archive. It fails if the archive is loaded.
 #[pyfunction]
 fn rename_archive(name_from: String, name_to: String) -> PyResult<()> {
-    bbclient::rename_archive(&name_from, &name_to).unwrap();
+    bbclient::rename_archive(&name_from, &name_to).map_err(to_py_err)?;
     Ok(())
 }
 
-/// Deletes an archive. This will be applied after after calling reset_server.
+/// Deletes an archive. It fails if the archive is loaded.
 #[pyfunction]
 fn delete_archive(acv_name: String) -> PyResult<()> {
-    bbclient::delete_archive(&acv_name).unwrap();
+    bbclient::delete_archive(&acv_name).map_err(to_py_err)?;
     Ok(())
 }
 
 /// Dumps all the unarchived data into an archive.
 #[pyfunction]
 fn dump(acv_name: String) -> PyResult<()> {
-    bbclient::dump(&acv_name).unwrap();
+    bbclient::dump(&acv_name).map_err(to_py_err)?;
     Ok(())
 }
 
-/// Delete all the temporary data and restores data from an archive. Each data is copied to memory or a separate file. No suffix is added to the tag.
+/// Delete all the temporary data and restores data from an archive. Each data is copied to memory or a separate file. No suffix is added to the tag. The revisions are selected as in load. If merge is True, the current data is kept and the revisions are appended.
 #[pyfunction]
-fn restore(acv_name: String) -> PyResult<()> {
-    bbclient::restore(&acv_name).unwrap();
+#[pyo3(signature = (acv_name, title=None, tag=None, regex=false, revisions=None, merge=false))]
+fn restore(
+    acv_name: String,
+    title: Option<String>,
+    tag: Option<String>,
+    regex: bool,
+    revisions: Option<(u64, u64)>,
+    merge: bool,
+) -> PyResult<()> {
+    let selection = selection(title, tag, regex, revisions);
+    if selection == Selection::default() && !merge {
+        bbclient::restore(&acv_name).map_err(to_py_err)?;
+    } else {
+        bbclient::restore_selected(&acv_name, &selection, merge).map_err(to_py_err)?;
+    }
+    Ok(())
+}
+
+/// Deletes revisions from an archive. All the tags are selected if `tag` is None, and all the revisions if `revisions` is None. The space is reclaimed by compact_archive.
+#[pyfunction]
+#[pyo3(signature = (acv_name, title, tag=None, revisions=None))]
+fn delete_from_archive(
+    acv_name: String,
+    title: String,
+    tag: Option<String>,
+    revisions: Option<Vec<u64>>,
+) -> PyResult<()> {
+    bbclient::delete_from_archive(&acv_name, &title, tag.as_deref(), revisions)
+        .map_err(to_py_err)?;
+    Ok(())
+}
+
+/// Rewrites an archive without the deleted revisions to reclaim the space.
+#[pyfunction]
+fn compact_archive(acv_name: String) -> PyResult<()> {
+    bbclient::compact_archive(&acv_name).map_err(to_py_err)?;
+    Ok(())
+}
+
+/// Exports an archive, or the board if archive_name is None, to `{output}.npz` and `{output}.json`.
+#[pyfunction]
+#[pyo3(signature = (output, archive_name=None))]
+fn export(output: String, archive_name: Option<String>) -> PyResult<()> {
+    bbclient::export(archive_name.as_deref(), &output).map_err(to_py_err)?;
     Ok(())
 }
 
+/// Posts the arrays in a `.npy`, `.npz` or CSV file. The arrays in an NPZ file are posted as `title/name`.
+#[pyfunction]
+fn import_file(title: String, tag: String, path: String) -> PyResult<()> {
+    bbclient::import(&title, &tag, &path).map_err(to_py_err)?;
+    Ok(())
+}
+
+/// Reads the metadata of an archive without loading it. The return values are (format version, creation time, server version, size of the data file, the number of keys, the number of revisions, list of (title, tag, revisions, bytes), namespace).
+#[pyfunction]
+fn archive_info_raw(py: Python<'_>, acv_name: String) -> PyResult<PyObject> {
+    let info = bbclient::archive_info(&acv_name).map_err(to_py_err)?;
+    Ok((
+        info.format_version,
+        info.created,
+        info.server_version,
+        info.size,
+        info.n_keys,
+        info.n_revisions,
+        info.keys,
+        info.namespace,
+    )
+        .into_pyobject(py)
+        .unwrap()
+        .into())
+}
+
+/// Checks an archive against its data. The return values are (format version, the number of valid revisions, truncated revisions, corrupted revisions, orphaned bytes, damage of the index, whether the index was rewritten).
+#[pyfunction]
+#[pyo3(signature = (acv_name, repair=false))]
+fn verify_archive_raw(py: Python<'_>, acv_name: String, repair: bool) -> PyResult<PyObject> {
+    let report = bbclient::verify_archive(&acv_name, repair).map_err(to_py_err)?;
+    Ok((
+        report.format_version,
+        report.valid,
+        report.truncated,
+        report.corrupted,
+        report.orphaned_bytes,
+        report.broken_index,
+        report.repaired,
+    )
+        .into_pyobject(py)
+        .unwrap()
+        .into())
+}
+
 /// Clears the log file of the server.
 #[pyfunction]
 fn clear_log() -> PyResult<()> {
-    bbclient::clear_log().unwrap();
+    bbclient::clear_log().map_err(to_py_err)?;
     Ok(())
 }
 
 /// Resets and clears the data. The archived data is not affected, but must be loaded before use.
 #[pyfunction]
 fn reset_server() -> PyResult<()> {
-    bbclient::reset_server().unwrap();
+    bbclient::reset_server().map_err(to_py_err)?;
     Ok(())
 }
 
 /// Terminates the server.
 #[pyfunction]
 fn terminate_server() -> PyResult<()> {
-    bbclient::terminate_server().unwrap();
+    bbclient::terminate_server().map_err(to_py_err)?;
     Ok(())
 }
 
@@ -319,6 +673,10 @@ fn terminate_server() -> PyResult<()> {
 fn bulletin_board_client(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(set_addr, m)?)?;
     m.add_function(wrap_pyfunction!(set_timeout, m)?)?;
+    m.add_function(wrap_pyfunction!(set_token, m)?)?;
+    m.add_function(wrap_pyfunction!(set_namespace, m)?)?;
+    m.add_function(wrap_pyfunction!(set_tls_ca, m)?)?;
+    m.add_function(wrap_pyfunction!(set_acknowledge, m)?)?;
     m.add_function(wrap_pyfunction!(post_integer, m)?)?;
     m.add_function(wrap_pyfunction!(post_real, m)?)?;
     m.add_function(wrap_pyfunction!(post_complex, m)?)?;
@@ -328,22 +686,33 @@ fn bulletin_board_client(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(post_complex_array, m)?)?;
     m.add_function(wrap_pyfunction!(post_string_array, m)?)?;
     m.add_function(wrap_pyfunction!(read_raw, m)?)?;
+    m.add_function(wrap_pyfunction!(watch_raw, m)?)?;
+    m.add_function(wrap_pyfunction!(set_retention, m)?)?;
     m.add_function(wrap_pyfunction!(relabel, m)?)?;
     m.add_function(wrap_pyfunction!(client_version, m)?)?;
     m.add_function(wrap_pyfunction!(server_version, m)?)?;
     m.add_function(wrap_pyfunction!(status_raw, m)?)?;
     m.add_function(wrap_pyfunction!(log, m)?)?;
     m.add_function(wrap_pyfunction!(view_board_raw, m)?)?;
+    m.add_function(wrap_pyfunction!(query_board_raw, m)?)?;
     m.add_function(wrap_pyfunction!(get_info_raw, m)?)?;
     m.add_function(wrap_pyfunction!(clear_revisions_raw, m)?)?;
     m.add_function(wrap_pyfunction!(remove, m)?)?;
     m.add_function(wrap_pyfunction!(archive, m)?)?;
     m.add_function(wrap_pyfunction!(load, m)?)?;
     m.add_function(wrap_pyfunction!(list_archive, m)?)?;
+    m.add_function(wrap_pyfunction!(unload, m)?)?;
+    m.add_function(wrap_pyfunction!(list_loaded, m)?)?;
     m.add_function(wrap_pyfunction!(rename_archive, m)?)?;
     m.add_function(wrap_pyfunction!(delete_archive, m)?)?;
     m.add_function(wrap_pyfunction!(dump, m)?)?;
     m.add_function(wrap_pyfunction!(restore, m)?)?;
+    m.add_function(wrap_pyfunction!(archive_info_raw, m)?)?;
+    m.add_function(wrap_pyfunction!(verify_archive_raw, m)?)?;
+    m.add_function(wrap_pyfunction!(delete_from_archive, m)?)?;
+    m.add_function(wrap_pyfunction!(compact_archive, m)?)?;
+    m.add_function(wrap_pyfunction!(export, m)?)?;
+    m.add_function(wrap_pyfunction!(import_file, m)?)?;
     m.add_function(wrap_pyfunction!(clear_log, m)?)?;
     m.add_function(wrap_pyfunction!(reset_server, m)?)?;
     m.add_function(wrap_pyfunction!(terminate_server, m)?)?;
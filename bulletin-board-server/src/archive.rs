@@ -0,0 +1,488 @@
+use crate::ACV_DIR;
+use bulletin_board_common::{ArchiveInfo, ArchiveReport, Attributes};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Seek, SeekFrom};
+use std::path::Path;
+use uuid::Uuid;
+
+/// Version of the archive format written by this server.
+///
+/// Version 1 archives consist of `data.bin`, `meta.bin` and `version.txt`. From version 2, `meta.bin` and `version.txt` are replaced by `index.bin`, which holds a [`Header`] followed by the [`Keys`]. The data file is named in the header, so that compaction can switch to a new data file by replacing the index.
+pub const FORMAT_VERSION: u32 = 2;
+
+#[derive(Serialize, Deserialize)]
+pub struct Header {
+    pub format_version: u32,
+    /// Creation time in nanoseconds since the epoch.
+    pub created: i64,
+    pub server_version: String,
+    /// Name of the data file in the archive directory.
+    #[serde(default = "default_data_file")]
+    pub data_file: String,
+    /// Namespace of the board the archive was created from. `None` for the default namespace.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+}
+
+fn default_data_file() -> String {
+    "data.bin".to_string()
+}
+
+/// Entry of a revision: offset and size in the data file, timestamp, CRC-32 checksum of the data and attributes. Revisions converted from version 1 have no checksum.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RevisionEntry {
+    pub offset: u64,
+    pub datasize: u64,
+    pub timestamp: i64,
+    pub checksum: Option<u32>,
+    #[serde(default, skip_serializing_if = "Attributes::is_empty")]
+    pub attributes: Attributes,
+}
+
+/// Revisions of each title and tag.
+pub type Keys = BTreeMap<String, BTreeMap<String, Vec<RevisionEntry>>>;
+
+/// Offset, datasize and new offset of a revision moved by [`compact`].
+pub type Relocation = (u64, u64, u64);
+
+/// Entry of a revision in `meta.bin` of version 1: offset and size in `data.bin`, timestamp and attributes.
+#[derive(Deserialize)]
+struct RevisionMetaV1(u64, u64, i64, #[serde(default)] Attributes);
+
+pub struct Archive {
+    pub name: String,
+    pub header: Header,
+    pub keys: Keys,
+}
+
+impl Archive {
+    pub fn dir(name: &str) -> String {
+        format!("{}/{}", *ACV_DIR, name)
+    }
+    pub fn data_path(&self) -> String {
+        format!("{}/{}", Self::dir(&self.name), self.header.data_file)
+    }
+    fn index_path(name: &str) -> String {
+        format!("{}/{}/index.bin", *ACV_DIR, name)
+    }
+    fn meta_path(name: &str) -> String {
+        format!("{}/{}/meta.bin", *ACV_DIR, name)
+    }
+    /// Creates an empty archive in memory. Nothing is written until [`Archive::save`].
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            header: Header {
+                format_version: FORMAT_VERSION,
+                created: Local::now().timestamp_nanos_opt().unwrap(),
+                server_version: env!("CARGO_PKG_VERSION").to_string(),
+                data_file: default_data_file(),
+                namespace: None,
+            },
+            keys: Keys::new(),
+        }
+    }
+    /// Reads the index of an archive. Version 1 archives are converted transparently.
+    pub fn open(name: &str) -> Result<Self, io::Error> {
+        match Self::salvage(name)? {
+            (archive, None) => Ok(archive),
+            (_, Some(err)) => Err(err),
+        }
+    }
+    /// The namespace is recorded only if the archive is created.
+    pub fn open_or_new(name: &str, namespace: Option<&str>) -> Result<Self, io::Error> {
+        match Self::open(name) {
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                let mut archive = Self::new(name);
+                archive.header.namespace = namespace.map(str::to_string);
+                Ok(archive)
+            }
+            result => result,
+        }
+    }
+    /// Reads the index as far as possible. The damage of the index, if any, is returned together with the revisions read before it.
+    pub fn salvage(name: &str) -> Result<(Self, Option<io::Error>), io::Error> {
+        if Path::new(&Self::index_path(name)).exists() {
+            Self::salvage_v2(name)
+        } else if Path::new(&Self::meta_path(name)).exists() {
+            Self::salvage_v1(name)
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Archive {name} does not exist."),
+            ))
+        }
+    }
+    fn salvage_v2(name: &str) -> Result<(Self, Option<io::Error>), io::Error> {
+        let mut reader = BufReader::new(File::open(Self::index_path(name))?);
+        let mut archive = Self::new(name);
+        archive.header = match ciborium::from_reader::<Header, _>(&mut reader) {
+            Ok(header) => header,
+            Err(err) => return Ok((archive, Some(corrupted(name, err)))),
+        };
+        if archive.header.format_version > FORMAT_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Archive {name} has format version {}, which is newer than this server supports.",
+                    archive.header.format_version
+                ),
+            ));
+        }
+        match ciborium::from_reader(&mut reader) {
+            Ok(keys) => {
+                archive.keys = keys;
+                Ok((archive, None))
+            }
+            Err(err) => Ok((archive, Some(corrupted(name, err)))),
+        }
+    }
+    fn salvage_v1(name: &str) -> Result<(Self, Option<io::Error>), io::Error> {
+        let mut reader = BufReader::new(File::open(Self::meta_path(name))?);
+        let mut archive = Self::new(name);
+        archive.header.format_version = 1;
+        let version_path = format!("{}/version.txt", Self::dir(name));
+        archive.header.server_version = fs::read_to_string(&version_path).unwrap_or_default();
+        archive.header.created = fs::metadata(&version_path)
+            .and_then(|meta| meta.modified())
+            .map(|time| DateTime::<Local>::from(time).timestamp_nanos_opt().unwrap())
+            .unwrap_or(0);
+        while !reader.fill_buf()?.is_empty() {
+            let (title, tag, revisions): (String, String, u64) =
+                match ciborium::from_reader(&mut reader) {
+                    Ok(key) => key,
+                    Err(err) => return Ok((archive, Some(corrupted(name, err)))),
+                };
+            let list = archive
+                .keys
+                .entry(title)
+                .or_default()
+                .entry(tag)
+                .or_default();
+            for _ in 0..revisions {
+                let RevisionMetaV1(offset, datasize, timestamp, attributes) =
+                    match ciborium::from_reader(&mut reader) {
+                        Ok(meta) => meta,
+                        Err(err) => return Ok((archive, Some(corrupted(name, err)))),
+                    };
+                list.push(RevisionEntry {
+                    offset,
+                    datasize,
+                    timestamp,
+                    checksum: None,
+                    attributes,
+                });
+            }
+        }
+        Ok((archive, None))
+    }
+    /// Replaces the index atomically. A version 1 archive is upgraded, and its `meta.bin` and `version.txt` are removed.
+    pub fn save(&mut self) -> Result<(), io::Error> {
+        fs::create_dir_all(Self::dir(&self.name))?;
+        self.header.format_version = FORMAT_VERSION;
+        let path = Self::index_path(&self.name);
+        let path_new = format!("{path}.new");
+        let mut writer = BufWriter::new(File::create(&path_new)?);
+        ciborium::into_writer(&self.header, &mut writer).map_err(io::Error::other)?;
+        ciborium::into_writer(&self.keys, &mut writer).map_err(io::Error::other)?;
+        writer.into_inner()?.sync_all()?;
+        fs::rename(&path_new, &path)?;
+        for legacy in [
+            Self::meta_path(&self.name),
+            format!("{}/version.txt", Self::dir(&self.name)),
+        ] {
+            if Path::new(&legacy).exists() {
+                fs::remove_file(legacy)?;
+            }
+        }
+        Ok(())
+    }
+    /// Removes revisions from the index. All the tags of the title are selected if `tag` is None, and all the revisions if `revisions` is None. Returns the offsets and sizes of the removed revisions. Their data is kept until [`compact`].
+    pub fn remove(
+        &mut self,
+        title: &str,
+        tag: Option<&str>,
+        revisions: Option<&[u64]>,
+    ) -> Result<Vec<(u64, u64)>, io::Error> {
+        let not_found = || {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Not found in archive {}.", self.name),
+            )
+        };
+        let tags = self.keys.get_mut(title).ok_or_else(not_found)?;
+        let selected: Vec<String> = match tag {
+            Some(tag) if tags.contains_key(tag) => vec![tag.to_string()],
+            Some(_) => return Err(not_found()),
+            None => tags.keys().cloned().collect(),
+        };
+        if let Some(revisions) = revisions {
+            for tag in &selected {
+                if revisions
+                    .iter()
+                    .any(|&revision| revision >= tags[tag].len() as u64)
+                {
+                    return Err(not_found());
+                }
+            }
+        }
+        let mut removed = vec![];
+        for tag in selected {
+            let list = tags.get_mut(&tag).unwrap();
+            let kept = std::mem::take(list)
+                .into_iter()
+                .enumerate()
+                .filter_map(|(revision, entry)| {
+                    if revisions.is_none_or(|revisions| revisions.contains(&(revision as u64))) {
+                        removed.push((entry.offset, entry.datasize));
+                        None
+                    } else {
+                        Some(entry)
+                    }
+                })
+                .collect();
+            *list = kept;
+        }
+        self.prune();
+        Ok(removed)
+    }
+    /// Drops the keys without revisions.
+    fn prune(&mut self) {
+        self.keys.retain(|_, tags| {
+            tags.retain(|_, revisions| !revisions.is_empty());
+            !tags.is_empty()
+        });
+    }
+}
+
+/// Path of the data file of an archive, read from the header without loading the index.
+pub fn data_path(name: &str) -> Result<String, io::Error> {
+    let path = Archive::index_path(name);
+    if !Path::new(&path).exists() {
+        return Ok(format!("{}/{}", Archive::dir(name), default_data_file()));
+    }
+    let header: Header = ciborium::from_reader(BufReader::new(File::open(path)?))
+        .map_err(|err| corrupted(name, err))?;
+    Ok(format!("{}/{}", Archive::dir(name), header.data_file))
+}
+
+fn corrupted(name: &str, err: impl std::fmt::Display) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Archive {name} is corrupted: {err}"),
+    )
+}
+
+fn checksum_mismatch() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        "Checksum of the archived data does not match.",
+    )
+}
+
+/// Fails if the data does not have the checksum. Data without a checksum is not verified.
+pub fn verify(data: &[u8], checksum: Option<u32>) -> Result<(), io::Error> {
+    match checksum {
+        Some(checksum) if crc32fast::hash(data) != checksum => Err(checksum_mismatch()),
+        _ => Ok(()),
+    }
+}
+
+/// Checks that a revision lies within the data file of the given length and matches its checksum, without keeping the data.
+pub fn check(file: &mut File, data_len: u64, entry: &RevisionEntry) -> Result<(), io::Error> {
+    if entry
+        .offset
+        .checked_add(entry.datasize)
+        .is_none_or(|end| end > data_len)
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "Archived data runs past the end of the data file.",
+        ));
+    }
+    file.seek(SeekFrom::Start(entry.offset))?;
+    let mut reader = ChecksumReader::new(file.take(entry.datasize), entry.checksum);
+    io::copy(&mut reader, &mut io::sink())?;
+    Ok(())
+}
+
+/// Computes the checksum of the data read through it. If the expected checksum is given, reaching the end with a different checksum is an error.
+pub struct ChecksumReader<R> {
+    inner: R,
+    hasher: crc32fast::Hasher,
+    expected: Option<u32>,
+}
+
+impl<R: Read> ChecksumReader<R> {
+    pub fn new(inner: R, expected: Option<u32>) -> Self {
+        Self {
+            inner,
+            hasher: crc32fast::Hasher::new(),
+            expected,
+        }
+    }
+    pub fn checksum(&self) -> u32 {
+        self.hasher.clone().finalize()
+    }
+}
+
+impl<R: Read> Read for ChecksumReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let size = self.inner.read(buf)?;
+        self.hasher.update(&buf[..size]);
+        if size == 0 && !buf.is_empty() && self.expected.is_some_and(|val| val != self.checksum()) {
+            return Err(checksum_mismatch());
+        }
+        Ok(size)
+    }
+}
+
+/// Summarizes an archive from its index without reading the data.
+pub fn archive_info(name: &str) -> Result<ArchiveInfo, io::Error> {
+    let archive = Archive::open(name)?;
+    let size = match fs::metadata(archive.data_path()) {
+        Ok(meta) => meta.len(),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => 0,
+        Err(err) => return Err(err),
+    };
+    let keys: Vec<_> = archive
+        .keys
+        .iter()
+        .flat_map(|(title, tags)| {
+            tags.iter().map(|(tag, revisions)| {
+                (
+                    title.clone(),
+                    tag.clone(),
+                    revisions.len() as u64,
+                    revisions.iter().map(|entry| entry.datasize).sum(),
+                )
+            })
+        })
+        .collect();
+    Ok(ArchiveInfo {
+        format_version: archive.header.format_version,
+        created: DateTime::<Local>::from(DateTime::from_timestamp_nanos(archive.header.created))
+            .to_string(),
+        server_version: archive.header.server_version,
+        namespace: archive.header.namespace,
+        size,
+        n_keys: keys.len() as u64,
+        n_revisions: keys.iter().map(|key| key.2).sum(),
+        keys,
+    })
+}
+
+/// Checks the revisions of an archive against the data file. If `repair` is true and the archive is damaged or of format version 1, the index is rewritten with the valid revisions, whose checksums are filled in. The data is kept as it is, so that loaded revisions stay valid.
+pub fn verify_archive(name: &str, repair: bool) -> Result<ArchiveReport, io::Error> {
+    let (mut archive, damage) = Archive::salvage(name)?;
+    let mut report = ArchiveReport {
+        format_version: archive.header.format_version,
+        broken_index: damage.map(|err| err.to_string()),
+        ..Default::default()
+    };
+    let mut file_data = match File::open(archive.data_path()) {
+        Ok(file) => Some(file),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+        Err(err) => return Err(err),
+    };
+    let data_len = match &file_data {
+        Some(file) => file.metadata()?.len(),
+        None => 0,
+    };
+    let mut used = vec![];
+    for (title, tags) in archive.keys.iter_mut() {
+        for (tag, revisions) in tags.iter_mut() {
+            let mut valid = vec![];
+            for (revision, mut entry) in std::mem::take(revisions).into_iter().enumerate() {
+                let position = (title.clone(), tag.clone(), revision as u64);
+                let Some(file) = file_data.as_mut().filter(|_| {
+                    entry
+                        .offset
+                        .checked_add(entry.datasize)
+                        .is_some_and(|end| end <= data_len)
+                }) else {
+                    report.truncated.push(position);
+                    continue;
+                };
+                file.seek(SeekFrom::Start(entry.offset))?;
+                let mut reader = ChecksumReader::new(file.take(entry.datasize), None);
+                io::copy(&mut reader, &mut io::sink())?;
+                if entry
+                    .checksum
+                    .is_some_and(|checksum| checksum != reader.checksum())
+                {
+                    report.corrupted.push(position);
+                    continue;
+                }
+                entry.checksum = Some(reader.checksum());
+                used.push((entry.offset, entry.offset + entry.datasize));
+                valid.push(entry);
+            }
+            report.valid += valid.len() as u64;
+            *revisions = valid;
+        }
+    }
+    used.sort_unstable();
+    let mut covered = 0;
+    let mut end = 0;
+    for (start, stop) in used {
+        covered += stop.saturating_sub(start.max(end));
+        end = end.max(stop);
+    }
+    report.orphaned_bytes = data_len - covered;
+    if repair && (!report.is_intact() || report.format_version < FORMAT_VERSION) {
+        archive.prune();
+        archive.save()?;
+        report.repaired = true;
+    }
+    Ok(report)
+}
+
+/// Rewrites the data of an archive to a new data file, keeping only the revisions in the index. The index is switched to the new file atomically and the old file is removed afterwards. Returns the relocations and the number of bytes reclaimed.
+///
+/// The data is verified while copying, so that corrupted revisions are not carried over silently. Run [`verify_archive`] with repair first if the archive is damaged.
+pub fn compact(name: &str) -> Result<(Vec<Relocation>, u64), io::Error> {
+    let mut archive = Archive::open(name)?;
+    let path_old = archive.data_path();
+    let mut file_old = File::open(&path_old)?;
+    let size_old = file_old.metadata()?.len();
+    let data_file = format!("data-{}.bin", Uuid::new_v4());
+    let path_new = format!("{}/{}", Archive::dir(name), data_file);
+    let mut file_new = BufWriter::new(File::create(&path_new)?);
+    let mut relocations = vec![];
+    let mut offset_new = 0;
+    let copied = archive
+        .keys
+        .values_mut()
+        .flat_map(|tags| tags.values_mut())
+        .flat_map(|revisions| revisions.iter_mut())
+        .try_for_each(|entry| {
+            file_old.seek(SeekFrom::Start(entry.offset))?;
+            let mut reader =
+                ChecksumReader::new((&mut file_old).take(entry.datasize), entry.checksum);
+            let size = io::copy(&mut reader, &mut file_new)?;
+            if size != entry.datasize {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!("Archive {name} is truncated."),
+                ));
+            }
+            relocations.push((entry.offset, entry.datasize, offset_new));
+            entry.offset = offset_new;
+            entry.checksum = Some(reader.checksum());
+            offset_new += size;
+            Ok(())
+        })
+        .and_then(|_| file_new.into_inner()?.sync_all());
+    if let Err(err) = copied {
+        fs::remove_file(&path_new)?;
+        return Err(err);
+    }
+    archive.header.data_file = data_file;
+    archive.save()?;
+    fs::remove_file(&path_old)?;
+    Ok((relocations, size_old.saturating_sub(offset_new)))
+}
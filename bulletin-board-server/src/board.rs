@@ -1,59 +1,639 @@
+use crate::archive::{self, Archive, ChecksumReader, RevisionEntry};
 use crate::bulletin::{Bulletin, BulletinBackend};
-use crate::{logging, ACV_DIR, FILE_THRETHOLD, MAX_RESULTS, TMP_DIR, TOT_MEM_LIMIT};
-use chrono::DateTime;
+use crate::export::Exporter;
+use crate::journal::{self, Checkpoint, Data, DataWriter, Entry, Flush, Journal, Replay};
+use crate::query::Filter;
+use crate::{
+    logging, ACV_DIR, FILE_THRETHOLD, JOURNAL, JOURNAL_LIMIT, MAX_AGE, MAX_BYTES, MAX_RESULTS,
+    MAX_REVISIONS, TMP_DIR, TOT_MEM_LIMIT,
+};
+use bulletin_board_common::{
+    ArchiveInfo, ArchiveReport, Continuation, Query, QueryPage, Retention, RevisionInfo, Selection,
+    SortKey, Status,
+};
+use chrono::{DateTime, Local};
+use std::collections::hash_map::Entry as MapEntry;
 use std::collections::{HashMap, HashSet};
 use std::fs::{self, File};
-use std::io::{self, Cursor, Read, Seek, SeekFrom};
+use std::io::{self, Read, Seek, SeekFrom, Write};
 use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+
+/// Eviction frees memory down to this fraction of the limit, so that it does not run on every post.
+const EVICTION_TARGET: f64 = 0.9;
+
+/// Locks of the keys being posted to. Only the locks in use are kept alive.
+type KeyLocks = HashMap<(String, String), Weak<Mutex<()>>>;
+
+/// Subdirectory of the temporary directory holding the boards of the namespaces other than the default one.
+pub const NAMESPACE_DIR: &str = "namespaces";
+
+/// Temporary directory of the board of a namespace. The default namespace uses the temporary directory itself, as before namespaces were introduced.
+pub fn dir(namespace: Option<&str>) -> String {
+    match namespace {
+        Some(namespace) => format!("{}/{NAMESPACE_DIR}/{namespace}", *TMP_DIR),
+        None => TMP_DIR.clone(),
+    }
+}
+
+/// Namespaces name directories, so that they are limited to alphanumeric characters, `-` and `_`.
+pub fn check_namespace(namespace: &str) -> Result<(), std::io::Error> {
+    let valid = !namespace.is_empty()
+        && namespace.len() <= 64
+        && namespace
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "Invalid namespace: {namespace}. Use up to 64 alphanumeric characters, - and _."
+            ),
+        ))
+    }
+}
+
+/// Revisions of a bulletin. The cleared revisions at the beginning are dropped by the retention policy, and the revision numbers of the others are kept.
+#[derive(Default)]
+pub struct Revisions {
+    first: u64,
+    list: Vec<Bulletin>,
+}
+
+impl Revisions {
+    pub fn get(&self, revision: u64) -> Option<&Bulletin> {
+        let index = usize::try_from(revision.checked_sub(self.first)?).ok()?;
+        self.list.get(index)
+    }
+    fn get_mut(&mut self, revision: u64) -> Option<&mut Bulletin> {
+        let index = usize::try_from(revision.checked_sub(self.first)?).ok()?;
+        self.list.get_mut(index)
+    }
+    /// The latest revision and its number.
+    pub fn latest(&self) -> Option<(u64, &Bulletin)> {
+        self.iter().next_back()
+    }
+    /// Number of revisions including the dropped ones, which is the number of the next revision.
+    pub fn count(&self) -> u64 {
+        self.first + self.list.len() as u64
+    }
+    /// Revisions kept with their numbers.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (u64, &Bulletin)> + ExactSizeIterator {
+        let first = self.first;
+        self.list
+            .iter()
+            .enumerate()
+            .map(move |(i, bulletin)| (first + i as u64, bulletin))
+    }
+    fn push(&mut self, bulletin: Bulletin) {
+        self.list.push(bulletin);
+    }
+    /// Drops the cleared revisions at the beginning. The latest revision is kept even if it is cleared.
+    fn prune(&mut self) {
+        let n = self
+            .list
+            .iter()
+            .take(self.list.len().saturating_sub(1))
+            .take_while(|bulletin| matches!(bulletin.data, BulletinBackend::Empty))
+            .count();
+        self.list.drain(..n);
+        self.first += n as u64;
+    }
+}
+
+impl<'a> IntoIterator for &'a Revisions {
+    type Item = &'a Bulletin;
+    type IntoIter = std::slice::Iter<'a, Bulletin>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.list.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut Revisions {
+    type Item = &'a mut Bulletin;
+    type IntoIter = std::slice::IterMut<'a, Bulletin>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.list.iter_mut()
+    }
+}
 
 pub struct BulletinBoard {
     datasize: u64,
     memory_used: u64,
+    /// Memory used by the boards of all the namespaces, which is limited by `BB_TOT_MEM_LIMIT`.
+    shared_memory: Arc<AtomicU64>,
     n_bulletins: u64,
     n_files: u64,
     n_archives: u64,
-    bulletins: HashMap<(String, String), Vec<Bulletin>>,
+    n_evicted: u64,
+    n_promoted: u64,
+    n_retired: u64,
+    bytes_retired: u64,
+    bulletins: HashMap<(String, String), Revisions>,
     loaded: HashMap<String, HashSet<(String, String)>>,
+    retention: HashMap<(String, Option<String>), Retention>,
+    journal: Option<Journal>,
+    /// Locks serializing the posts to each key. See [`BulletinBoard::key_lock`].
+    key_locks: Mutex<KeyLocks>,
+    namespace: Option<String>,
+    dir: String,
 }
 
 impl BulletinBoard {
-    pub fn new() -> Result<Self, std::io::Error> {
-        if Path::new(&*TMP_DIR).exists() {
-            fs::remove_dir_all(&*TMP_DIR)?;
+    /// Opens the board of a namespace, `None` being the default one. In the journal mode, the board is recovered from the journal left in its temporary directory.
+    pub fn new(
+        namespace: Option<String>,
+        shared_memory: Arc<AtomicU64>,
+    ) -> Result<Self, std::io::Error> {
+        let dir = dir(namespace.as_deref());
+        let journal_path = journal::path(&dir);
+        let recovering = *JOURNAL && Path::new(&journal_path).exists();
+        if Path::new(&dir).exists() {
+            for entry in fs::read_dir(&dir)? {
+                let path = entry?.path();
+                // The boards of the other namespaces live in the directory of the default one.
+                if recovering && path == Path::new(&journal_path)
+                    || namespace.is_none() && path.ends_with(NAMESPACE_DIR)
+                {
+                    continue;
+                } else if path.is_dir() {
+                    fs::remove_dir_all(path)?;
+                } else {
+                    fs::remove_file(path)?;
+                }
+            }
         }
-        Ok(Self {
+        let mut board = Self {
             memory_used: 0,
+            shared_memory,
             datasize: 0,
             n_bulletins: 0,
             n_files: 0,
             n_archives: 0,
+            n_evicted: 0,
+            n_promoted: 0,
+            n_retired: 0,
+            bytes_retired: 0,
             bulletins: HashMap::new(),
             loaded: HashMap::new(),
-        })
+            retention: HashMap::new(),
+            journal: None,
+            key_locks: Mutex::new(HashMap::new()),
+            namespace,
+            dir,
+        };
+        if recovering {
+            board.recover()?;
+        }
+        if *JOURNAL {
+            fs::create_dir_all(&board.dir)?;
+            board.journal = Some(board.checkpoint()?.commit()?);
+        }
+        Ok(board)
+    }
+    fn recover(&mut self) -> Result<(), std::io::Error> {
+        let mut n_entries = 0;
+        // Data of the posts waiting for their entries.
+        let mut data = HashMap::new();
+        for (entry, bulletin) in Replay::open(&self.dir)? {
+            n_entries += 1;
+            let replayed = match entry {
+                Entry::Post(title, tag, _, timestamp, attributes) => {
+                    let mut bulletin = bulletin.unwrap();
+                    bulletin.timestamp = DateTime::from_timestamp_nanos(timestamp).into();
+                    bulletin.attributes = attributes;
+                    self.add((title, tag), bulletin).map(|_| ())
+                }
+                Entry::Data(number, _) => {
+                    data.insert(number, bulletin.unwrap());
+                    Ok(())
+                }
+                Entry::Posted(title, tag, number, timestamp, attributes) => {
+                    match data.remove(&number) {
+                        Some(mut bulletin) => {
+                            bulletin.timestamp = DateTime::from_timestamp_nanos(timestamp).into();
+                            bulletin.attributes = attributes;
+                            self.add((title, tag), bulletin).map(|_| ())
+                        }
+                        None => Err(std::io::Error::new(
+                            std::io::ErrorKind::NotFound,
+                            format!("Data {number} of {title}:{tag} is missing."),
+                        )),
+                    }
+                }
+                Entry::Relabel(title_from, tag_from, title_to, tag_to) => {
+                    self.relabel(title_from, tag_from, title_to, tag_to)
+                }
+                Entry::Clear(title, tag, revisions) => self.clear_bulletins(title, tag, revisions),
+                Entry::Retire(title, tag, revisions) => self.retire(&(title, tag), revisions),
+                Entry::Remove(title, tag) => self.remove(title, tag),
+                Entry::Load(acv_name) => self.load(acv_name, &Selection::default()),
+                Entry::Unload(acv_name) => self.unload(acv_name),
+                Entry::LoadSelected(acv_name, selection) => self.load(acv_name, &selection),
+                Entry::Archived(
+                    title,
+                    tag,
+                    acv_name,
+                    offset,
+                    datasize,
+                    timestamp,
+                    checksum,
+                    attributes,
+                ) => {
+                    let mut bulletin = Bulletin::from_archive(
+                        &acv_name,
+                        offset,
+                        datasize,
+                        DateTime::from_timestamp_nanos(timestamp).into(),
+                    );
+                    bulletin.attributes = attributes;
+                    bulletin.checksum = checksum;
+                    self.datasize += datasize;
+                    self.n_bulletins += 1;
+                    self.n_archives += 1;
+                    self.bulletins
+                        .entry((title, tag))
+                        .or_default()
+                        .push(bulletin);
+                    Ok(())
+                }
+                Entry::Deleted(title, tag, datasize, timestamp) => {
+                    let mut bulletin = Bulletin::from_data(vec![]);
+                    bulletin.data = BulletinBackend::Empty;
+                    bulletin.datasize = datasize;
+                    bulletin.timestamp = DateTime::from_timestamp_nanos(timestamp).into();
+                    self.bulletins
+                        .entry((title, tag))
+                        .or_default()
+                        .push(bulletin);
+                    Ok(())
+                }
+                Entry::Loaded(acv_name, keys) => {
+                    self.loaded.insert(acv_name, keys.into_iter().collect());
+                    Ok(())
+                }
+                Entry::Pruned(title, tag, first) => {
+                    self.bulletins.entry((title, tag)).or_default().first = first;
+                    Ok(())
+                }
+                // The revisions cleared by the policy are replayed by their own entries.
+                Entry::SetRetention(title, tag, retention) => {
+                    self.store_retention(title, tag, retention);
+                    Ok(())
+                }
+            };
+            if let Err(err) = replayed {
+                logging::warn(format!("Failed to replay a journal entry: {err}"));
+            }
+        }
+        // The data of the posts that were not on the board at the crash.
+        for mut bulletin in data.into_values() {
+            bulletin.clear()?;
+        }
+        logging::notice(format!(
+            "Recovered the board{} from {n_entries} journal entries.",
+            self.describe()
+        ));
+        Ok(())
+    }
+    /// Writes the current state to a new journal so that the journal does not keep the history.
+    fn checkpoint(&self) -> Result<Checkpoint, std::io::Error> {
+        let mut checkpoint = Checkpoint::new(&self.dir)?;
+        for ((title, tag), list) in &self.bulletins {
+            if list.first > 0 {
+                checkpoint.write(&Entry::Pruned(title.clone(), tag.clone(), list.first))?;
+            }
+            for bulletin in list {
+                let timestamp = bulletin.timestamp.timestamp_nanos_opt().unwrap();
+                match &bulletin.data {
+                    BulletinBackend::Memory(_) | BulletinBackend::File(_) => {
+                        checkpoint.write_post(title, tag, bulletin)?;
+                    }
+                    BulletinBackend::Archive((acv_name, offset)) => {
+                        checkpoint.write(&Entry::Archived(
+                            title.clone(),
+                            tag.clone(),
+                            acv_name.clone(),
+                            *offset,
+                            bulletin.datasize,
+                            timestamp,
+                            bulletin.checksum,
+                            bulletin.attributes.clone(),
+                        ))?;
+                    }
+                    BulletinBackend::Empty => {
+                        checkpoint.write(&Entry::Deleted(
+                            title.clone(),
+                            tag.clone(),
+                            bulletin.datasize,
+                            timestamp,
+                        ))?;
+                    }
+                }
+            }
+        }
+        for (acv_name, keys) in &self.loaded {
+            checkpoint.write(&Entry::Loaded(
+                acv_name.clone(),
+                keys.iter().cloned().collect(),
+            ))?;
+        }
+        for ((title, tag), retention) in &self.retention {
+            checkpoint.write(&Entry::SetRetention(
+                title.clone(),
+                tag.clone(),
+                Some(retention.clone()),
+            ))?;
+        }
+        Ok(checkpoint)
+    }
+    /// Replaces the journal by a checkpoint. This is needed when the index of a loaded archive changes, since replaying the journal reads the current index.
+    fn rewrite_journal(&mut self) -> Result<(), std::io::Error> {
+        if self.journal.is_some() {
+            self.journal = Some(self.checkpoint()?.commit()?);
+        }
+        Ok(())
     }
+    /// Rewrites the journal if it exceeds the limit and twice the data on the board, which happens as the retention policy or removals free data. The cost of a checkpoint is thus spread over the posts.
+    fn limit_journal(&mut self) -> Result<(), std::io::Error> {
+        let Some(journal) = &self.journal else {
+            return Ok(());
+        };
+        let size = journal.size();
+        if size <= (*JOURNAL_LIMIT).max(2 * self.datasize) {
+            return Ok(());
+        }
+        self.rewrite_journal()?;
+        logging::info(format!(
+            "Rewrote the journal{} of {size} bytes with the current state.",
+            self.describe()
+        ));
+        Ok(())
+    }
+    fn record(&mut self, entry: Entry) -> Result<(), std::io::Error> {
+        match &mut self.journal {
+            Some(journal) => journal.append(&entry),
+            None => Ok(()),
+        }
+    }
+    /// In the journal mode, the post is written to the journal but not flushed. Wait for [`BulletinBoard::flush`] after unlocking the board.
+    ///
+    /// `data` is the data written to the journal by the [`BulletinBoard::journal_writer`] before locking the board, if any. The post is recorded only once it is placed on the board.
     pub fn post(
         &mut self,
         title: String,
         tag: String,
         mut bulletin: Bulletin,
-    ) -> Result<(), std::io::Error> {
+        data: Option<Data>,
+    ) -> Result<u64, std::io::Error> {
+        let in_memory = self.place(&mut bulletin)?;
+        let recorded = match &mut self.journal {
+            Some(journal) => journal.write_post(&title, &tag, &bulletin, data),
+            None => Ok(()),
+        };
+        if let Err(err) = recorded {
+            if in_memory {
+                self.shared_memory
+                    .fetch_sub(bulletin.datasize, Ordering::Relaxed);
+            }
+            bulletin.clear()?;
+            return Err(err);
+        }
         let key = (title, tag);
-        if bulletin.datasize < *FILE_THRETHOLD
-            && self.memory_used + bulletin.datasize < *TOT_MEM_LIMIT
-        {
+        let revision = self.push(key.clone(), bulletin, in_memory);
+        self.apply_retention(&key)?;
+        self.limit_journal()?;
+        Ok(revision)
+    }
+    /// Adds a bulletin without recording it or applying the retention policy, which is how the journal is replayed.
+    fn add(
+        &mut self,
+        key: (String, String),
+        mut bulletin: Bulletin,
+    ) -> Result<u64, std::io::Error> {
+        let in_memory = self.place(&mut bulletin)?;
+        Ok(self.push(key, bulletin, in_memory))
+    }
+    /// Keeps the data in memory if it fits, or saves it to a file otherwise. Returns whether it is kept in memory, in which case the memory is reserved.
+    fn place(&mut self, bulletin: &mut Bulletin) -> Result<bool, std::io::Error> {
+        if let BulletinBackend::File(_) = bulletin.data {
+            Ok(false)
+        } else if bulletin.datasize < *FILE_THRETHOLD && self.reserve_memory(bulletin.datasize)? {
+            self.shared_memory
+                .fetch_add(bulletin.datasize, Ordering::Relaxed);
+            Ok(true)
+        } else {
+            bulletin.save_to_file(&self.dir)?;
+            Ok(false)
+        }
+    }
+    fn push(&mut self, key: (String, String), bulletin: Bulletin, in_memory: bool) -> u64 {
+        if in_memory {
             self.memory_used += bulletin.datasize;
         } else {
-            bulletin.save_to_file()?;
             self.n_files += 1;
         }
         self.n_bulletins += 1;
         self.datasize += bulletin.datasize;
         let entry = self.bulletins.entry(key).or_default();
         entry.push(bulletin);
+        entry.count() - 1
+    }
+    /// Writer of the data of posts to the journal, which is used without locking the board. `None` unless in the journal mode.
+    pub fn journal_writer(&self) -> Option<DataWriter> {
+        self.journal.as_ref().map(Journal::writer)
+    }
+    /// Lock of a key held while posting to it. Posts to the same key are thus numbered in the order they started, while posts to the other keys go on in parallel and only share the short insertion under the lock of the board.
+    pub fn key_lock(&self, title: &str, tag: &str) -> Arc<Mutex<()>> {
+        let mut key_locks = self.key_locks.lock().unwrap();
+        let key = (title.to_string(), tag.to_string());
+        if let Some(lock) = key_locks.get(&key).and_then(Weak::upgrade) {
+            return lock;
+        }
+        // The locks of the keys no longer posted to are dropped as new ones are made.
+        key_locks.retain(|_, lock| lock.strong_count() > 0);
+        let lock = Arc::new(Mutex::new(()));
+        key_locks.insert(key, Arc::downgrade(&lock));
+        lock
+    }
+    /// Makes room for data of the given size by saving the least recently read bulletins to files. Returns whether the data fits in memory. Only the bulletins of this board are saved, while the limit applies to all the namespaces.
+    fn reserve_memory(&mut self, size: u64) -> Result<bool, std::io::Error> {
+        if self.shared_memory.load(Ordering::Relaxed) + size < *TOT_MEM_LIMIT {
+            return Ok(true);
+        }
+        if size >= *TOT_MEM_LIMIT {
+            return Ok(false);
+        }
+        let target = (*TOT_MEM_LIMIT as f64 * EVICTION_TARGET) as u64;
+        let mut candidates: Vec<_> = self
+            .bulletins
+            .values_mut()
+            .flatten()
+            .filter(|bulletin| matches!(bulletin.data, BulletinBackend::Memory(_)))
+            .collect();
+        candidates.sort_unstable_by_key(|bulletin| bulletin.last_access());
+        let mut evicted = 0;
+        for bulletin in candidates {
+            if self.shared_memory.load(Ordering::Relaxed) + size <= target {
+                break;
+            }
+            bulletin.save_to_file(&self.dir)?;
+            self.memory_used -= bulletin.datasize;
+            self.shared_memory
+                .fetch_sub(bulletin.datasize, Ordering::Relaxed);
+            self.n_files += 1;
+            evicted += 1;
+        }
+        self.n_evicted += evicted;
+        if evicted > 0 {
+            logging::info(format!(
+                "Saved {evicted} bulletins to files to free memory."
+            ));
+        }
+        Ok(self.shared_memory.load(Ordering::Relaxed) + size < *TOT_MEM_LIMIT)
+    }
+    /// Moves frequently read revisions saved to files back to memory if there is room without eviction.
+    pub fn promote(
+        &mut self,
+        title: String,
+        tag: String,
+        revisions: Vec<u64>,
+    ) -> Result<(), std::io::Error> {
+        let Some(list) = self.bulletins.get_mut(&(title, tag)) else {
+            return Ok(());
+        };
+        for revision in revisions {
+            let Some(bulletin) = list.get_mut(revision) else {
+                continue;
+            };
+            if !bulletin.is_hot()
+                || self.shared_memory.load(Ordering::Relaxed) + bulletin.datasize >= *TOT_MEM_LIMIT
+            {
+                continue;
+            }
+            bulletin.load_to_memory()?;
+            self.memory_used += bulletin.datasize;
+            self.shared_memory
+                .fetch_add(bulletin.datasize, Ordering::Relaxed);
+            self.n_files -= 1;
+            self.n_promoted += 1;
+        }
         Ok(())
     }
-    pub fn take(&mut self, title: String, tag: String) -> Option<&mut Vec<Bulletin>> {
-        self.bulletins.get_mut(&(title, tag))
+    pub fn set_retention(
+        &mut self,
+        title: String,
+        tag: Option<String>,
+        retention: Option<Retention>,
+    ) -> Result<(), std::io::Error> {
+        self.store_retention(title.clone(), tag.clone(), retention.clone());
+        self.record(Entry::SetRetention(title.clone(), tag.clone(), retention))?;
+        let keys: Vec<_> = self
+            .bulletins
+            .keys()
+            .filter(|key| key.0 == title && tag.as_ref().is_none_or(|tag| key.1 == *tag))
+            .cloned()
+            .collect();
+        for key in keys {
+            self.apply_retention(&key)?;
+        }
+        self.limit_journal()
+    }
+    fn store_retention(
+        &mut self,
+        title: String,
+        tag: Option<String>,
+        retention: Option<Retention>,
+    ) {
+        match retention {
+            Some(retention) => {
+                self.retention.insert((title, tag), retention);
+            }
+            None => {
+                self.retention.remove(&(title, tag));
+            }
+        }
+    }
+    fn retention_of(&self, title: &str, tag: &str) -> Retention {
+        self.retention
+            .get(&(title.to_string(), Some(tag.to_string())))
+            .or_else(|| self.retention.get(&(title.to_string(), None)))
+            .cloned()
+            .unwrap_or_else(|| Retention {
+                max_revisions: *MAX_REVISIONS,
+                max_age: *MAX_AGE,
+                max_bytes: *MAX_BYTES,
+            })
+    }
+    /// Clears the revisions exceeding the retention policy, from the newest to the oldest. The latest revision is always kept.
+    fn apply_retention(&mut self, key: &(String, String)) -> Result<(), std::io::Error> {
+        let retention = self.retention_of(&key.0, &key.1);
+        if retention == Retention::default() {
+            return Ok(());
+        }
+        let Some(list) = self.bulletins.get(key) else {
+            return Ok(());
+        };
+        let now = Local::now();
+        let mut kept = 0;
+        let mut bytes = 0;
+        let mut expired_revisions = vec![];
+        for (revision, bulletin) in list.iter().rev() {
+            if let BulletinBackend::Empty | BulletinBackend::Archive(_) = bulletin.data {
+                continue;
+            }
+            let expired = kept > 0
+                && (retention.max_revisions.is_some_and(|max| kept >= max)
+                    || retention.max_age.is_some_and(|max| {
+                        (now - bulletin.timestamp).num_seconds()
+                            > max.try_into().unwrap_or(i64::MAX)
+                    })
+                    || retention
+                        .max_bytes
+                        .is_some_and(|max| bytes + bulletin.datasize > max));
+            if expired {
+                expired_revisions.push(revision);
+            } else {
+                kept += 1;
+                bytes += bulletin.datasize;
+            }
+        }
+        if expired_revisions.is_empty() {
+            return Ok(());
+        }
+        let cleared = expired_revisions.len();
+        self.retire(key, expired_revisions.clone())?;
+        self.record(Entry::Retire(
+            key.0.clone(),
+            key.1.clone(),
+            expired_revisions,
+        ))?;
+        logging::info(format!(
+            "Retention policy cleared {cleared} revisions (title: {}, tag: {}).",
+            key.0, key.1
+        ));
+        Ok(())
+    }
+    /// Clears the revisions expired by the retention policy and drops the cleared revisions at the beginning.
+    fn retire(
+        &mut self,
+        key: &(String, String),
+        revisions: Vec<u64>,
+    ) -> Result<(), std::io::Error> {
+        let (n_bulletins, datasize) = (self.n_bulletins, self.datasize);
+        self.clear_bulletins(key.0.clone(), key.1.clone(), revisions)?;
+        self.n_retired += n_bulletins - self.n_bulletins;
+        self.bytes_retired += datasize - self.datasize;
+        if let Some(list) = self.bulletins.get_mut(key) {
+            list.prune();
+        }
+        Ok(())
+    }
+    pub fn get(&self, title: String, tag: String) -> Option<&Revisions> {
+        self.bulletins.get(&(title, tag))
     }
     pub fn find_tags(&self, title: &String) -> Vec<String> {
         self.bulletins
@@ -69,6 +649,12 @@ impl BulletinBoard {
         title_to: Option<String>,
         tag_to: Option<String>,
     ) -> Result<(), std::io::Error> {
+        let entry = Entry::Relabel(
+            title_from.clone(),
+            tag_from.clone(),
+            title_to.clone(),
+            tag_to.clone(),
+        );
         match self
             .bulletins
             .remove(&(title_from.clone(), tag_from.clone()))
@@ -76,15 +662,30 @@ impl BulletinBoard {
             Some(mut bulletins) => {
                 let title_to = match title_to {
                     Some(val) => val,
-                    None => title_from,
+                    None => title_from.clone(),
                 };
                 let tag_to = match tag_to {
                     Some(val) => val,
-                    None => tag_from,
+                    None => tag_from.clone(),
                 };
-                let entry = self.bulletins.entry((title_to, tag_to)).or_default();
-                entry.append(&mut bulletins);
-                Ok(())
+                // The loaded bulletins are unloaded under the new key.
+                let key_from = (title_from, tag_from);
+                let key_to = (title_to, tag_to);
+                for keys in self.loaded.values_mut() {
+                    if keys.remove(&key_from) {
+                        keys.insert(key_to.clone());
+                    }
+                }
+                // A new key keeps the revision numbers.
+                match self.bulletins.entry(key_to) {
+                    MapEntry::Vacant(entry) => {
+                        entry.insert(bulletins);
+                    }
+                    MapEntry::Occupied(mut entry) => {
+                        entry.get_mut().list.append(&mut bulletins.list)
+                    }
+                }
+                self.record(entry)
             }
             None => Err(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
@@ -92,7 +693,7 @@ impl BulletinBoard {
             )),
         }
     }
-    pub fn status(&self) -> (u64, u64, f64, u64, u64, u64) {
+    pub fn status(&self) -> Status {
         (
             self.datasize,
             self.memory_used,
@@ -100,6 +701,10 @@ impl BulletinBoard {
             self.n_bulletins,
             self.n_files,
             self.n_archives,
+            self.n_evicted,
+            self.n_promoted,
+            self.n_retired,
+            self.bytes_retired,
         )
     }
     pub fn view(&self) -> Vec<(String, String, u64)> {
@@ -109,30 +714,115 @@ impl BulletinBoard {
         self.bulletins
             .iter()
             .take(*MAX_RESULTS)
-            .map(|((title, tag), v)| (title.clone(), tag.clone(), v.len() as u64))
+            .map(|((title, tag), v)| (title.clone(), tag.clone(), v.count()))
             .collect()
     }
-    pub fn get_info(&self, title: String, tag: String) -> Option<Vec<(u64, u64, String, String)>> {
+    pub fn query(&self, query: &Query, filter: &Filter) -> QueryPage {
+        let mut list: Vec<_> = self
+            .bulletins
+            .iter()
+            .filter(|((title, tag), v)| {
+                filter.matches(title, tag)
+                    && filter.matches_attributes(v.into_iter().map(|bulletin| &bulletin.attributes))
+            })
+            .map(|((title, tag), v)| {
+                let key = match query.sort {
+                    SortKey::Name => 0,
+                    SortKey::LastModified => v
+                        .into_iter()
+                        .filter_map(|bulletin| bulletin.timestamp.timestamp_nanos_opt())
+                        .max()
+                        .unwrap_or(0)
+                        .max(0) as u64,
+                    SortKey::Size => v
+                        .into_iter()
+                        .filter(|bulletin| !matches!(bulletin.data, BulletinBackend::Empty))
+                        .map(|bulletin| bulletin.datasize)
+                        .sum(),
+                };
+                let position = Continuation {
+                    key,
+                    title: title.clone(),
+                    tag: tag.clone(),
+                };
+                (position, v.count())
+            })
+            .collect();
+        if query.descending {
+            list.sort_unstable_by(|a, b| b.0.cmp(&a.0));
+        } else {
+            list.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        }
+        let start = match &query.after {
+            Some(after) if query.descending => list.partition_point(|(pos, _)| pos >= after),
+            Some(after) => list.partition_point(|(pos, _)| pos <= after),
+            None => 0,
+        };
+        let start = start.saturating_add(query.offset.try_into().unwrap_or(usize::MAX));
+        let limit = query
+            .limit
+            .map_or(*MAX_RESULTS, |limit| limit.try_into().unwrap_or(usize::MAX))
+            .min(*MAX_RESULTS);
+        let page: Vec<_> = list.into_iter().skip(start).collect();
+        let next = if page.len() > limit {
+            Some(page[limit - 1].0.clone())
+        } else {
+            None
+        };
+        let bulletins = page
+            .into_iter()
+            .take(limit)
+            .map(|(pos, revisions)| (pos.title, pos.tag, revisions))
+            .collect();
+        QueryPage { bulletins, next }
+    }
+    pub fn get_info(&self, title: String, tag: String) -> Option<Vec<RevisionInfo>> {
         let bulletin = self.bulletins.get(&(title, tag))?;
         let mut info = vec![];
-        if bulletin.len() > *MAX_RESULTS {
+        if bulletin.iter().len() > *MAX_RESULTS {
             logging::warn("List is truncated (view_board).".to_string());
         }
-        for (i, val) in bulletin.iter().take(*MAX_RESULTS).enumerate() {
+        for (i, val) in bulletin.iter().take(*MAX_RESULTS) {
             info.push((
-                i as u64,
+                i,
                 val.datasize,
                 val.timestamp.to_string(),
                 val.backend(),
+                val.attributes.clone(),
             ));
         }
         Some(info)
     }
+    /// Revisions loaded from an archive cannot be cleared; they are deleted by `delete_from_archive`.
     pub fn clear_revisions(
         &mut self,
         title: String,
         tag: String,
         revisions: Vec<u64>,
+    ) -> Result<(), std::io::Error> {
+        if let Some(list) = self.bulletins.get(&(title.clone(), tag.clone())) {
+            let archived = revisions.iter().any(|&revision| {
+                matches!(
+                    list.get(revision).map(|bulletin| &bulletin.data),
+                    Some(BulletinBackend::Archive(_))
+                )
+            });
+            if archived {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "Archived entry cannot be cleared.",
+                ));
+            }
+        }
+        self.clear_bulletins(title.clone(), tag.clone(), revisions.clone())?;
+        self.record(Entry::Clear(title, tag, revisions))
+    }
+    /// Revisions loaded from an archive are only unloaded.
+    fn clear_bulletins(
+        &mut self,
+        title: String,
+        tag: String,
+        revisions: Vec<u64>,
     ) -> Result<(), std::io::Error> {
         let list = self
             .bulletins
@@ -142,31 +832,36 @@ impl BulletinBoard {
                 "Not found.",
             ))?;
         for revision in revisions {
-            let bulletin =
-                list.get_mut::<usize>(revision.try_into().unwrap())
-                    .ok_or(std::io::Error::new(
-                        std::io::ErrorKind::NotFound,
-                        "Not found.",
-                    ))?;
+            // Dropped by the retention policy.
+            if revision < list.first {
+                continue;
+            }
+            let bulletin = list.get_mut(revision).ok_or(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "Not found.",
+            ))?;
+            match &bulletin.data {
+                BulletinBackend::Empty => continue,
+                BulletinBackend::Archive(_) => self.n_archives -= 1,
+                _ => {}
+            }
             let (datasize, mem_size, n_file) = bulletin.clear()?;
             self.datasize -= datasize;
             self.n_bulletins -= 1;
             self.n_files -= n_file;
             self.memory_used -= mem_size;
+            self.shared_memory.fetch_sub(mem_size, Ordering::Relaxed);
         }
         Ok(())
     }
+    /// Revisions loaded from an archive are only unloaded.
     pub fn remove(&mut self, title: String, tag: String) -> Result<(), std::io::Error> {
-        match self.bulletins.remove(&(title, tag)) {
+        match self.bulletins.remove(&(title.clone(), tag.clone())) {
             Some(mut bulletins) => {
-                for bulletin in &mut bulletins {
-                    let (datasize, mem_size, n_file) = bulletin.clear()?;
-                    self.datasize -= datasize;
-                    self.n_bulletins -= 1;
-                    self.n_files -= n_file;
-                    self.memory_used -= mem_size;
-                }
-                Ok(())
+                // The key is gone even if a file of its data is left.
+                let cleared = self.clear_all(&mut bulletins.list);
+                self.record(Entry::Remove(title, tag))?;
+                cleared
             }
             None => Err(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
@@ -174,169 +869,466 @@ impl BulletinBoard {
             )),
         }
     }
+    fn clear_all(&mut self, bulletins: &mut [Bulletin]) -> Result<(), std::io::Error> {
+        for bulletin in bulletins {
+            match bulletin.data {
+                BulletinBackend::Empty => continue,
+                BulletinBackend::Archive(_) => self.n_archives -= 1,
+                _ => {}
+            }
+            let (datasize, mem_size, n_file) = bulletin.clear()?;
+            self.datasize -= datasize;
+            self.n_bulletins -= 1;
+            self.n_files -= n_file;
+            self.memory_used -= mem_size;
+            self.shared_memory.fetch_sub(mem_size, Ordering::Relaxed);
+        }
+        Ok(())
+    }
     pub fn archive(
         &mut self,
         acv_name: String,
         title: String,
         tag: String,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        match self.bulletins.remove(&(title.clone(), tag.clone())) {
-            Some(mut rev_list) => {
-                let dir = format!("{}/{}", *ACV_DIR, acv_name);
-                if !Path::new(&dir).exists() {
-                    fs::create_dir_all(&dir)?;
-                    std::fs::write(dir.clone() + "/version.txt", env!("CARGO_PKG_VERSION"))?;
-                }
-                let filename_data = dir.clone() + "/data.bin";
-                let filename_meta = dir + "/meta.bin";
-                let mut file_data = File::options()
-                    .write(true)
-                    .create(true)
-                    .truncate(false)
-                    .open(&filename_data)?;
-                let mut file_meta = File::options()
-                    .write(true)
-                    .create(true)
-                    .truncate(false)
-                    .open(&filename_meta)?;
-                file_data.seek(SeekFrom::End(0))?;
-                file_meta.seek(SeekFrom::End(0))?;
-                let mut temp = vec![];
-                let mut buffer = Cursor::new(vec![]);
-                for bulletin in &mut rev_list {
-                    match &mut bulletin.data {
-                        BulletinBackend::Archive(_) => {}
-                        BulletinBackend::Empty => {}
-                        _ => {
-                            let offset = file_data.stream_position()?;
-                            io::copy(bulletin, &mut file_data)?;
-                            ciborium::into_writer(
-                                &(
-                                    offset,
-                                    bulletin.datasize,
-                                    bulletin.timestamp.timestamp_nanos_opt().unwrap(),
-                                ),
-                                &mut buffer,
-                            )?;
-                            temp.push(Bulletin::from_archive(
-                                &acv_name,
-                                offset,
-                                bulletin.datasize,
-                                bulletin.timestamp,
-                            ));
-                            let (_, mem_size, n_file) = bulletin.clear()?;
-                            self.n_files -= n_file;
-                            self.memory_used -= mem_size;
-                            self.n_bulletins -= 1;
-                        }
+        if !self.bulletins.contains_key(&(title.clone(), tag.clone())) {
+            return Err(Box::new(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "Not found.",
+            )));
+        }
+        self.archive_keys(acv_name, vec![(title, tag)])
+    }
+    /// Appends the revisions of the keys to the data file and updates the index once at the end.
+    fn archive_keys(
+        &mut self,
+        acv_name: String,
+        keys: Vec<(String, String)>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if keys.is_empty() {
+            return Ok(());
+        }
+        let mut archive = Archive::open_or_new(&acv_name, self.namespace.as_deref())?;
+        fs::create_dir_all(Archive::dir(&acv_name))?;
+        let mut file_data = File::options()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(archive.data_path())?;
+        file_data.seek(SeekFrom::End(0))?;
+        for (title, tag) in keys {
+            let Some(mut rev_list) = self.bulletins.remove(&(title.clone(), tag.clone())) else {
+                continue;
+            };
+            self.record(Entry::Remove(title.clone(), tag.clone()))?;
+            let mut revisions = vec![];
+            for bulletin in &mut rev_list {
+                match &mut bulletin.data {
+                    BulletinBackend::Archive(_) => {}
+                    BulletinBackend::Empty => {}
+                    _ => {
+                        let offset = file_data.stream_position()?;
+                        let mut reader = ChecksumReader::new(&mut *bulletin, None);
+                        io::copy(&mut reader, &mut file_data)?;
+                        let checksum = reader.checksum();
+                        revisions.push(RevisionEntry {
+                            offset,
+                            datasize: bulletin.datasize,
+                            timestamp: bulletin.timestamp.timestamp_nanos_opt().unwrap(),
+                            checksum: Some(checksum),
+                            attributes: std::mem::take(&mut bulletin.attributes),
+                        });
+                        let (_, mem_size, n_file) = bulletin.clear()?;
+                        self.n_files -= n_file;
+                        self.memory_used -= mem_size;
+                        self.shared_memory.fetch_sub(mem_size, Ordering::Relaxed);
+                        self.n_bulletins -= 1;
                     }
                 }
-                if !temp.is_empty() {
-                    ciborium::into_writer(&(title, tag, temp.len() as u64), &mut file_meta)?;
-                    buffer.set_position(0);
-                    io::copy(&mut buffer, &mut file_meta)?;
-                } else {
-                    logging::warn("All revisions are already in archives.".to_string());
+            }
+            if !revisions.is_empty() {
+                archive
+                    .keys
+                    .entry(title)
+                    .or_default()
+                    .entry(tag)
+                    .or_default()
+                    .append(&mut revisions);
+            } else {
+                logging::warn("All revisions are already in archives.".to_string());
+            }
+        }
+        file_data.sync_all()?;
+        archive.save()?;
+        Ok(())
+    }
+    /// Returns the revisions of an archive selected by title, tag and revision range.
+    fn select(
+        archive: Archive,
+        selection: &Selection,
+    ) -> Result<Vec<(String, String, Vec<RevisionEntry>)>, std::io::Error> {
+        let filter = Filter::from_selection(selection)?;
+        let mut selected = vec![];
+        for (title, tags) in archive.keys {
+            for (tag, revisions) in tags {
+                if !filter.matches(&title, &tag) {
+                    continue;
+                }
+                let revisions: Vec<_> = revisions
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(revision, _)| {
+                        selection
+                            .revisions
+                            .as_ref()
+                            .is_none_or(|range| range.contains(&(*revision as u64)))
+                    })
+                    .map(|(_, entry)| entry)
+                    .collect();
+                if !revisions.is_empty() {
+                    selected.push((title.clone(), tag, revisions));
                 }
-                Ok(())
             }
-            None => Err(Box::new(std::io::Error::new(
+        }
+        if selected.is_empty() && *selection != Selection::default() {
+            return Err(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
-                "Not found.",
-            ))),
+                format!(
+                    "No revision in archive {} matches the selection.",
+                    archive.name
+                ),
+            ));
         }
+        Ok(selected)
     }
-    pub fn load(&mut self, acv_name: String) -> Result<(), std::io::Error> {
-        let filename_meta = format!("{}/{}/meta.bin", *ACV_DIR, acv_name);
-        let mut file_meta = File::open(&filename_meta)?;
+    /// Loads the selected revisions of an archive. The keys loaded before from the archive are replaced if they match the selection.
+    pub fn load(&mut self, acv_name: String, selection: &Selection) -> Result<(), std::io::Error> {
+        let selected = Self::select(Archive::open(&acv_name)?, selection)?;
+        self.unload_keys(&acv_name, selection)?;
         let loaded = self.loaded.entry(acv_name.clone()).or_default();
-        for key in loaded.iter() {
-            self.bulletins.remove(key);
-        }
-
-        while let Ok((title, tag, revisions)) =
-            ciborium::from_reader::<(String, String, u64), _>(&mut file_meta)
-        {
-            let key = (title, format!("{acv_name}:{tag}"));
+        for (title, tag, revisions) in selected {
+            let key = (title.clone(), format!("{acv_name}:{tag}"));
             loaded.insert(key.clone());
             let entry = self.bulletins.entry(key).or_default();
-            for _ in 0..revisions {
-                if let Ok((offset, datasize, timestamp)) = ciborium::from_reader(&mut file_meta) {
-                    let bulletin = Bulletin::from_archive(
-                        &acv_name,
-                        offset,
-                        datasize,
-                        DateTime::from_timestamp_nanos(timestamp).into(),
-                    );
-                    entry.push(bulletin);
-                    self.datasize += datasize;
-                    self.n_bulletins += 1;
-                    self.n_archives += 1;
-                } else {
-                    panic!();
-                }
+            for revision in revisions {
+                let mut bulletin = Bulletin::from_archive(
+                    &acv_name,
+                    revision.offset,
+                    revision.datasize,
+                    DateTime::from_timestamp_nanos(revision.timestamp).into(),
+                );
+                bulletin.attributes = revision.attributes;
+                bulletin.checksum = revision.checksum;
+                entry.push(bulletin);
+                self.datasize += revision.datasize;
+                self.n_bulletins += 1;
+                self.n_archives += 1;
+            }
+        }
+        if *selection == Selection::default() {
+            self.record(Entry::Load(acv_name))
+        } else {
+            self.record(Entry::LoadSelected(acv_name, selection.clone()))
+        }
+    }
+    /// Removes the bulletins loaded from an archive. The other bulletins are kept.
+    pub fn unload(&mut self, acv_name: String) -> Result<(), std::io::Error> {
+        if !self.is_loaded(&acv_name) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("Archive {acv_name} is not loaded."),
+            ));
+        }
+        self.unload_keys(&acv_name, &Selection::default())?;
+        self.record(Entry::Unload(acv_name))
+    }
+    /// Removes the keys loaded from an archive matching the title and tag patterns of the selection.
+    fn unload_keys(&mut self, acv_name: &str, selection: &Selection) -> Result<(), std::io::Error> {
+        let filter = Filter::from_selection(selection)?;
+        let prefix = format!("{acv_name}:");
+        let Some(loaded) = self.loaded.get_mut(acv_name) else {
+            return Ok(());
+        };
+        let keys: Vec<_> = loaded
+            .iter()
+            .filter(|(title, tag)| filter.matches(title, tag.strip_prefix(&prefix).unwrap_or(tag)))
+            .cloned()
+            .collect();
+        for key in &keys {
+            loaded.remove(key);
+        }
+        if loaded.is_empty() {
+            self.loaded.remove(acv_name);
+        }
+        for key in keys {
+            if let Some(mut bulletins) = self.bulletins.remove(&key) {
+                self.clear_all(&mut bulletins.list)?;
             }
         }
         Ok(())
     }
+    /// Lists the archives whose bulletins are on the board.
+    pub fn list_loaded(&self) -> Vec<String> {
+        let mut list: Vec<_> = self
+            .loaded
+            .keys()
+            .filter(|acv_name| self.is_loaded(acv_name))
+            .cloned()
+            .collect();
+        list.sort();
+        list
+    }
     pub fn list_archive(&self) -> Result<Vec<String>, fs_extra::error::Error> {
-        let dirs = fs_extra::dir::get_dir_content(&*ACV_DIR)?
+        let mut dirs: Vec<_> = fs_extra::dir::get_dir_content(&*ACV_DIR)?
             .files
             .iter()
-            .filter(|x| x.contains("meta.bin"))
+            .filter(|x| x.ends_with("/index.bin") || x.ends_with("/meta.bin"))
             .map(|x| {
                 let mut x = x.clone();
-                x.truncate(x.len() - 9);
+                x.truncate(x.rfind('/').unwrap());
                 x.split_off(ACV_DIR.len() + 1)
             })
             .collect();
+        dirs.sort();
+        dirs.dedup();
         Ok(dirs)
     }
-    pub fn rename_archive(&self, name_from: String, name_to: String) -> Result<(), std::io::Error> {
-        std::fs::rename(
-            format!("{}/{}", *ACV_DIR, name_from),
-            format!("{}/{}", *ACV_DIR, name_to),
-        )?;
-        Ok(())
+    /// Returns true if any bulletin on the board reads its data from the archive, including those relabeled after loading.
+    pub fn is_loaded(&self, acv_name: &str) -> bool {
+        self.bulletins.values().flatten().any(
+            |bulletin| matches!(&bulletin.data, BulletinBackend::Archive((name, _)) if name == acv_name),
+        )
     }
-    pub fn delete_archive(&self, acv_name: String) -> Result<(), std::io::Error> {
-        fs::remove_dir_all(format!("{}/{}", *ACV_DIR, acv_name))?;
+    /// Fails unless the archive exists and is not loaded, since the loaded bulletins read their data from the archive.
+    fn check_unloaded(&self, acv_name: &str) -> Result<(), std::io::Error> {
+        if !Path::new(&Archive::dir(acv_name)).exists() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("Archive {acv_name} does not exist."),
+            ));
+        }
+        if self.is_loaded(acv_name) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "Archive {acv_name} is loaded. Remove the loaded bulletins or reset the server first."
+                ),
+            ));
+        }
         Ok(())
     }
-    pub fn dump(&mut self, acv_name: String) -> Result<(), Box<dyn std::error::Error>> {
-        let keys: Vec<_> = self.bulletins.keys().cloned().collect();
-        for (title, tag) in keys {
-            self.archive(acv_name.clone(), title.clone(), tag.clone())?;
+    pub fn rename_archive(
+        &mut self,
+        name_from: String,
+        name_to: String,
+    ) -> Result<(), std::io::Error> {
+        self.check_unloaded(&name_from)?;
+        if Path::new(&Archive::dir(&name_to)).exists() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Archive {name_to} already exists."),
+            ));
         }
+        std::fs::rename(Archive::dir(&name_from), Archive::dir(&name_to))?;
+        self.loaded.remove(&name_from);
         Ok(())
     }
-    pub fn restore(&mut self, acv_name: String) -> Result<(), Box<dyn std::error::Error>> {
-        let filename_data = format!("{}/{}/data.bin", *ACV_DIR, acv_name);
-        let filename_meta = format!("{}/{}/meta.bin", *ACV_DIR, acv_name);
-        let mut file_data = File::open(&filename_data)?;
-        let mut file_meta = File::open(&filename_meta)?;
-        while let Ok((title, tag, revisions)) =
-            ciborium::from_reader::<(String, String, u64), _>(&mut file_meta)
-        {
-            for _ in 0..revisions {
-                if let Ok((offset, datasize, timestamp)) =
-                    ciborium::from_reader::<(u64, u64, _), _>(&mut file_meta)
-                {
-                    let mut buf = vec![0u8; datasize.try_into().unwrap()];
-                    file_data.seek(SeekFrom::Start(offset))?;
-                    file_data.read_exact(&mut buf).unwrap();
-                    let mut bulletin = Bulletin::from_data(buf);
-                    bulletin.timestamp = DateTime::from_timestamp_nanos(timestamp).into();
-                    self.post(title.clone(), tag.clone(), bulletin)?;
-                } else {
-                    panic!();
+    pub fn delete_archive(&mut self, acv_name: String) -> Result<(), std::io::Error> {
+        self.check_unloaded(&acv_name)?;
+        fs::remove_dir_all(Archive::dir(&acv_name))?;
+        self.loaded.remove(&acv_name);
+        Ok(())
+    }
+    pub fn archive_info(&self, acv_name: String) -> Result<ArchiveInfo, std::io::Error> {
+        archive::archive_info(&acv_name)
+    }
+    pub fn verify_archive(
+        &self,
+        acv_name: String,
+        repair: bool,
+    ) -> Result<ArchiveReport, std::io::Error> {
+        archive::verify_archive(&acv_name, repair)
+    }
+    /// Deletes revisions from the index of an archive. The loaded revisions are cleared from the board as well.
+    pub fn delete_from_archive(
+        &mut self,
+        acv_name: String,
+        title: String,
+        tag: Option<String>,
+        revisions: Option<Vec<u64>>,
+    ) -> Result<(), std::io::Error> {
+        let mut archive = Archive::open(&acv_name)?;
+        let removed = archive.remove(&title, tag.as_deref(), revisions.as_deref())?;
+        archive.save()?;
+        let relocations: Vec<_> = removed
+            .into_iter()
+            .map(|(offset, datasize)| (offset, datasize, None))
+            .collect();
+        self.relocate_archived(&acv_name, &relocations);
+        self.rewrite_journal()
+    }
+    /// Rewrites the data of an archive without the deleted revisions. The loaded revisions are pointed to the new data.
+    pub fn compact_archive(&mut self, acv_name: String) -> Result<(), std::io::Error> {
+        let (moved, reclaimed) = archive::compact(&acv_name)?;
+        let moved: HashMap<_, _> = moved
+            .into_iter()
+            .map(|(offset, datasize, offset_new)| ((offset, datasize), offset_new))
+            .collect();
+        // Loaded revisions missing from the index have lost their data.
+        let relocations: Vec<_> = self
+            .bulletins
+            .values()
+            .flatten()
+            .filter_map(|bulletin| match &bulletin.data {
+                BulletinBackend::Archive((name, offset)) if *name == acv_name => Some((
+                    *offset,
+                    bulletin.datasize,
+                    moved.get(&(*offset, bulletin.datasize)).copied(),
+                )),
+                _ => None,
+            })
+            .collect();
+        self.relocate_archived(&acv_name, &relocations);
+        logging::notice(format!(
+            "Compacted archive {acv_name} and reclaimed {reclaimed} bytes."
+        ));
+        self.rewrite_journal()
+    }
+    /// Points the loaded revisions of an archive to their new offsets, or clears them if they are deleted.
+    fn relocate_archived(&mut self, acv_name: &str, relocations: &[(u64, u64, Option<u64>)]) {
+        let relocations: HashMap<_, _> = relocations
+            .iter()
+            .map(|&(offset, datasize, offset_new)| ((offset, datasize), offset_new))
+            .collect();
+        for bulletin in self.bulletins.values_mut().flatten() {
+            let BulletinBackend::Archive((name, offset)) = &bulletin.data else {
+                continue;
+            };
+            if name != acv_name {
+                continue;
+            }
+            match relocations.get(&(*offset, bulletin.datasize)) {
+                Some(Some(offset_new)) => bulletin.relocate(*offset_new),
+                Some(None) => {
+                    let _ = bulletin.clear();
+                    self.datasize -= bulletin.datasize;
+                    self.n_bulletins -= 1;
+                    self.n_archives -= 1;
                 }
+                None => {}
+            }
+        }
+    }
+    /// Exports an archive, or the board if the archive name is None, writing the NPZ file to the writer. Cleared revisions of the board are skipped.
+    pub fn export<W: Write>(
+        &self,
+        acv_name: Option<String>,
+        writer: W,
+    ) -> Result<(W, String), std::io::Error> {
+        if let Some(acv_name) = acv_name {
+            let mut exporter = Exporter::new(writer, &acv_name);
+            exporter.add_archive(&acv_name)?;
+            return exporter.finish();
+        }
+        let mut exporter = Exporter::new(writer, "board");
+        let mut keys: Vec<_> = self.bulletins.keys().collect();
+        keys.sort_unstable();
+        for (title, tag) in keys {
+            let bulletins = &self.bulletins[&(title.clone(), tag.clone())];
+            for (revision, bulletin) in bulletins.iter() {
+                if matches!(bulletin.data, BulletinBackend::Empty) {
+                    continue;
+                }
+                exporter.add(
+                    title,
+                    tag,
+                    revision,
+                    bulletin.timestamp,
+                    &bulletin.attributes,
+                    bulletin.get()?,
+                )?;
+            }
+        }
+        exporter.finish()
+    }
+    pub fn dump(&mut self, acv_name: String) -> Result<(), Box<dyn std::error::Error>> {
+        let keys: Vec<_> = self.bulletins.keys().cloned().collect();
+        self.archive_keys(acv_name, keys)
+    }
+    /// Copies the selected revisions of an archive to the board. Unless merged, the board is reset first.
+    pub fn restore(
+        &mut self,
+        acv_name: String,
+        selection: &Selection,
+        merge: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let archive = Archive::open(&acv_name)?;
+        let mut file_data = File::open(archive.data_path())?;
+        let data_len = file_data.metadata()?.len();
+        let selected = Self::select(archive, selection)?;
+        // A damaged archive is refused before the board is reset or anything is posted.
+        for (title, tag, revisions) in &selected {
+            for revision in revisions {
+                archive::check(&mut file_data, data_len, revision).map_err(|err| {
+                    std::io::Error::new(
+                        err.kind(),
+                        format!("{title}:{tag} of archive {acv_name}: {err}"),
+                    )
+                })?;
+            }
+        }
+        if !merge {
+            self.reset()?;
+        }
+        for (title, tag, revisions) in selected {
+            for revision in revisions {
+                let mut buf = vec![0u8; revision.datasize.try_into().unwrap()];
+                file_data.seek(SeekFrom::Start(revision.offset))?;
+                file_data.read_exact(&mut buf)?;
+                archive::verify(&buf, revision.checksum)?;
+                let mut bulletin = Bulletin::from_data(buf);
+                bulletin.timestamp = DateTime::from_timestamp_nanos(revision.timestamp).into();
+                bulletin.attributes = revision.attributes;
+                self.post(title.clone(), tag.clone(), bulletin, None)?;
             }
         }
         Ok(())
     }
+    /// Retention policies are kept. The journal is discarded. The boards of the other namespaces are not affected.
     pub fn reset(&mut self) -> Result<(), std::io::Error> {
-        *self = Self::new()?;
-        Ok(())
+        let retention = std::mem::take(&mut self.retention);
+        let key_locks = std::mem::take(&mut self.key_locks);
+        if self.journal.take().is_some() {
+            fs::remove_file(journal::path(&self.dir))?;
+        }
+        *self = Self::new(self.namespace.take(), self.shared_memory.clone())?;
+        self.retention = retention;
+        self.key_locks = key_locks;
+        self.rewrite_journal()
+    }
+    pub fn flush(&self) -> Option<Flush> {
+        self.journal.as_ref().map(Journal::flush)
+    }
+    pub fn namespace(&self) -> Option<&str> {
+        self.namespace.as_deref()
+    }
+    /// Temporary directory where the data of large bulletins is saved.
+    pub fn dir(&self) -> &str {
+        &self.dir
+    }
+    /// Suffix naming the namespace in log messages. Empty for the default namespace.
+    fn describe(&self) -> String {
+        match &self.namespace {
+            Some(namespace) => format!(" of namespace {namespace}"),
+            None => String::new(),
+        }
+    }
+}
+
+impl Drop for BulletinBoard {
+    /// Returns the memory of the board to the other namespaces, e.g. when it is reset.
+    fn drop(&mut self) {
+        self.shared_memory
+            .fetch_sub(self.memory_used, Ordering::Relaxed);
     }
 }
@@ -1,15 +1,30 @@
-use crate::{logging, ACV_DIR, TMP_DIR};
+use crate::archive::{self, ChecksumReader};
+use crate::{logging, FILE_THRETHOLD, PROMOTE_READS};
+use bulletin_board_common::Attributes;
 use chrono::{DateTime, Local};
 use std::fs::{self, File};
-use std::io::{self, Cursor, Read, Seek, Write};
+use std::io::{self, Cursor, Read, Seek, SeekFrom, Write};
 use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
 use uuid::Uuid;
 
+/// Ticked on every access so that the least recently used bulletins can be evicted from memory.
+static ACCESS_CLOCK: AtomicU64 = AtomicU64::new(0);
+
+fn tick() -> u64 {
+    ACCESS_CLOCK.fetch_add(1, Ordering::Relaxed)
+}
+
 pub struct Bulletin {
     pub data: BulletinBackend,
     pub datasize: u64,
     pub timestamp: DateTime<Local>,
+    pub attributes: Attributes,
+    /// Checksum of the archived data. Archives of format version 1 have no checksum.
+    pub checksum: Option<u32>,
     file_opened: Option<File>,
+    last_access: AtomicU64,
+    reads: AtomicU64,
 }
 
 pub enum BulletinBackend {
@@ -33,10 +48,7 @@ impl Write for Bulletin {
                 self.datasize += size as u64;
                 Ok(size)
             }
-            BulletinBackend::Archive(_) => Err(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "Archive is read only.",
-            )),
+            BulletinBackend::Archive(_) => Err(std::io::Error::other("Archive is read only.")),
             BulletinBackend::Empty => Err(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
                 "No data backend.",
@@ -48,15 +60,9 @@ impl Write for Bulletin {
             BulletinBackend::Memory(data) => data.flush(),
             BulletinBackend::File(_) => match &mut self.file_opened {
                 Some(file) => file.flush(),
-                None => Err(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    "File is not opened.",
-                )),
+                None => Err(std::io::Error::other("File is not opened.")),
             },
-            BulletinBackend::Archive(_) => Err(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "Archive is read only.",
-            )),
+            BulletinBackend::Archive(_) => Err(std::io::Error::other("Archive is read only.")),
             BulletinBackend::Empty => Err(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
                 "No data backend.",
@@ -74,7 +80,7 @@ impl Read for Bulletin {
                 file.read(buf)
             }
             BulletinBackend::Archive((name, offset)) => {
-                let filename = format!("{}/{}/data.bin", *ACV_DIR, name);
+                let filename = archive::data_path(name)?;
                 let file = self.file_opened.get_or_insert({
                     let mut file = File::open(filename)?;
                     file.seek_relative(*offset as i64)?;
@@ -101,7 +107,11 @@ impl Bulletin {
             data: BulletinBackend::Archive((name.to_owned(), offset)),
             datasize,
             timestamp,
+            attributes: Attributes::new(),
+            checksum: None,
             file_opened: None,
+            last_access: AtomicU64::new(tick()),
+            reads: AtomicU64::new(0),
         }
     }
     pub fn from_data(data: Vec<u8>) -> Self {
@@ -110,14 +120,80 @@ impl Bulletin {
             data: BulletinBackend::Memory(Cursor::new(data)),
             datasize,
             timestamp: Local::now(),
+            attributes: Attributes::new(),
+            checksum: None,
             file_opened: None,
+            last_access: AtomicU64::new(tick()),
+            reads: AtomicU64::new(0),
+        }
+    }
+    /// Receives data of unknown size. The data is kept in memory up to the file threshold and written to a file beyond it.
+    pub fn from_reader<R: Read>(reader: &mut R, dir: &str) -> Result<Self, std::io::Error> {
+        let mut data = vec![];
+        reader
+            .by_ref()
+            .take(*FILE_THRETHOLD)
+            .read_to_end(&mut data)?;
+        let mut bulletin = Self::from_data(data);
+        if bulletin.datasize < *FILE_THRETHOLD {
+            return Ok(bulletin);
+        }
+        bulletin.save_to_file(dir)?;
+        if let BulletinBackend::File(filename) = &bulletin.data {
+            let copied = File::options()
+                .append(true)
+                .open(filename)
+                .and_then(|mut file| io::copy(reader, &mut file));
+            match copied {
+                Ok(size) => bulletin.datasize += size,
+                Err(err) => {
+                    fs::remove_file(filename)?;
+                    return Err(err);
+                }
+            }
+        }
+        Ok(bulletin)
+    }
+    /// Opens the data for reading without loading it at once. The returned reader does not borrow the bulletin, so that it can be used after the board is unlocked.
+    pub fn reader(&self) -> Result<Box<dyn Read + Send>, std::io::Error> {
+        self.touch();
+        match &self.data {
+            BulletinBackend::Memory(data) => Ok(Box::new(Cursor::new(data.get_ref().clone()))),
+            BulletinBackend::File(filename) => Ok(Box::new(File::open(filename)?)),
+            BulletinBackend::Archive((name, offset)) => {
+                let filename = archive::data_path(name)?;
+                let mut file = File::open(filename)?;
+                file.seek(SeekFrom::Start(*offset))?;
+                Ok(Box::new(ChecksumReader::new(
+                    file.take(self.datasize),
+                    self.checksum,
+                )))
+            }
+            BulletinBackend::Empty => Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "No data backend.",
+            )),
         }
     }
-    pub fn get(&mut self) -> Result<Vec<u8>, std::io::Error> {
-        let mut buf = vec![0; self.datasize.try_into().unwrap()];
-        self.read_exact(&mut buf)?;
-        self.close();
-        Ok(buf)
+    pub fn get(&self) -> Result<Vec<u8>, std::io::Error> {
+        self.touch();
+        match &self.data {
+            BulletinBackend::Memory(data) => Ok(data.get_ref().clone()),
+            BulletinBackend::File(filename) => fs::read(filename),
+            BulletinBackend::Archive((name, offset)) => {
+                let filename = archive::data_path(name)?;
+                let mut file = File::open(filename)?;
+                file.seek(SeekFrom::Start(*offset))?;
+                let mut buf = vec![0; self.datasize.try_into().unwrap()];
+                file.read_exact(&mut buf)?;
+                archive::verify(&buf, self.checksum)?;
+                Ok(buf)
+            }
+            BulletinBackend::Empty => Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "No data backend.",
+            )),
+        }
     }
     pub fn clear(&mut self) -> Result<(u64, u64, u64), std::io::Error> {
         match &mut self.data {
@@ -131,7 +207,8 @@ impl Bulletin {
                 Ok((self.datasize, 0, 1))
             }
             BulletinBackend::Archive(_) => {
-                logging::info("Archived entry cannot be deleted.".to_string());
+                self.data = BulletinBackend::Empty;
+                self.file_opened = None;
                 Ok((self.datasize, 0, 0))
             },
             BulletinBackend::Empty => {
@@ -140,43 +217,66 @@ impl Bulletin {
             }
         }
     }
-    pub fn save_to_file(&mut self) -> Result<(), std::io::Error> {
+    /// Moves the data in memory to a file under the temporary directory of the board.
+    pub fn save_to_file(&mut self, dir: &str) -> Result<(), std::io::Error> {
         match &mut self.data {
             BulletinBackend::Memory(data) => {
                 let mut uuid = Uuid::new_v4().to_string();
                 let first: String = uuid.drain(..2).collect();
                 let second: String = uuid.drain(..2).collect();
-                let dir = format!("{}/{}/{}", *TMP_DIR, first, second);
+                let dir = format!("{dir}/{first}/{second}");
                 if !Path::new(&dir).exists() {
                     fs::create_dir_all(&dir)?;
                 }
                 let filename = [dir, uuid].join("/");
                 let mut file = File::create(&filename)?;
+                data.set_position(0);
                 io::copy(data, &mut file)?;
                 self.data = BulletinBackend::File(filename);
+                self.reads.store(0, Ordering::Relaxed);
                 Ok(())
             }
             BulletinBackend::Empty => Err(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
                 "No data backend.",
             )),
-            _ => Err(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "Already moved out of memory.",
-            )),
+            _ => Err(std::io::Error::other("Already moved out of memory.")),
         }
     }
-    pub fn close(&mut self) {
-        match &mut self.data {
-            BulletinBackend::Memory(data) => {
-                data.set_position(0);
-            }
-            BulletinBackend::Empty => {}
-            _ => {
+    /// Points the archived data to a new offset after the archive is compacted.
+    pub fn relocate(&mut self, offset_new: u64) {
+        if let BulletinBackend::Archive((_, offset)) = &mut self.data {
+            *offset = offset_new;
+            self.file_opened = None;
+        }
+    }
+    /// Moves the data saved to a file back to memory.
+    pub fn load_to_memory(&mut self) -> Result<(), std::io::Error> {
+        match &self.data {
+            BulletinBackend::File(filename) => {
+                let data = fs::read(filename)?;
+                fs::remove_file(filename)?;
+                self.data = BulletinBackend::Memory(Cursor::new(data));
                 self.file_opened = None;
+                self.reads.store(0, Ordering::Relaxed);
+                Ok(())
             }
+            _ => Err(std::io::Error::other("Not saved to a file.")),
         }
     }
+    fn touch(&self) {
+        self.last_access.store(tick(), Ordering::Relaxed);
+        self.reads.fetch_add(1, Ordering::Relaxed);
+    }
+    pub fn last_access(&self) -> u64 {
+        self.last_access.load(Ordering::Relaxed)
+    }
+    /// Returns true if the data is saved to a file but read often enough to be moved to memory.
+    pub fn is_hot(&self) -> bool {
+        matches!(self.data, BulletinBackend::File(_))
+            && self.datasize < *FILE_THRETHOLD
+            && PROMOTE_READS.is_some_and(|min| self.reads.load(Ordering::Relaxed) >= min)
+    }
     pub fn backend(&self) -> String {
         match &self.data {
             BulletinBackend::Memory(_) => "memory".to_string(),
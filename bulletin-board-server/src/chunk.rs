@@ -0,0 +1,70 @@
+use bulletin_board_common::CHUNK_SIZE;
+use serde_bytes::{ByteBuf, Bytes};
+use std::io::{self, Read, Write};
+
+/// Reader of the data sent in chunks by [`bulletin_board_common::Operation::PostStream`].
+pub struct ChunkReader<'a, S: Read> {
+    stream: &'a mut S,
+    chunk: Vec<u8>,
+    position: usize,
+    finished: bool,
+}
+
+impl<'a, S: Read> ChunkReader<'a, S> {
+    pub fn new(stream: &'a mut S) -> Self {
+        Self {
+            stream,
+            chunk: vec![],
+            position: 0,
+            finished: false,
+        }
+    }
+    /// Discards the rest of the data so that the next operation can be read from the stream.
+    pub fn skip(&mut self) -> Result<(), ciborium::de::Error<io::Error>> {
+        while !self.finished {
+            let chunk: ByteBuf = ciborium::from_reader(&mut *self.stream)?;
+            self.finished = chunk.is_empty();
+        }
+        Ok(())
+    }
+}
+
+impl<S: Read> Read for ChunkReader<'_, S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.chunk.len() {
+            if self.finished {
+                return Ok(0);
+            }
+            let chunk: ByteBuf =
+                ciborium::from_reader(&mut *self.stream).map_err(|err| match err {
+                    ciborium::de::Error::Io(err) => err,
+                    err => io::Error::new(io::ErrorKind::InvalidData, err.to_string()),
+                })?;
+            self.finished = chunk.is_empty();
+            self.chunk = chunk.into_vec();
+            self.position = 0;
+        }
+        let size = buf.len().min(self.chunk.len() - self.position);
+        buf[..size].copy_from_slice(&self.chunk[self.position..self.position + size]);
+        self.position += size;
+        Ok(size)
+    }
+}
+
+/// Sends the data in chunks followed by an empty chunk. Errors of the reader are returned after the end of the chunks is sent, so that the stream stays consistent. Errors of the stream are returned as they are.
+pub fn write_chunks<R: Read, S: Write>(
+    reader: &mut R,
+    stream: &mut S,
+) -> Result<Result<(), io::Error>, ciborium::ser::Error<io::Error>> {
+    let mut buf = vec![0; CHUNK_SIZE];
+    let result = loop {
+        match reader.read(&mut buf) {
+            Ok(0) => break Ok(()),
+            Ok(size) => ciborium::into_writer(Bytes::new(&buf[..size]), &mut *stream)?,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => break Err(err),
+        }
+    };
+    ciborium::into_writer(Bytes::new(&[]), stream)?;
+    Ok(result)
+}
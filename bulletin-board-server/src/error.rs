@@ -1,3 +1,4 @@
+use bulletin_board_common::{ErrorKind, Response};
 use std::error::Error;
 use std::fmt::{Debug, Display};
 
@@ -7,6 +8,7 @@ pub struct BulletinError {
     title: String,
     tag: String,
     revision: Option<u64>,
+    kind: ErrorKind,
 }
 
 impl BulletinError {
@@ -23,6 +25,20 @@ impl BulletinError {
             title,
             tag,
             revision,
+            kind: ErrorKind::Other,
+        }
+    }
+    pub fn with_kind(mut self, kind: ErrorKind) -> Self {
+        self.kind = kind;
+        self
+    }
+    pub fn response(&self) -> Response {
+        match self.kind {
+            ErrorKind::NotFound => Response::NotFound,
+            kind => Response::Error {
+                kind,
+                message: self.to_string(),
+            },
         }
     }
 }
@@ -46,7 +62,7 @@ impl Display for BulletinError {
 
 impl Debug for BulletinError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        <Self as Display>::fmt(&self, f)
+        <Self as Display>::fmt(self, f)
     }
 }
 
@@ -56,6 +72,7 @@ pub struct ArchiveError {
     operation: String,
     message: String,
     acv_name: String,
+    kind: ErrorKind,
 }
 
 impl ArchiveError {
@@ -64,6 +81,20 @@ impl ArchiveError {
             operation: operation.to_string(),
             message,
             acv_name,
+            kind: ErrorKind::Other,
+        }
+    }
+    pub fn with_kind(mut self, kind: ErrorKind) -> Self {
+        self.kind = kind;
+        self
+    }
+    pub fn response(&self) -> Response {
+        match self.kind {
+            ErrorKind::NotFound => Response::NotFound,
+            kind => Response::Error {
+                kind,
+                message: self.to_string(),
+            },
         }
     }
 }
@@ -80,8 +111,93 @@ impl Display for ArchiveError {
 
 impl Debug for ArchiveError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        <Self as Display>::fmt(&self, f)
+        <Self as Display>::fmt(self, f)
     }
 }
 
 impl Error for ArchiveError {}
+
+/// The tag is omitted, but the title has multiple tags.
+pub struct NotUniqueError {
+    operation: String,
+    title: String,
+    tags: Vec<String>,
+}
+
+impl NotUniqueError {
+    pub fn new(operation: &str, title: String, tags: Vec<String>) -> Self {
+        Self {
+            operation: operation.to_string(),
+            title,
+            tags,
+        }
+    }
+    pub fn response(&self) -> Response {
+        Response::NotUnique(self.tags.clone())
+    }
+}
+
+impl Display for NotUniqueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "message: \"Found multiple entries having the same name.\", operation: {}, title: {}, tags: {}.",
+            self.operation,
+            self.title,
+            self.tags.join(", ")
+        )
+    }
+}
+
+impl Debug for NotUniqueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        <Self as Display>::fmt(self, f)
+    }
+}
+
+impl Error for NotUniqueError {}
+
+/// Kind reported to the client for an error raised by the board.
+pub fn error_kind(err: &(dyn Error + 'static)) -> ErrorKind {
+    match err.downcast_ref::<std::io::Error>().map(|err| err.kind()) {
+        Some(std::io::ErrorKind::NotFound) => ErrorKind::NotFound,
+        Some(std::io::ErrorKind::InvalidInput) => ErrorKind::InvalidArgument,
+        Some(_) => ErrorKind::Io,
+        None => ErrorKind::Other,
+    }
+}
+
+/// Converts an error raised while processing an operation into the response sent to the client.
+pub fn error_response(err: &(dyn Error + 'static)) -> Response {
+    if let Some(err) = err.downcast_ref::<BulletinError>() {
+        err.response()
+    } else if let Some(err) = err.downcast_ref::<ArchiveError>() {
+        err.response()
+    } else if let Some(err) = err.downcast_ref::<NotUniqueError>() {
+        err.response()
+    } else if let Some(err) = err.downcast_ref::<std::io::Error>() {
+        match error_kind(err) {
+            ErrorKind::NotFound => Response::NotFound,
+            kind => Response::Error {
+                kind,
+                message: err.to_string(),
+            },
+        }
+    } else if err.is::<ciborium::de::Error<std::io::Error>>() {
+        Response::Error {
+            kind: ErrorKind::Protocol,
+            message: format!("Malformed request: {err}"),
+        }
+    } else {
+        Response::Error {
+            kind: ErrorKind::Other,
+            message: err.to_string(),
+        }
+    }
+}
+
+/// Whether the error leaves the stream in an unknown position, so that the connection must be closed.
+pub fn is_fatal(err: &(dyn Error + 'static)) -> bool {
+    err.is::<ciborium::de::Error<std::io::Error>>()
+        || err.is::<ciborium::ser::Error<std::io::Error>>()
+}
@@ -0,0 +1,438 @@
+use crate::archive::{self, Archive};
+use array_object::adaptor::{Pair, VecShape};
+use array_object::{ArrayObject, DataType, Unpack};
+use bulletin_board_common::Attributes;
+use chrono::{DateTime, Local};
+use std::fmt::{Display, Write as _};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+
+/// Arrays with at most this number of elements are written into the manifest as well.
+pub const INLINE_LIMIT: usize = 256;
+
+pub fn invalid(err: impl Display) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err.to_string())
+}
+
+/// Elements of an unpacked ArrayObject in row-major order.
+enum Elements {
+    UnsignedInteger(Vec<u64>),
+    SignedInteger(Vec<i64>),
+    Real(Vec<f64>),
+    Complex(Vec<(f64, f64)>),
+    String(Vec<String>),
+}
+
+/// Unpacked ArrayObject with the size of each element (of each part for complex numbers) in bytes.
+struct Array {
+    shape: Vec<u64>,
+    width: usize,
+    elements: Elements,
+}
+
+fn elements<T>(obj: ArrayObject) -> Result<Vec<T>, io::Error>
+where
+    T: TryFrom<ArrayObject>,
+    VecShape<T>: TryFrom<ArrayObject>,
+    <T as TryFrom<ArrayObject>>::Error: Display,
+    <VecShape<T> as TryFrom<ArrayObject>>::Error: Display,
+{
+    if obj.dimension() == 0 {
+        T::try_from(obj).map(|val| vec![val]).map_err(invalid)
+    } else {
+        VecShape::<T>::try_from(obj)
+            .map(|VecShape(val, _)| val)
+            .map_err(invalid)
+    }
+}
+
+impl Array {
+    fn unpack(data: Vec<u8>) -> Result<Self, io::Error> {
+        let obj = ArrayObject::unpack(data).map_err(invalid)?;
+        let shape = obj.shape().into_iter().map(|x| x as u64).collect();
+        let width = match obj.len() {
+            0 => 8,
+            len => obj.datasize() / len,
+        };
+        let (width, elements) = match obj.datatype() {
+            DataType::UnsignedInteger => (width, Elements::UnsignedInteger(elements(obj)?)),
+            DataType::SignedInteger => (width, Elements::SignedInteger(elements(obj)?)),
+            DataType::Real => (width, Elements::Real(elements(obj)?)),
+            DataType::Complex => (
+                width / 2,
+                Elements::Complex(
+                    elements::<Pair<f64>>(obj)?
+                        .into_iter()
+                        .map(|Pair(re, im)| (re, im))
+                        .collect(),
+                ),
+            ),
+            DataType::String => (0, Elements::String(elements(obj)?)),
+        };
+        if !matches!(width, 0 | 1 | 2 | 4 | 8) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Elements of {width} bytes cannot be exported."),
+            ));
+        }
+        Ok(Self {
+            shape,
+            width,
+            elements,
+        })
+    }
+    fn len(&self) -> usize {
+        match &self.elements {
+            Elements::UnsignedInteger(val) => val.len(),
+            Elements::SignedInteger(val) => val.len(),
+            Elements::Real(val) => val.len(),
+            Elements::Complex(val) => val.len(),
+            Elements::String(val) => val.len(),
+        }
+    }
+    fn datatype(&self) -> &'static str {
+        match &self.elements {
+            Elements::UnsignedInteger(_) => "UnsignedInteger",
+            Elements::SignedInteger(_) => "SignedInteger",
+            Elements::Real(_) => "Real",
+            Elements::Complex(_) => "Complex",
+            Elements::String(_) => "String",
+        }
+    }
+    /// Number of characters of the longest string, which is the width of the NumPy unicode type.
+    fn max_chars(&self) -> usize {
+        match &self.elements {
+            Elements::String(val) => val.iter().map(|x| x.chars().count()).max().unwrap_or(0),
+            _ => 0,
+        }
+        .max(1)
+    }
+    /// Type descriptor of NumPy.
+    fn descr(&self) -> String {
+        let order = if self.width == 1 { '|' } else { '<' };
+        match &self.elements {
+            Elements::UnsignedInteger(_) => format!("{order}u{}", self.width),
+            Elements::SignedInteger(_) => format!("{order}i{}", self.width),
+            Elements::Real(_) => format!("<f{}", self.width),
+            Elements::Complex(_) => format!("<c{}", 2 * self.width),
+            Elements::String(_) => format!("<U{}", self.max_chars()),
+        }
+    }
+    /// Encodes the array in the NPY format version 1.0.
+    fn to_npy(&self) -> Vec<u8> {
+        let shape = match self.shape.as_slice() {
+            [] => "()".to_string(),
+            [len] => format!("({len},)"),
+            shape => format!(
+                "({})",
+                shape
+                    .iter()
+                    .map(|x| x.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        };
+        let mut header = format!(
+            "{{'descr': '{}', 'fortran_order': False, 'shape': {shape}, }}",
+            self.descr()
+        );
+        // The magic string, the version and the header length take 10 bytes, and the header ends with a newline.
+        let padding = (64 - (11 + header.len()) % 64) % 64;
+        header.extend(std::iter::repeat_n(' ', padding));
+        header.push('\n');
+        let mut npy = b"\x93NUMPY\x01\x00".to_vec();
+        npy.extend((header.len() as u16).to_le_bytes());
+        npy.extend(header.as_bytes());
+        let width = self.width;
+        match &self.elements {
+            Elements::UnsignedInteger(val) => {
+                for x in val {
+                    npy.extend(&x.to_le_bytes()[..width]);
+                }
+            }
+            Elements::SignedInteger(val) => {
+                for x in val {
+                    npy.extend(&x.to_le_bytes()[..width]);
+                }
+            }
+            Elements::Real(val) => {
+                for &x in val {
+                    push_real(&mut npy, x, width);
+                }
+            }
+            Elements::Complex(val) => {
+                for &(re, im) in val {
+                    push_real(&mut npy, re, width);
+                    push_real(&mut npy, im, width);
+                }
+            }
+            Elements::String(val) => {
+                let max_chars = self.max_chars();
+                for x in val {
+                    let chars = x.chars().count();
+                    for c in x.chars() {
+                        npy.extend((c as u32).to_le_bytes());
+                    }
+                    npy.extend(std::iter::repeat_n(0, 4 * (max_chars - chars)));
+                }
+            }
+        }
+        npy
+    }
+    /// Writes the elements as a flat JSON array.
+    fn to_json(&self) -> String {
+        let list: Vec<_> = match &self.elements {
+            Elements::UnsignedInteger(val) => val.iter().map(|x| x.to_string()).collect(),
+            Elements::SignedInteger(val) => val.iter().map(|x| x.to_string()).collect(),
+            Elements::Real(val) => val.iter().map(|&x| json_real(x, self.width)).collect(),
+            Elements::Complex(val) => val
+                .iter()
+                .map(|&(re, im)| {
+                    format!(
+                        "[{}, {}]",
+                        json_real(re, self.width),
+                        json_real(im, self.width)
+                    )
+                })
+                .collect(),
+            Elements::String(val) => val.iter().map(|x| json_string(x)).collect(),
+        };
+        format!("[{}]", list.join(", "))
+    }
+}
+
+fn push_real(npy: &mut Vec<u8>, x: f64, width: usize) {
+    if width == 4 {
+        npy.extend((x as f32).to_le_bytes());
+    } else {
+        npy.extend(x.to_le_bytes());
+    }
+}
+
+/// NaN and infinities are written as null, which JSON has no number for.
+fn json_real(x: f64, width: usize) -> String {
+    if !x.is_finite() {
+        "null".to_string()
+    } else if width == 4 {
+        format!("{:?}", x as f32)
+    } else {
+        format!("{x:?}")
+    }
+}
+
+fn json_string(val: &str) -> String {
+    let mut json = String::with_capacity(val.len() + 2);
+    json.push('"');
+    for c in val.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+fn too_large() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "The export exceeds 4 GiB or 65535 arrays, which the NPZ file without ZIP64 cannot hold.",
+    )
+}
+
+/// Writer of an uncompressed ZIP file, which NumPy reads as NPZ.
+struct NpzWriter<W: Write> {
+    writer: W,
+    offset: u32,
+    central: Vec<u8>,
+    n_entries: u16,
+}
+
+impl<W: Write> NpzWriter<W> {
+    fn new(writer: W) -> Self {
+        Self {
+            writer,
+            offset: 0,
+            central: vec![],
+            n_entries: 0,
+        }
+    }
+    fn add(&mut self, name: &str, data: &[u8]) -> Result<(), io::Error> {
+        let name = format!("{name}.npy");
+        let crc = crc32fast::hash(data);
+        let size = u32::try_from(data.len()).map_err(|_| too_large())?;
+        let name_len = u16::try_from(name.len()).map_err(|_| too_large())?;
+        let n_entries = self.n_entries.checked_add(1).ok_or_else(too_large)?;
+        // Version needed, flags (UTF-8 names), stored, time, date (1980-01-01), checksum and sizes.
+        let mut fields = vec![];
+        fields.extend(20u16.to_le_bytes());
+        fields.extend(0x0800u16.to_le_bytes());
+        fields.extend(0u16.to_le_bytes());
+        fields.extend(0u16.to_le_bytes());
+        fields.extend(0x0021u16.to_le_bytes());
+        fields.extend(crc.to_le_bytes());
+        fields.extend(size.to_le_bytes());
+        fields.extend(size.to_le_bytes());
+        fields.extend(name_len.to_le_bytes());
+        fields.extend(0u16.to_le_bytes());
+
+        let mut local = 0x04034b50u32.to_le_bytes().to_vec();
+        local.extend(&fields);
+        local.extend(name.as_bytes());
+        let next = (local.len() as u32)
+            .checked_add(size)
+            .and_then(|len| self.offset.checked_add(len))
+            .ok_or_else(too_large)?;
+        self.writer.write_all(&local)?;
+        self.writer.write_all(data)?;
+
+        self.central.extend(0x02014b50u32.to_le_bytes());
+        self.central.extend(20u16.to_le_bytes());
+        self.central.extend(&fields);
+        // Comment length, disk number, internal and external attributes.
+        self.central.extend([0; 10]);
+        self.central.extend(self.offset.to_le_bytes());
+        self.central.extend(name.as_bytes());
+
+        self.offset = next;
+        self.n_entries = n_entries;
+        Ok(())
+    }
+    fn finish(mut self) -> Result<W, io::Error> {
+        let size = u32::try_from(self.central.len()).map_err(|_| too_large())?;
+        self.offset.checked_add(size).ok_or_else(too_large)?;
+        self.writer.write_all(&self.central)?;
+        let mut end = 0x06054b50u32.to_le_bytes().to_vec();
+        end.extend([0; 4]);
+        end.extend(self.n_entries.to_le_bytes());
+        end.extend(self.n_entries.to_le_bytes());
+        end.extend(size.to_le_bytes());
+        end.extend(self.offset.to_le_bytes());
+        end.extend([0; 2]);
+        self.writer.write_all(&end)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Converts revisions to an NPZ file, in which each revision is stored as `title/tag/revision.npy`, and a JSON manifest describing them.
+///
+/// The manifest lists the title, the tag, the revision, the key in the NPZ file, the timestamp, the attributes, the data type, the NumPy type and the shape of each revision. The elements of the arrays up to [`INLINE_LIMIT`] are written in the manifest as well, as a flat list in row-major order. Complex numbers are pairs of the real and imaginary parts.
+pub struct Exporter<W: Write> {
+    npz: NpzWriter<W>,
+    source: String,
+    entries: Vec<String>,
+}
+
+impl<W: Write> Exporter<W> {
+    pub fn new(writer: W, source: &str) -> Self {
+        Self {
+            npz: NpzWriter::new(writer),
+            source: source.to_string(),
+            entries: vec![],
+        }
+    }
+    pub fn add(
+        &mut self,
+        title: &str,
+        tag: &str,
+        revision: u64,
+        timestamp: DateTime<Local>,
+        attributes: &Attributes,
+        data: Vec<u8>,
+    ) -> Result<(), io::Error> {
+        let array = Array::unpack(data)?;
+        let key = format!("{title}/{tag}/{revision}");
+        self.npz.add(&key, &array.to_npy())?;
+        let attributes: Vec<_> = attributes
+            .iter()
+            .map(|(key, val)| format!("{}: {}", json_string(key), json_string(val)))
+            .collect();
+        let shape: Vec<_> = array.shape.iter().map(|x| x.to_string()).collect();
+        let mut entry = format!(
+            "{{\"title\": {}, \"tag\": {}, \"revision\": {revision}, \"key\": {}, \"timestamp\": {}, \"attributes\": {{{}}}, \"datatype\": {}, \"dtype\": {}, \"shape\": [{}]",
+            json_string(title),
+            json_string(tag),
+            json_string(&key),
+            json_string(&timestamp.to_rfc3339()),
+            attributes.join(", "),
+            json_string(array.datatype()),
+            json_string(&array.descr()),
+            shape.join(", "),
+        );
+        if array.len() <= INLINE_LIMIT {
+            write!(entry, ", \"data\": {}", array.to_json()).unwrap();
+        }
+        entry.push('}');
+        self.entries.push(entry);
+        Ok(())
+    }
+    /// Adds the revisions in the index of an archive. The data is verified against the checksums.
+    pub fn add_archive(&mut self, name: &str) -> Result<(), io::Error> {
+        let archive = Archive::open(name)?;
+        if archive.keys.is_empty() {
+            return Ok(());
+        }
+        let mut file = File::open(archive.data_path())?;
+        for (title, tags) in &archive.keys {
+            for (tag, revisions) in tags {
+                for (revision, entry) in revisions.iter().enumerate() {
+                    file.seek(SeekFrom::Start(entry.offset))?;
+                    let mut data = vec![0; entry.datasize.try_into().unwrap()];
+                    file.read_exact(&mut data)?;
+                    archive::verify(&data, entry.checksum)?;
+                    let timestamp = DateTime::from_timestamp_nanos(entry.timestamp).into();
+                    self.add(
+                        title,
+                        tag,
+                        revision as u64,
+                        timestamp,
+                        &entry.attributes,
+                        data,
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+    /// Number of the revisions added so far.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    /// Completes the NPZ file and returns the writer together with the manifest.
+    pub fn finish(self) -> Result<(W, String), io::Error> {
+        let writer = self.npz.finish()?;
+        let manifest = format!(
+            "{{\n  \"source\": {},\n  \"exported\": {},\n  \"entries\": [\n    {}\n  ]\n}}\n",
+            json_string(&self.source),
+            json_string(&Local::now().to_rfc3339()),
+            self.entries.join(",\n    ")
+        );
+        Ok((writer, manifest))
+    }
+}
+
+/// Exports an archive in the archive directory to `{output}.npz` and `{output}.json`. Returns the number of exported revisions.
+pub fn export_archive(name: &str, output: &str) -> Result<usize, io::Error> {
+    let npz = format!("{output}.npz");
+    let mut exporter = Exporter::new(BufWriter::new(File::create(&npz)?), name);
+    let result = exporter
+        .add_archive(name)
+        .and_then(|()| {
+            let len = exporter.len();
+            exporter.finish().map(|(_, manifest)| (len, manifest))
+        })
+        .and_then(|(len, manifest)| {
+            fs::write(format!("{output}.json"), manifest)?;
+            Ok(len)
+        });
+    if result.is_err() {
+        fs::remove_file(&npz).ok();
+    }
+    result
+}
@@ -0,0 +1,392 @@
+use crate::chunk::write_chunks;
+use crate::export::invalid;
+use crate::server::loopback;
+use crate::tls;
+use crate::{LISTEN_ADDR, TLS_CERT};
+use array_object::adaptor::{VecShape, VecVecShape};
+use array_object::{ArrayObject, Pack};
+use bulletin_board_common::*;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{self, Cursor, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::Path;
+
+#[cfg(target_family = "unix")]
+use std::os::unix::net::UnixStream;
+
+/// Packed ArrayObjects with their names in the NPZ file, which are None for the other formats.
+pub type Arrays = Vec<(Option<String>, Vec<u8>)>;
+
+/// Reads the arrays in a `.npy`, `.npz` or CSV file, chosen by the extension of the file name.
+pub fn read_file(filename: &str, data: &[u8]) -> Result<Arrays, io::Error> {
+    let extension = Path::new(filename)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase());
+    match extension.as_deref() {
+        Some("npy") => Ok(vec![(None, read_npy(data)?.pack())]),
+        Some("npz") => read_npz(data),
+        Some("csv") => Ok(vec![(None, read_csv(data)?.pack())]),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Unsupported file: {filename}. The extension has to be .npy, .npz or .csv."),
+        )),
+    }
+}
+
+fn unsupported(message: impl Display) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn array<T>(mut val: Vec<T>, shape: Vec<u64>) -> Result<ArrayObject, io::Error>
+where
+    T: Into<ArrayObject>,
+    VecShape<T>: TryInto<ArrayObject>,
+    <VecShape<T> as TryInto<ArrayObject>>::Error: Display,
+{
+    if shape.is_empty() {
+        Ok(val
+            .pop()
+            .ok_or_else(|| unsupported("The data is truncated."))?
+            .into())
+    } else {
+        VecShape(val, shape).try_into().map_err(invalid)
+    }
+}
+
+/// Header of an NPY file.
+struct NpyHeader {
+    descr: String,
+    fortran_order: bool,
+    shape: Vec<u64>,
+}
+
+/// Value of a key in the header, which is a Python dict literal.
+fn header_value<'a>(header: &'a str, key: &str) -> Result<&'a str, io::Error> {
+    let start = header
+        .find(&format!("'{key}':"))
+        .ok_or_else(|| unsupported(format!("The NPY header has no {key}.")))?
+        + key.len()
+        + 3;
+    Ok(header[start..].trim_start())
+}
+
+impl NpyHeader {
+    fn parse(header: &str) -> Result<Self, io::Error> {
+        let descr = header_value(header, "descr")?;
+        let descr = descr
+            .strip_prefix('\'')
+            .and_then(|descr| descr.split_once('\''))
+            .ok_or_else(|| unsupported("Structured arrays are not supported."))?
+            .0
+            .to_string();
+        let fortran_order = header_value(header, "fortran_order")?.starts_with("True");
+        let shape = header_value(header, "shape")?;
+        let shape = shape
+            .strip_prefix('(')
+            .and_then(|shape| shape.split_once(')'))
+            .ok_or_else(|| unsupported("The shape in the NPY header is broken."))?
+            .0
+            .split(',')
+            .map(str::trim)
+            .filter(|len| !len.is_empty())
+            .map(|len| len.parse().map_err(invalid))
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            descr,
+            fortran_order,
+            shape,
+        })
+    }
+    /// Positions of the elements in the data in row-major order.
+    fn order(&self) -> Vec<usize> {
+        let len = self.shape.iter().product::<u64>() as usize;
+        if !self.fortran_order || self.shape.len() < 2 {
+            return (0..len).collect();
+        }
+        let strides: Vec<usize> = self
+            .shape
+            .iter()
+            .scan(1, |stride, &dim| {
+                let current = *stride;
+                *stride *= dim as usize;
+                Some(current)
+            })
+            .collect();
+        (0..len)
+            .map(|mut index| {
+                let mut position = 0;
+                for (&dim, &stride) in self.shape.iter().zip(&strides).rev() {
+                    position += index % dim as usize * stride;
+                    index /= dim as usize;
+                }
+                position
+            })
+            .collect()
+    }
+}
+
+/// Reads an array in the NPY format of any version. Booleans, integers, real and complex numbers of 4 or 8 bytes and strings are supported.
+fn read_npy(data: &[u8]) -> Result<ArrayObject, io::Error> {
+    let data = data
+        .strip_prefix(b"\x93NUMPY")
+        .ok_or_else(|| unsupported("The file is not in the NPY format."))?;
+    let (header_len, data) = match data {
+        [1, _, len @ ..] if len.len() >= 2 => {
+            (u16::from_le_bytes([len[0], len[1]]) as usize, &len[2..])
+        }
+        [2 | 3, _, len @ ..] if len.len() >= 4 => (
+            u32::from_le_bytes([len[0], len[1], len[2], len[3]]) as usize,
+            &len[4..],
+        ),
+        _ => return Err(unsupported("The NPY version is not supported.")),
+    };
+    if data.len() < header_len {
+        return Err(unsupported("The data is truncated."));
+    }
+    let (header, data) = data.split_at(header_len);
+    let header = NpyHeader::parse(&String::from_utf8_lossy(header))?;
+    let order = header.order();
+    let len = order.len();
+    let descr = header.descr.as_str();
+    if descr.len() < 3 || !descr.is_ascii() {
+        return Err(unsupported(format!("The type {descr} is not supported.")));
+    }
+    let (byteorder, kind) = descr.split_at(1);
+    if !matches!(byteorder, "<" | ">" | "|" | "=") {
+        return Err(unsupported(format!("The type {descr} is not supported.")));
+    }
+    let (kind, size) = kind.split_at(1);
+    let size: usize = size
+        .parse()
+        .map_err(|_| unsupported(format!("The type {descr} is not supported.")))?;
+    let width = if kind == "U" { 4 * size } else { size };
+    if data.len() < len * width {
+        return Err(unsupported("The data is truncated."));
+    }
+    // Elements in row-major order with the bytes in little endian.
+    let elements: Vec<Vec<u8>> = order
+        .iter()
+        .map(|&position| {
+            let mut bytes = data[position * width..(position + 1) * width].to_vec();
+            if byteorder == ">" {
+                match kind {
+                    "c" => {
+                        bytes[..size / 2].reverse();
+                        bytes[size / 2..].reverse();
+                    }
+                    "U" => bytes.chunks_mut(4).for_each(|c| c.reverse()),
+                    _ => bytes.reverse(),
+                }
+            }
+            bytes
+        })
+        .collect();
+    let shape = header.shape;
+    macro_rules! numbers {
+        ($ty:ty) => {
+            array(
+                elements
+                    .iter()
+                    .map(|x| <$ty>::from_le_bytes(x.as_slice().try_into().unwrap()))
+                    .collect(),
+                shape,
+            )
+        };
+    }
+    macro_rules! complex {
+        ($ty:ty) => {{
+            let half = size / 2;
+            let re: Vec<$ty> = elements
+                .iter()
+                .map(|x| <$ty>::from_le_bytes(x[..half].try_into().unwrap()))
+                .collect();
+            let im: Vec<$ty> = elements
+                .iter()
+                .map(|x| <$ty>::from_le_bytes(x[half..].try_into().unwrap()))
+                .collect();
+            if shape.is_empty() {
+                Ok(array_object::adaptor::Pair(re[0], im[0]).into())
+            } else {
+                VecVecShape(re, im, shape).try_into().map_err(invalid)
+            }
+        }};
+    }
+    match (kind, size) {
+        ("b", 1) => array(
+            elements.iter().map(|x| u8::from(x[0] != 0)).collect(),
+            shape,
+        ),
+        ("u", 1) => numbers!(u8),
+        ("u", 2) => numbers!(u16),
+        ("u", 4) => numbers!(u32),
+        ("u", 8) => numbers!(u64),
+        ("i", 1) => numbers!(i8),
+        ("i", 2) => numbers!(i16),
+        ("i", 4) => numbers!(i32),
+        ("i", 8) => numbers!(i64),
+        ("f", 4) => numbers!(f32),
+        ("f", 8) => numbers!(f64),
+        ("c", 8) => complex!(f32),
+        ("c", 16) => complex!(f64),
+        ("U", _) => array(
+            elements
+                .iter()
+                .map(|x| {
+                    x.chunks(4)
+                        .map(|c| u32::from_le_bytes(c.try_into().unwrap()))
+                        .take_while(|&c| c != 0)
+                        .map(|c| char::from_u32(c).unwrap_or(char::REPLACEMENT_CHARACTER))
+                        .collect::<String>()
+                })
+                .collect(),
+            shape,
+        ),
+        ("S", _) => array(
+            elements
+                .iter()
+                .map(|x| {
+                    let end = x.iter().position(|&c| c == 0).unwrap_or(x.len());
+                    String::from_utf8_lossy(&x[..end]).into_owned()
+                })
+                .collect(),
+            shape,
+        ),
+        _ => Err(unsupported(format!("The type {descr} is not supported."))),
+    }
+}
+
+/// Reads the arrays in an NPZ file, compressed or not, in the order in the file.
+fn read_npz(data: &[u8]) -> Result<Arrays, io::Error> {
+    let mut npz = zip::ZipArchive::new(Cursor::new(data)).map_err(invalid)?;
+    let mut arrays = vec![];
+    for index in 0..npz.len() {
+        let mut file = npz.by_index(index).map_err(invalid)?;
+        if file.is_dir() {
+            continue;
+        }
+        let name = file.name().to_string();
+        let mut npy = vec![];
+        file.read_to_end(&mut npy)?;
+        let obj = read_npy(&npy).map_err(|err| unsupported(format!("{name}: {err}")))?;
+        let name = name.strip_suffix(".npy").unwrap_or(&name).to_string();
+        arrays.push((Some(name), obj.pack()));
+    }
+    Ok(arrays)
+}
+
+/// Reads a numeric CSV file as a two-dimensional array of rows and columns, or a one-dimensional array if there is only one column. The first row is skipped as a header if it is not numeric, and so are empty lines and lines starting with `#`. The array is of integers if all the fields are integers, and of real numbers otherwise, in which empty fields are NaN.
+fn read_csv(data: &[u8]) -> Result<ArrayObject, io::Error> {
+    let text = std::str::from_utf8(data).map_err(invalid)?;
+    let mut rows: Vec<Vec<&str>> = vec![];
+    for (line, row) in text.lines().enumerate() {
+        let row = row.trim();
+        if row.is_empty() || row.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<_> = row.split(',').map(str::trim).collect();
+        let numeric = fields
+            .iter()
+            .all(|field| field.is_empty() || field.parse::<f64>().is_ok());
+        if !numeric {
+            if rows.is_empty() {
+                continue;
+            }
+            return Err(unsupported(format!("Line {} is not numeric.", line + 1)));
+        }
+        if let Some(first) = rows.first()
+            && fields.len() != first.len()
+        {
+            return Err(unsupported(format!(
+                "Line {} has {} columns while the first row has {}.",
+                line + 1,
+                fields.len(),
+                first.len()
+            )));
+        }
+        rows.push(fields);
+    }
+    let Some(first) = rows.first() else {
+        return Err(unsupported("The CSV file has no data."));
+    };
+    let shape = match first.len() {
+        1 => vec![rows.len() as u64],
+        cols => vec![rows.len() as u64, cols as u64],
+    };
+    let fields: Vec<_> = rows.into_iter().flatten().collect();
+    match fields
+        .iter()
+        .map(|field| field.parse())
+        .collect::<Result<Vec<i64>, _>>()
+    {
+        Ok(val) => array(val, shape),
+        Err(_) => array(
+            fields
+                .iter()
+                .map(|field| field.parse().unwrap_or(f64::NAN))
+                .collect::<Vec<f64>>(),
+            shape,
+        ),
+    }
+}
+
+/// Posts the arrays in a file to the running server by [`Operation::Import`]. The board is selected by `BB_NAMESPACE` as in the clients.
+pub fn import_file(path: &str, title: &str, tag: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut file = File::open(path)?;
+    let filename = Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    match LISTEN_ADDR.to_socket_addrs() {
+        Ok(addrs) => {
+            let addrs: Vec<_> = addrs.map(loopback).collect();
+            let stream = TcpStream::connect(&addrs[..])?;
+            if TLS_CERT.is_some() {
+                let stream = tls::connect_local(stream)?;
+                request(stream, &mut file, &filename, title, tag)
+            } else {
+                request(stream, &mut file, &filename, title, tag)
+            }
+        }
+        #[cfg(target_family = "unix")]
+        Err(_) => request(
+            UnixStream::connect(&*LISTEN_ADDR)?,
+            &mut file,
+            &filename,
+            title,
+            tag,
+        ),
+        #[cfg(not(target_family = "unix"))]
+        Err(err) => Err(Box::new(err)),
+    }
+}
+
+fn request<S: Read + Write>(
+    mut stream: S,
+    file: &mut File,
+    filename: &str,
+    title: &str,
+    tag: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    ciborium::into_writer(&Operation::Handshake, &mut stream)?;
+    let handshake = Handshake::new(capability::ACKNOWLEDGE)
+        .with_token(std::env::var("BB_TOKEN").ok())
+        .with_namespace(std::env::var("BB_NAMESPACE").ok());
+    ciborium::into_writer(&handshake, &mut stream)?;
+    check(ciborium::from_reader(&mut stream)?)?;
+    let _: Handshake = ciborium::from_reader(&mut stream)?;
+    ciborium::into_writer(&Operation::Import, &mut stream)?;
+    ciborium::into_writer(&(title, tag, filename), &mut stream)?;
+    write_chunks(file, &mut stream)??;
+    check(ciborium::from_reader(&mut stream)?)?;
+    Ok(())
+}
+
+fn check(response: Response) -> Result<(), io::Error> {
+    match response {
+        Response::Ok => Ok(()),
+        Response::NotFound => Err(io::Error::new(io::ErrorKind::NotFound, "Not found.")),
+        Response::NotUnique(_) => Err(io::Error::other("Not unique.")),
+        Response::Error { message, .. } => Err(io::Error::other(message)),
+    }
+}
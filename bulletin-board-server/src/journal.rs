@@ -0,0 +1,269 @@
+use crate::bulletin::{Bulletin, BulletinBackend};
+use crate::logging;
+use bulletin_board_common::{Attributes, Retention, Selection};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Cursor, Read, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Operations recorded in the journal. Each entry is written after the operation is applied to the board, so that a failed operation is not replayed.
+#[derive(Serialize, Deserialize)]
+pub enum Entry {
+    /// Title, tag, datasize, timestamp and attributes. The data of the given size follows the entry. Only written by checkpoints.
+    Post(String, String, u64, i64, Attributes),
+    /// Number and datasize of the data of a post, which follows the entry. It is written before the board is locked, and a crash before the [`Entry::Posted`] of the same number leaves it unused.
+    Data(u64, u64),
+    /// Title, tag, number of the [`Entry::Data`], timestamp and attributes of a post.
+    Posted(String, String, u64, i64, Attributes),
+    Relabel(String, String, Option<String>, Option<String>),
+    Clear(String, String, Vec<u64>),
+    /// Revisions cleared by the retention policy. The cleared revisions at the beginning are dropped as well.
+    Retire(String, String, Vec<u64>),
+    Remove(String, String),
+    Load(String),
+    Unload(String),
+    LoadSelected(String, Selection),
+    /// Title, tag, archive name, offset, datasize, timestamp, checksum and attributes of a revision kept in an archive. Only written by checkpoints.
+    Archived(
+        String,
+        String,
+        String,
+        u64,
+        u64,
+        i64,
+        Option<u32>,
+        Attributes,
+    ),
+    /// Title, tag, datasize and timestamp of a cleared revision. Only written by checkpoints.
+    Deleted(String, String, u64, i64),
+    /// Keys loaded from an archive. Only written by checkpoints.
+    Loaded(String, Vec<(String, String)>),
+    /// Title, tag and the number of the first revision kept after the retention policy dropped the older ones. Only written by checkpoints.
+    Pruned(String, String, u64),
+    /// Title, tag and retention policy given by `set_retention`. The revisions cleared by the policy are recorded by [`Entry::Retire`].
+    SetRetention(String, Option<String>, Option<Retention>),
+}
+
+/// Path of the journal of the board in the directory.
+pub fn path(dir: &str) -> String {
+    format!("{dir}/journal.bin")
+}
+
+/// Journal file shared by the board and the writers of post data.
+struct Log {
+    file: File,
+    /// Size of the journal in bytes. It is locked while an entry is written, so that the entries are not interleaved.
+    size: Mutex<u64>,
+    /// Number of the next [`Entry::Data`].
+    next_data: AtomicU64,
+}
+
+impl Log {
+    fn write(&self, entry: &Entry, data: Option<&Bulletin>) -> Result<(), io::Error> {
+        let mut buffer = Cursor::new(vec![]);
+        ciborium::into_writer(entry, &mut buffer).map_err(io::Error::other)?;
+        let mut size = self.size.lock().unwrap();
+        (&self.file).write_all(buffer.get_ref())?;
+        *size += buffer.get_ref().len() as u64;
+        let Some(bulletin) = data else {
+            return Ok(());
+        };
+        *size += match &bulletin.data {
+            BulletinBackend::Memory(data) => {
+                (&self.file).write_all(data.get_ref())?;
+                data.get_ref().len() as u64
+            }
+            BulletinBackend::File(filename) => {
+                io::copy(&mut File::open(filename)?, &mut &self.file)?
+            }
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Only the data in memory or files can be journaled.",
+                ));
+            }
+        };
+        Ok(())
+    }
+    fn write_data(self: &Arc<Self>, bulletin: &Bulletin) -> Result<Data, io::Error> {
+        let number = self.next_data.fetch_add(1, Ordering::Relaxed);
+        self.write(&Entry::Data(number, bulletin.datasize), Some(bulletin))?;
+        Ok(Data {
+            log: self.clone(),
+            number,
+        })
+    }
+}
+
+pub struct Journal(Arc<Log>);
+
+/// Waits for the entries written to a journal to reach the disk. It does not borrow the board, so that the board can be unlocked while waiting.
+pub struct Flush(Arc<Log>);
+
+impl Flush {
+    pub fn wait(self) -> Result<(), io::Error> {
+        self.0.file.sync_data()
+    }
+}
+
+/// Writes the data of posts to the journal without locking the board.
+pub struct DataWriter(Arc<Log>);
+
+impl DataWriter {
+    /// Writes the data of a post, which is recorded by [`Journal::write_post`] once the post is on the board.
+    pub fn write(&self, bulletin: &Bulletin) -> Result<Data, io::Error> {
+        self.0.write_data(bulletin)
+    }
+}
+
+/// Data of a post written to a journal.
+pub struct Data {
+    log: Arc<Log>,
+    number: u64,
+}
+
+impl Journal {
+    pub fn open(path: &str) -> Result<Self, io::Error> {
+        let file = File::options().create(true).append(true).open(path)?;
+        Ok(Self(Arc::new(Log {
+            size: Mutex::new(file.metadata()?.len()),
+            file,
+            next_data: AtomicU64::new(0),
+        })))
+    }
+    /// Size of the journal in bytes.
+    pub fn size(&self) -> u64 {
+        *self.0.size.lock().unwrap()
+    }
+    pub fn flush(&self) -> Flush {
+        Flush(self.0.clone())
+    }
+    pub fn writer(&self) -> DataWriter {
+        DataWriter(self.0.clone())
+    }
+    /// Appends an entry and waits until it reaches the disk.
+    pub fn append(&mut self, entry: &Entry) -> Result<(), io::Error> {
+        self.write(entry)?;
+        self.0.file.sync_data()
+    }
+    fn write(&mut self, entry: &Entry) -> Result<(), io::Error> {
+        self.0.write(entry, None)
+    }
+    /// Writes a post without waiting for the disk. The caller waits by [`Journal::flush`] before acknowledging the post.
+    ///
+    /// The data written by [`DataWriter::write`] is referred to, unless the journal has been rewritten since, in which case it is written again.
+    pub fn write_post(
+        &mut self,
+        title: &str,
+        tag: &str,
+        bulletin: &Bulletin,
+        data: Option<Data>,
+    ) -> Result<(), io::Error> {
+        let data = match data {
+            Some(data) if Arc::ptr_eq(&data.log, &self.0) => data,
+            _ => self.0.write_data(bulletin)?,
+        };
+        self.write(&Entry::Posted(
+            title.to_string(),
+            tag.to_string(),
+            data.number,
+            bulletin.timestamp.timestamp_nanos_opt().unwrap(),
+            bulletin.attributes.clone(),
+        ))
+    }
+}
+
+/// Writes the state of a board to a new journal, which replaces the current one by [`Checkpoint::commit`].
+pub struct Checkpoint {
+    journal: Journal,
+    path: String,
+    target: String,
+}
+
+impl Checkpoint {
+    pub fn new(dir: &str) -> Result<Self, io::Error> {
+        let target = path(dir);
+        let path = format!("{target}.new");
+        if fs::exists(&path)? {
+            fs::remove_file(&path)?;
+        }
+        Ok(Self {
+            journal: Journal::open(&path)?,
+            path,
+            target,
+        })
+    }
+    pub fn write(&mut self, entry: &Entry) -> Result<(), io::Error> {
+        self.journal.write(entry)
+    }
+    pub fn write_post(
+        &mut self,
+        title: &str,
+        tag: &str,
+        bulletin: &Bulletin,
+    ) -> Result<(), io::Error> {
+        let entry = Entry::Post(
+            title.to_string(),
+            tag.to_string(),
+            bulletin.datasize,
+            bulletin.timestamp.timestamp_nanos_opt().unwrap(),
+            bulletin.attributes.clone(),
+        );
+        self.journal.0.write(&entry, Some(bulletin))
+    }
+    pub fn commit(self) -> Result<Journal, io::Error> {
+        self.journal.0.file.sync_all()?;
+        fs::rename(&self.path, &self.target)?;
+        Journal::open(&self.target)
+    }
+}
+
+/// Reads the entries of a journal. A truncated entry at the end, left by a crash while writing, ends the replay.
+pub struct Replay {
+    reader: BufReader<File>,
+    dir: String,
+}
+
+impl Replay {
+    /// Opens the journal in the directory, where the data of large bulletins is saved as well.
+    pub fn open(dir: &str) -> Result<Self, io::Error> {
+        Ok(Self {
+            reader: BufReader::new(File::open(path(dir))?),
+            dir: dir.to_string(),
+        })
+    }
+}
+
+impl Iterator for Replay {
+    type Item = (Entry, Option<Bulletin>);
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.reader.fill_buf().ok()?.is_empty() {
+            return None;
+        }
+        let entry: Entry = match ciborium::from_reader(&mut self.reader) {
+            Ok(entry) => entry,
+            Err(err) => {
+                logging::warn(format!("Journal replay stopped at a broken entry: {err}"));
+                return None;
+            }
+        };
+        if let Entry::Post(_, _, datasize, _, _) | Entry::Data(_, datasize) = &entry {
+            let mut bulletin =
+                match Bulletin::from_reader(&mut self.reader.by_ref().take(*datasize), &self.dir) {
+                    Ok(bulletin) => bulletin,
+                    Err(err) => {
+                        logging::warn(format!("Journal replay stopped at a broken entry: {err}"));
+                        return None;
+                    }
+                };
+            if bulletin.datasize != *datasize {
+                logging::warn("Journal replay stopped at a truncated entry.".to_string());
+                let _ = bulletin.clear();
+                return None;
+            }
+            return Some((entry, Some(bulletin)));
+        }
+        Some((entry, None))
+    }
+}
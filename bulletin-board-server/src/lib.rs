@@ -1,11 +1,25 @@
+mod archive;
 mod board;
 mod bulletin;
+mod chunk;
 mod error;
+mod export;
+mod import;
+mod journal;
 mod logging;
+mod permission;
+mod query;
 mod server;
+mod session;
+mod tls;
+mod watch;
 
+pub use archive::verify_archive;
+pub use export::export_archive;
+pub use import::import_file;
 pub use server::{BBServer, ServerOptions};
 
+use permission::Role;
 use std::sync::LazyLock;
 
 static LISTEN_ADDR: LazyLock<String> =
@@ -28,6 +42,37 @@ static FILE_THRETHOLD: LazyLock<u64> = LazyLock::new(|| {
     parse_size::parse_size(std::env::var("BB_FILE_THRETHOLD").unwrap_or("1MiB".to_string()))
         .unwrap()
 });
+static MAX_REVISIONS: LazyLock<Option<u64>> = LazyLock::new(|| {
+    std::env::var("BB_MAX_REVISIONS")
+        .ok()
+        .map(|val| val.parse().unwrap())
+});
+static MAX_AGE: LazyLock<Option<u64>> = LazyLock::new(|| {
+    std::env::var("BB_MAX_AGE")
+        .ok()
+        .map(|val| val.parse().unwrap())
+});
+static MAX_BYTES: LazyLock<Option<u64>> = LazyLock::new(|| {
+    std::env::var("BB_MAX_BYTES")
+        .ok()
+        .map(|val| parse_size::parse_size(val).unwrap())
+});
+static PROMOTE_READS: LazyLock<Option<u64>> = LazyLock::new(|| {
+    std::env::var("BB_PROMOTE_READS")
+        .ok()
+        .map(|val| val.parse().unwrap())
+});
+static JOURNAL: LazyLock<bool> = LazyLock::new(|| std::env::var("BB_JOURNAL").is_ok());
+static JOURNAL_LIMIT: LazyLock<u64> = LazyLock::new(|| {
+    parse_size::parse_size(std::env::var("BB_JOURNAL_LIMIT").unwrap_or("256MiB".to_string()))
+        .unwrap()
+});
+static MAX_NAMESPACES: LazyLock<usize> = LazyLock::new(|| {
+    std::env::var("BB_MAX_NAMESPACES")
+        .unwrap_or("256".to_string())
+        .parse()
+        .unwrap()
+});
 static LOG_FILE: LazyLock<String> =
     LazyLock::new(|| std::env::var("BB_LOG_FILE").unwrap_or("./bulletin-board.log".to_string()));
 
@@ -39,3 +84,31 @@ static LOG_LEVEL: LazyLock<u8> = LazyLock::new(|| {
         .parse()
         .unwrap()
 });
+
+static TLS_CERT: LazyLock<Option<String>> = LazyLock::new(|| std::env::var("BB_TLS_CERT").ok());
+static TLS_KEY: LazyLock<Option<String>> = LazyLock::new(|| std::env::var("BB_TLS_KEY").ok());
+static TLS_CA: LazyLock<Option<String>> = LazyLock::new(|| std::env::var("BB_TLS_CA").ok());
+
+/// Users, their tokens and roles accepted over TCP. Authentication is disabled if it is empty.
+static TOKENS: LazyLock<Vec<(String, String, Role)>> = LazyLock::new(|| {
+    let mut tokens = vec![];
+    if let Ok(token) = std::env::var("BB_TOKEN") {
+        tokens.push(("shared".to_string(), token, Role::Admin));
+    }
+    if let Ok(path) = std::env::var("BB_TOKEN_FILE") {
+        tokens.extend(permission::read_tokens(&path).unwrap());
+    }
+    tokens
+});
+/// Role of the TCP clients if authentication is disabled.
+static DEFAULT_ROLE: LazyLock<Role> = LazyLock::new(|| {
+    std::env::var("BB_DEFAULT_ROLE")
+        .map(|val| val.parse().unwrap())
+        .unwrap_or(Role::Admin)
+});
+/// Roles of the users connecting through the Unix socket by their uids. The others are admins.
+static UNIX_ROLES: LazyLock<Vec<(u32, Role)>> = LazyLock::new(|| {
+    std::env::var("BB_UNIX_ROLES")
+        .map(|val| permission::parse_unix_roles(&val).unwrap())
+        .unwrap_or_default()
+});
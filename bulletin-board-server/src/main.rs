@@ -1,5 +1,5 @@
-use bulletin_board_server::{BBServer, ServerOptions};
-use clap::Parser;
+use bulletin_board_server::{BBServer, ServerOptions, export_archive, import_file, verify_archive};
+use clap::{Parser, Subcommand};
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
@@ -7,9 +7,43 @@ struct Args {
     /// Log to stdout
     #[arg(short, long)]
     debug: bool,
+    /// Record operations in a journal and recover the board on restart
+    #[arg(short, long)]
+    journal: bool,
     /// Log level [0: No logging, 1: Error, 2: +Warn, 3: +Notice (default), 4: +Info, 5: +Debug]
     #[arg(short, long)]
     log_level: Option<u8>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Check an archive in BB_ACV_DIR without starting the server
+    Verify {
+        /// Name of the archive
+        name: String,
+        /// Rewrite the index with the valid revisions
+        #[arg(short, long)]
+        repair: bool,
+    },
+    /// Export an archive in BB_ACV_DIR to NPZ and JSON without starting the server
+    Export {
+        /// Name of the archive
+        name: String,
+        /// Path of the output without the extension [default: name of the archive]
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Post a .npy, .npz or CSV file to the running server
+    Import {
+        /// Path of the file
+        file: String,
+        /// Title of the bulletin. The arrays in a .npz file are posted as title/name
+        title: String,
+        /// Tag of the bulletin
+        tag: String,
+    },
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -20,12 +54,52 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         opt.set_debug();
     }
 
+    if args.journal {
+        opt.set_journal();
+    }
+
     if let Some(log_level) = args.log_level {
         opt.set_log_level(log_level);
     }
 
     opt.load_options();
 
+    if let Some(Command::Export { name, output }) = args.command {
+        let output = output.unwrap_or_else(|| name.clone());
+        let len = export_archive(&name, &output)?;
+        println!("{len} revisions are exported to {output}.npz and {output}.json.");
+        return Ok(());
+    }
+
+    if let Some(Command::Import { file, title, tag }) = args.command {
+        import_file(&file, &title, &tag)?;
+        println!("{file} is posted to {title} with tag {tag}.");
+        return Ok(());
+    }
+
+    if let Some(Command::Verify { name, repair }) = args.command {
+        let report = verify_archive(&name, repair)?;
+        println!("format version: {}", report.format_version);
+        println!("valid revisions: {}", report.valid);
+        for (title, tag, revision) in &report.truncated {
+            println!("truncated: title: {title}, tag: {tag}, revision: {revision}");
+        }
+        for (title, tag, revision) in &report.corrupted {
+            println!("corrupted: title: {title}, tag: {tag}, revision: {revision}");
+        }
+        if let Some(broken_index) = &report.broken_index {
+            println!("broken index: {broken_index}");
+        }
+        println!("orphaned bytes: {}", report.orphaned_bytes);
+        if report.repaired {
+            println!("The index is rewritten with the valid revisions.");
+        } else if !report.is_intact() {
+            println!("The archive is damaged. Run with --repair to drop the damaged revisions.");
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     let mut server = BBServer::new()?;
     server.listen()?;
     Ok(())
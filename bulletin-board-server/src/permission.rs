@@ -0,0 +1,178 @@
+use bulletin_board_common::Operation;
+use std::fmt::Display;
+use std::io;
+use std::str::FromStr;
+
+/// Role of a client. Each role is allowed the operations of the roles below it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Role {
+    /// Reads the board and the archives.
+    ReadOnly,
+    /// Posts, archives and loads bulletins and merges archives into the board as well.
+    Writer,
+    /// Resets and terminates the server, restores archives in place of the board, deletes and repairs archives and manages the log as well.
+    Admin,
+}
+
+impl Role {
+    pub fn permits(&self, operation: &Operation) -> bool {
+        *self >= Self::required(operation)
+    }
+    /// The least role allowed to run an operation. A restore without merging and a repair of an archive need the admin role, which is checked with the arguments.
+    pub fn required(operation: &Operation) -> Self {
+        match operation {
+            Operation::Read
+            | Operation::Version
+            | Operation::Status
+            | Operation::ViewBoard
+            | Operation::GetInfo
+            | Operation::ListArchive
+            | Operation::NoReply
+            | Operation::Handshake
+            | Operation::ReadStream
+            | Operation::Watch
+            | Operation::WaitRead
+            | Operation::Query
+            | Operation::ListLoaded
+            | Operation::ArchiveInfo
+            | Operation::Export => Role::ReadOnly,
+            Operation::Post
+            | Operation::Relabel
+            | Operation::ClearRevisions
+            | Operation::Remove
+            | Operation::Archive
+            | Operation::Load
+            | Operation::RenameArchive
+            | Operation::Dump
+            | Operation::Restore
+            | Operation::PostStream
+            | Operation::SetRetention
+            | Operation::VerifyArchive
+            | Operation::CompactArchive
+            | Operation::Unload
+            | Operation::LoadSelected
+            | Operation::RestoreSelected
+            | Operation::Import => Role::Writer,
+            Operation::Log
+            | Operation::ClearLog
+            | Operation::Reset
+            | Operation::Terminate
+            | Operation::DeleteArchive
+            | Operation::DeleteFromArchive => Role::Admin,
+        }
+    }
+}
+
+impl FromStr for Role {
+    type Err = io::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "read-only" => Ok(Role::ReadOnly),
+            "writer" => Ok(Role::Writer),
+            "admin" => Ok(Role::Admin),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Unknown role: {s}. The role is read-only, writer or admin."),
+            )),
+        }
+    }
+}
+
+impl Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Role::ReadOnly => write!(f, "read-only"),
+            Role::Writer => write!(f, "writer"),
+            Role::Admin => write!(f, "admin"),
+        }
+    }
+}
+
+/// Reads a token file. Each line is `user:token` or `user:token:role`, where the role is admin if omitted. Empty lines and lines starting with `#` are ignored.
+pub fn read_tokens(path: &str) -> Result<Vec<(String, String, Role)>, io::Error> {
+    let mut tokens = vec![];
+    for (i, line) in std::fs::read_to_string(path)?.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let invalid = |message: String| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Line {} of {path}: {message}", i + 1),
+            )
+        };
+        let mut fields = line.splitn(3, ':');
+        match (fields.next(), fields.next(), fields.next()) {
+            (Some(user), Some(token), role) if !user.is_empty() && !token.is_empty() => {
+                let role = match role {
+                    Some(role) => role
+                        .parse()
+                        .map_err(|err: io::Error| invalid(err.to_string()))?,
+                    None => Role::Admin,
+                };
+                tokens.push((user.to_string(), token.to_string(), role));
+            }
+            _ => return Err(invalid("not in the form user:token:role.".to_string())),
+        }
+    }
+    Ok(tokens)
+}
+
+/// Parses the roles of Unix users in the form `uid:role,uid:role`.
+pub fn parse_unix_roles(roles: &str) -> Result<Vec<(u32, Role)>, io::Error> {
+    roles
+        .split(',')
+        .filter(|entry| !entry.trim().is_empty())
+        .map(|entry| {
+            let invalid = || {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{entry} is not in the form uid:role."),
+                )
+            };
+            let (uid, role) = entry.trim().split_once(':').ok_or_else(invalid)?;
+            Ok((uid.parse().map_err(|_| invalid())?, role.parse()?))
+        })
+        .collect()
+}
+
+/// User id of the peer of a Unix socket.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn peer_uid(stream: &std::os::unix::net::UnixStream) -> Result<u32, io::Error> {
+    use std::os::fd::AsRawFd;
+    let mut cred = libc::ucred {
+        pid: 0,
+        uid: 0,
+        gid: 0,
+    };
+    let mut len = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+    let ret = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            &mut cred as *mut libc::ucred as *mut libc::c_void,
+            &mut len,
+        )
+    };
+    if ret != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(cred.uid)
+}
+
+/// User id of the peer of a Unix socket.
+#[cfg(all(
+    target_family = "unix",
+    not(any(target_os = "linux", target_os = "android"))
+))]
+pub fn peer_uid(stream: &std::os::unix::net::UnixStream) -> Result<u32, io::Error> {
+    use std::os::fd::AsRawFd;
+    let mut uid = 0;
+    let mut gid = 0;
+    if unsafe { libc::getpeereid(stream.as_raw_fd(), &mut uid, &mut gid) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(uid)
+}
@@ -0,0 +1,94 @@
+use bulletin_board_common::{Attributes, Pattern, Query, Selection};
+use regex::Regex;
+use std::io;
+
+/// Compiled patterns of a [`Query`] or a [`Selection`].
+pub struct Filter {
+    title: Option<Regex>,
+    tag: Option<Regex>,
+    attributes: Vec<(String, Regex)>,
+}
+
+impl Filter {
+    pub fn new(query: &Query) -> Result<Self, io::Error> {
+        Ok(Self {
+            title: query.title.as_ref().map(compile).transpose()?,
+            tag: query.tag.as_ref().map(compile).transpose()?,
+            attributes: query
+                .attributes
+                .iter()
+                .map(|(key, pattern)| Ok((key.clone(), compile(pattern)?)))
+                .collect::<Result<_, io::Error>>()?,
+        })
+    }
+    pub fn from_selection(selection: &Selection) -> Result<Self, io::Error> {
+        Ok(Self {
+            title: selection.title.as_ref().map(compile).transpose()?,
+            tag: selection.tag.as_ref().map(compile).transpose()?,
+            attributes: vec![],
+        })
+    }
+    pub fn matches(&self, title: &str, tag: &str) -> bool {
+        self.title.as_ref().is_none_or(|re| re.is_match(title))
+            && self.tag.as_ref().is_none_or(|re| re.is_match(tag))
+    }
+    /// Returns true if any of the revisions has all the attributes matching.
+    pub fn matches_attributes<'a>(
+        &self,
+        mut revisions: impl Iterator<Item = &'a Attributes>,
+    ) -> bool {
+        self.attributes.is_empty()
+            || revisions.any(|attributes| {
+                self.attributes
+                    .iter()
+                    .all(|(key, re)| attributes.get(key).is_some_and(|val| re.is_match(val)))
+            })
+    }
+}
+
+/// Compiles a pattern into a regex matching the whole string.
+fn compile(pattern: &Pattern) -> Result<Regex, io::Error> {
+    let re = match pattern {
+        Pattern::Glob(glob) => glob_to_regex(glob),
+        Pattern::Regex(re) => format!("^(?:{re})$"),
+    };
+    Regex::new(&re).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))
+}
+
+/// An unclosed `[` is taken literally. A class starting with `!` is negated.
+fn glob_to_regex(glob: &str) -> String {
+    let mut re = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => re.push_str(".*"),
+            '?' => re.push('.'),
+            '[' => {
+                let class: String = chars.clone().take_while(|&c| c != ']').collect();
+                if chars.clone().nth(class.chars().count()).is_none() || class.is_empty() {
+                    re.push_str(r"\[");
+                    continue;
+                }
+                chars.nth(class.chars().count());
+                re.push('[');
+                let class = match class.strip_prefix('!') {
+                    Some(rest) => {
+                        re.push('^');
+                        rest.to_string()
+                    }
+                    None => class,
+                };
+                for c in class.chars() {
+                    if matches!(c, '\\' | '[' | '&' | '~' | '^') {
+                        re.push('\\');
+                    }
+                    re.push(c);
+                }
+                re.push(']');
+            }
+            c => re.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+    }
+    re.push('$');
+    re
+}
@@ -1,27 +1,57 @@
-use std::io::Cursor;
-use std::net::TcpListener;
+use std::io::{Cursor, Read};
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, TcpStream};
 
 #[cfg(target_family = "unix")]
-use std::os::unix::net::UnixListener;
+use std::os::unix::net::{UnixListener, UnixStream};
 
-use crate::board::BulletinBoard;
-use crate::bulletin::Bulletin;
-use crate::error::{ArchiveError, BulletinError};
+use crate::board::{self, BulletinBoard, NAMESPACE_DIR};
+use crate::bulletin::{Bulletin, BulletinBackend};
+use crate::chunk::{ChunkReader, write_chunks};
+use crate::error::{
+    ArchiveError, BulletinError, NotUniqueError, error_kind, error_response, is_fatal,
+};
+use crate::import;
 use crate::logging;
+use crate::permission::Role;
+use crate::query::Filter;
+use crate::session::Session;
+use crate::tls;
+use crate::watch::{Peer, Watchers};
 use crate::{
-    ACV_DIR, DEBUG, FILE_THRETHOLD, LISTEN_ADDR, LOG_FILE, LOG_LEVEL, TMP_DIR, TOT_MEM_LIMIT,
+    ACV_DIR, DEBUG, DEFAULT_ROLE, FILE_THRETHOLD, JOURNAL, JOURNAL_LIMIT, LISTEN_ADDR, LOG_FILE,
+    LOG_LEVEL, MAX_AGE, MAX_BYTES, MAX_NAMESPACES, MAX_REVISIONS, PROMOTE_READS, TLS_CERT, TLS_KEY,
+    TMP_DIR, TOKENS, TOT_MEM_LIMIT, UNIX_ROLES,
 };
 use bulletin_board_common::*;
+use rustls::{ServerConnection, StreamOwned};
 use serde_bytes::ByteBuf;
+use std::collections::HashMap;
 use std::env;
 use std::fs;
 use std::io;
 use std::net::ToSocketAddrs;
 use std::path::Path;
-use std::sync::LazyLock;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, RwLock, mpsc};
+use std::thread;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Arguments of [`Operation::Post`]. Clients of protocol version 1 do not send the attributes.
+#[derive(serde::Deserialize)]
+struct PostArgs(String, String, ByteBuf, #[serde(default)] Attributes);
+
+/// Header of [`Operation::PostStream`] preceding the chunks.
+#[derive(serde::Deserialize)]
+struct PostStreamArgs(String, String, #[serde(default)] Attributes);
+
+/// Boards and watchers of the namespaces opened so far. The default namespace is keyed by `None`.
+type Namespaces = HashMap<Option<String>, (Arc<RwLock<BulletinBoard>>, Arc<Watchers>)>;
 
 pub struct ServerOptions {
     debug: bool,
+    journal: bool,
+    journal_limit: Option<String>,
     listen_addr: Option<String>,
     tmp_dir: Option<String>,
     acv_dir: Option<String>,
@@ -29,12 +59,31 @@ pub struct ServerOptions {
     file_threshold: Option<String>,
     log_file: Option<String>,
     log_level: Option<u8>,
+    max_revisions: Option<u64>,
+    max_age: Option<u64>,
+    max_bytes: Option<String>,
+    promote_reads: Option<u64>,
+    max_namespaces: Option<usize>,
+    token: Option<String>,
+    token_file: Option<String>,
+    default_role: Option<String>,
+    unix_roles: Option<String>,
+    tls_cert: Option<String>,
+    tls_key: Option<String>,
+}
+
+impl Default for ServerOptions {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ServerOptions {
     pub fn new() -> Self {
         Self {
             debug: false,
+            journal: false,
+            journal_limit: None,
             listen_addr: None,
             tmp_dir: None,
             acv_dir: None,
@@ -42,11 +91,30 @@ impl ServerOptions {
             file_threshold: None,
             log_file: None,
             log_level: None,
+            max_revisions: None,
+            max_age: None,
+            max_bytes: None,
+            promote_reads: None,
+            max_namespaces: None,
+            token: None,
+            token_file: None,
+            default_role: None,
+            unix_roles: None,
+            tls_cert: None,
+            tls_key: None,
         }
     }
     pub fn set_debug(&mut self) {
         self.debug = true;
     }
+    /// Records the operations in a journal so that the board is recovered after a restart.
+    pub fn set_journal(&mut self) {
+        self.journal = true;
+    }
+    /// Sets the size of the journal beyond which it is rewritten with the current state if it is also larger than twice the data on the board, e.g. "256MiB".
+    pub fn set_journal_limit(&mut self, journal_limit: String) {
+        self.journal_limit = Some(journal_limit);
+    }
     pub fn set_listen_addr(&mut self, listen_addr: String) {
         self.listen_addr = Some(listen_addr);
     }
@@ -68,12 +136,66 @@ impl ServerOptions {
     pub fn set_log_level(&mut self, log_level: u8) {
         self.log_level = Some(log_level);
     }
+    /// Sets the default maximum number of revisions kept for each bulletin.
+    pub fn set_max_revisions(&mut self, max_revisions: u64) {
+        self.max_revisions = Some(max_revisions);
+    }
+    /// Sets the default maximum age of revisions in seconds.
+    pub fn set_max_age(&mut self, max_age: u64) {
+        self.max_age = Some(max_age);
+    }
+    /// Sets the default maximum total size of revisions of each bulletin, e.g. "100MiB".
+    pub fn set_max_bytes(&mut self, max_bytes: String) {
+        self.max_bytes = Some(max_bytes);
+    }
+    /// Sets the number of reads after which a bulletin saved to a file is moved back to memory.
+    pub fn set_promote_reads(&mut self, promote_reads: u64) {
+        self.promote_reads = Some(promote_reads);
+    }
+    /// Sets the maximum number of namespaces, including the default one.
+    pub fn set_max_namespaces(&mut self, max_namespaces: usize) {
+        self.max_namespaces = Some(max_namespaces);
+    }
+    /// Sets a token shared by all the clients connecting over TCP.
+    pub fn set_token(&mut self, token: String) {
+        self.token = Some(token);
+    }
+    /// Sets the path to a file of per-user tokens for clients connecting over TCP. Each line is `user:token:role`, where the role is read-only, writer or admin (default).
+    pub fn set_token_file(&mut self, token_file: String) {
+        self.token_file = Some(token_file);
+    }
+    /// Sets the role of the clients connecting over TCP when token authentication is disabled, e.g. "read-only".
+    pub fn set_default_role(&mut self, default_role: String) {
+        self.default_role = Some(default_role);
+    }
+    /// Sets the roles of the users connecting through the Unix socket, e.g. "1001:read-only,1002:writer". The other users are admins.
+    pub fn set_unix_roles(&mut self, unix_roles: String) {
+        self.unix_roles = Some(unix_roles);
+    }
+    /// Sets the path to the PEM file of the certificate chain. TLS is enabled on the TCP listener if the key is set as well.
+    pub fn set_tls_cert(&mut self, tls_cert: String) {
+        self.tls_cert = Some(tls_cert);
+    }
+    /// Sets the path to the PEM file of the private key of the certificate.
+    pub fn set_tls_key(&mut self, tls_key: String) {
+        self.tls_key = Some(tls_key);
+    }
     pub fn load_options(&self) {
         if self.debug {
             unsafe {
                 env::set_var("BB_DEBUG", "");
             }
         }
+        if self.journal {
+            unsafe {
+                env::set_var("BB_JOURNAL", "");
+            }
+        }
+        if let Some(journal_limit) = &self.journal_limit {
+            unsafe {
+                env::set_var("BB_JOURNAL_LIMIT", journal_limit);
+            }
+        }
         if let Some(listen_addr) = &self.listen_addr {
             unsafe {
                 env::set_var("BB_LISTEN_ADDR", listen_addr);
@@ -109,7 +231,64 @@ impl ServerOptions {
                 env::set_var("BB_LOG_LEVEL", log_level.to_string());
             }
         }
+        if let Some(max_revisions) = &self.max_revisions {
+            unsafe {
+                env::set_var("BB_MAX_REVISIONS", max_revisions.to_string());
+            }
+        }
+        if let Some(max_age) = &self.max_age {
+            unsafe {
+                env::set_var("BB_MAX_AGE", max_age.to_string());
+            }
+        }
+        if let Some(max_bytes) = &self.max_bytes {
+            unsafe {
+                env::set_var("BB_MAX_BYTES", max_bytes);
+            }
+        }
+        if let Some(promote_reads) = &self.promote_reads {
+            unsafe {
+                env::set_var("BB_PROMOTE_READS", promote_reads.to_string());
+            }
+        }
+        if let Some(max_namespaces) = &self.max_namespaces {
+            unsafe {
+                env::set_var("BB_MAX_NAMESPACES", max_namespaces.to_string());
+            }
+        }
+        if let Some(token) = &self.token {
+            unsafe {
+                env::set_var("BB_TOKEN", token);
+            }
+        }
+        if let Some(token_file) = &self.token_file {
+            unsafe {
+                env::set_var("BB_TOKEN_FILE", token_file);
+            }
+        }
+        if let Some(default_role) = &self.default_role {
+            unsafe {
+                env::set_var("BB_DEFAULT_ROLE", default_role);
+            }
+        }
+        if let Some(unix_roles) = &self.unix_roles {
+            unsafe {
+                env::set_var("BB_UNIX_ROLES", unix_roles);
+            }
+        }
+        if let Some(tls_cert) = &self.tls_cert {
+            unsafe {
+                env::set_var("BB_TLS_CERT", tls_cert);
+            }
+        }
+        if let Some(tls_key) = &self.tls_key {
+            unsafe {
+                env::set_var("BB_TLS_KEY", tls_key);
+            }
+        }
         LazyLock::force(&DEBUG);
+        LazyLock::force(&JOURNAL);
+        LazyLock::force(&JOURNAL_LIMIT);
         LazyLock::force(&LISTEN_ADDR);
         LazyLock::force(&TMP_DIR);
         LazyLock::force(&ACV_DIR);
@@ -117,12 +296,42 @@ impl ServerOptions {
         LazyLock::force(&FILE_THRETHOLD);
         LazyLock::force(&LOG_FILE);
         LazyLock::force(&LOG_LEVEL);
+        LazyLock::force(&MAX_REVISIONS);
+        LazyLock::force(&MAX_AGE);
+        LazyLock::force(&MAX_BYTES);
+        LazyLock::force(&PROMOTE_READS);
+        LazyLock::force(&MAX_NAMESPACES);
+        LazyLock::force(&TOKENS);
+        LazyLock::force(&DEFAULT_ROLE);
+        LazyLock::force(&UNIX_ROLES);
+        LazyLock::force(&TLS_CERT);
+        LazyLock::force(&TLS_KEY);
+    }
+}
+
+/// Replaces an unspecified address to listen on by the loopback address to connect to.
+pub fn loopback(mut addr: SocketAddr) -> SocketAddr {
+    if addr.ip().is_unspecified() {
+        match addr {
+            SocketAddr::V4(_) => addr.set_ip(Ipv4Addr::LOCALHOST.into()),
+            SocketAddr::V6(_) => addr.set_ip(Ipv6Addr::LOCALHOST.into()),
+        }
     }
+    addr
 }
 
+/// Bulletin board server. Each connection is served by a separate thread.
+///
+/// The board is shared behind a reader/writer lock so that read-only operations run in parallel while mutating operations are serialized.
+///
+/// Each namespace has its own board. A connection works on the board of the namespace selected in the handshake, or the default one.
+#[derive(Clone)]
 pub struct BBServer {
-    bulletinboard: BulletinBoard,
-    archive_manipulations: Vec<(String, Option<String>)>,
+    bulletinboard: Arc<RwLock<BulletinBoard>>,
+    watchers: Arc<Watchers>,
+    namespaces: Arc<Mutex<Namespaces>>,
+    memory: Arc<AtomicU64>,
+    terminated: Arc<AtomicBool>,
 }
 
 impl BBServer {
@@ -130,11 +339,107 @@ impl BBServer {
         if *LOG_LEVEL == 5 {
             logging::warn("Server is running in verbose mode.".to_string());
         }
+        let memory = Arc::new(AtomicU64::new(0));
+        let bulletinboard = Arc::new(RwLock::new(BulletinBoard::new(None, memory.clone())?));
+        let watchers = Arc::new(Watchers::default());
+        let mut namespaces = Namespaces::new();
+        namespaces.insert(None, (bulletinboard.clone(), watchers.clone()));
+        // In the journal mode, the boards of the other namespaces are recovered as well. Otherwise, they are left from the previous run.
+        let namespace_dir = format!("{}/{NAMESPACE_DIR}", *TMP_DIR);
+        if Path::new(&namespace_dir).exists() {
+            if *JOURNAL {
+                for entry in fs::read_dir(&namespace_dir)? {
+                    let namespace = entry?.file_name().to_string_lossy().to_string();
+                    if board::check_namespace(&namespace).is_ok() {
+                        let board = BulletinBoard::new(Some(namespace.clone()), memory.clone())?;
+                        namespaces.insert(
+                            Some(namespace),
+                            (Arc::new(RwLock::new(board)), Arc::new(Watchers::default())),
+                        );
+                    }
+                }
+            } else {
+                fs::remove_dir_all(&namespace_dir)?;
+            }
+        }
         Ok(Self {
-            bulletinboard: BulletinBoard::new()?,
-            archive_manipulations: vec![],
+            bulletinboard,
+            watchers,
+            namespaces: Arc::new(Mutex::new(namespaces)),
+            memory,
+            terminated: Arc::new(AtomicBool::new(false)),
         })
     }
+    /// Returns the server working on the board of the namespace. The board is created when the namespace is selected for the first time if `create` is true.
+    fn select(&self, namespace: Option<&str>, create: bool) -> Result<Self, std::io::Error> {
+        if let Some(namespace) = namespace {
+            board::check_namespace(namespace)?;
+        }
+        let key = namespace.map(str::to_string);
+        let mut namespaces = self.namespaces.lock().unwrap();
+        let (bulletinboard, watchers) = match namespaces.get(&key) {
+            Some(entry) => entry.clone(),
+            None if !create => {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("Namespace {} does not exist.", namespace.unwrap_or("")),
+                ));
+            }
+            None if namespaces.len() >= *MAX_NAMESPACES => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "The number of namespaces reached the limit of {}.",
+                        *MAX_NAMESPACES
+                    ),
+                ));
+            }
+            None => {
+                let entry = (
+                    Arc::new(RwLock::new(BulletinBoard::new(
+                        key.clone(),
+                        self.memory.clone(),
+                    )?)),
+                    Arc::new(Watchers::default()),
+                );
+                logging::info(format!("Created namespace {}.", namespace.unwrap_or("")));
+                namespaces.insert(key, entry.clone());
+                entry
+            }
+        };
+        Ok(Self {
+            bulletinboard,
+            watchers,
+            namespaces: self.namespaces.clone(),
+            memory: self.memory.clone(),
+            terminated: self.terminated.clone(),
+        })
+    }
+    /// Boards of the namespaces other than the selected one.
+    fn other_boards(&self) -> Vec<(Option<String>, Arc<RwLock<BulletinBoard>>)> {
+        self.namespaces
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, (board, _))| !Arc::ptr_eq(board, &self.bulletinboard))
+            .map(|(namespace, (board, _))| (namespace.clone(), board.clone()))
+            .collect()
+    }
+    /// Fails if the archive is loaded in another namespace, since the board there reads the data from the archive.
+    fn check_unloaded_elsewhere(&self, acv_name: &str) -> Result<(), std::io::Error> {
+        for (namespace, board) in self.other_boards() {
+            if board.read().unwrap().is_loaded(acv_name) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "Archive {acv_name} is loaded in namespace {}.",
+                        namespace.as_deref().unwrap_or("default")
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
     pub fn listen(&mut self) -> Result<(), std::io::Error> {
         let ip = LISTEN_ADDR.to_socket_addrs();
         #[cfg(not(target_family = "unix"))]
@@ -164,6 +469,7 @@ impl BBServer {
         Ok(())
     }
     fn listen_tcp(&mut self) -> Result<(), std::io::Error> {
+        let tls = tls::server_config()?;
         {
             let version = env!("CARGO_PKG_VERSION");
             let message = format!("Bulletin Board Server v{version} started.");
@@ -171,20 +477,48 @@ impl BBServer {
 
             let message = format!("Listening on TCP socket: {}.", &*LISTEN_ADDR);
             logging::info(message);
+
+            if TOKENS.is_empty() {
+                let message = format!(
+                    "Token authentication is disabled. Clients are given the {} role.",
+                    *DEFAULT_ROLE
+                );
+                logging::warn(message);
+            } else {
+                let message = format!(
+                    "Token authentication is enabled for {} users.",
+                    TOKENS.len()
+                );
+                logging::info(message);
+            }
+
+            if tls.is_some() {
+                logging::info("TLS is enabled.".to_string());
+            }
         }
         let listener = TcpListener::bind(&*LISTEN_ADDR)?;
         for stream in listener.incoming() {
-            let stream = stream?;
-            match self.process(stream) {
-                Ok(exit) => {
-                    if exit {
-                        break;
+            if self.terminated.load(Ordering::SeqCst) {
+                break;
+            }
+            match stream {
+                Ok(stream) => {
+                    // Responses are small and written in pieces, so they should not wait for delayed ACKs.
+                    if let Err(err) = stream.set_nodelay(true) {
+                        logging::warn(err.to_string());
+                    }
+                    // Clients have to present a token to get a role if tokens are configured.
+                    let session = Session::legacy(TOKENS.is_empty().then_some(*DEFAULT_ROLE));
+                    match &tls {
+                        // The TLS handshake is done in the spawned thread on the first read.
+                        Some(config) => match ServerConnection::new(config.clone()) {
+                            Ok(conn) => self.spawn(StreamOwned::new(conn, stream), session),
+                            Err(err) => logging::error(err.to_string()),
+                        },
+                        None => self.spawn(stream, session),
                     }
                 }
-                Err(err) => {
-                    let err = Box::leak(err);
-                    logging::error(err.to_string());
-                }
+                Err(err) => logging::error(err.to_string()),
             }
         }
         Ok(())
@@ -204,215 +538,721 @@ impl BBServer {
         }
         let listener = UnixListener::bind(&*LISTEN_ADDR)?;
         for stream in listener.incoming() {
-            let stream = stream?;
-            match self.process(stream) {
-                Ok(exit) => {
-                    if exit {
-                        break;
+            if self.terminated.load(Ordering::SeqCst) {
+                break;
+            }
+            match stream {
+                // Access to a Unix socket is restricted by the permission of the file. The role is given by the user of the peer.
+                Ok(stream) => match crate::permission::peer_uid(&stream) {
+                    Ok(uid) => {
+                        let role = UNIX_ROLES
+                            .iter()
+                            .find(|(id, _)| *id == uid)
+                            .map_or(Role::Admin, |(_, role)| *role);
+                        let session = Session::legacy(Some(role)).with_user(format!("uid {uid}"));
+                        self.spawn(stream, session);
                     }
-                }
-                Err(err) => {
-                    let err = Box::leak(err);
-                    logging::error(err.to_string());
+                    Err(err) => {
+                        logging::error(format!("Failed to get the peer credentials: {err}"))
+                    }
+                },
+                Err(err) => logging::error(err.to_string()),
+            }
+        }
+        if std::path::Path::new(&*LISTEN_ADDR).exists() {
+            std::fs::remove_file(&*LISTEN_ADDR)?;
+        }
+        Ok(())
+    }
+    fn spawn<S: std::io::Read + std::io::Write + Peer + Send + 'static>(
+        &self,
+        stream: S,
+        session: Session,
+    ) {
+        let server = self.clone();
+        thread::spawn(move || match server.process(stream, session) {
+            Ok(exit) => {
+                if exit {
+                    server.terminate();
                 }
             }
+            Err(err) => {
+                logging::error(err.to_string());
+            }
+        });
+    }
+    fn terminate(&self) {
+        self.terminated.store(true, Ordering::SeqCst);
+        for (_, watchers) in self.namespaces.lock().unwrap().values() {
+            watchers.clear();
+        }
+        // Wake up the listener blocked in accept so that it can observe the flag.
+        let woken = match LISTEN_ADDR.to_socket_addrs() {
+            Ok(mut addrs) => match addrs.next() {
+                Some(addr) => TcpStream::connect(loopback(addr)).map(|_| ()),
+                None => Ok(()),
+            },
+            #[cfg(target_family = "unix")]
+            Err(_) => UnixStream::connect(&*LISTEN_ADDR).map(|_| ()),
+            #[cfg(not(target_family = "unix"))]
+            Err(_) => Ok(()),
+        };
+        if let Err(err) = woken {
+            logging::error(format!("Failed to stop the listener: {err}"));
+        }
+    }
+    /// Reads the arguments of a rejected operation so that the client receives the response instead of a reset connection.
+    fn discard_arguments<S: std::io::Read>(
+        stream: &mut S,
+        operation: &Operation,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        match operation {
+            Operation::Version
+            | Operation::Status
+            | Operation::Log
+            | Operation::ViewBoard
+            | Operation::ListArchive
+            | Operation::ListLoaded
+            | Operation::ClearLog
+            | Operation::Reset
+            | Operation::Terminate
+            | Operation::NoReply => {}
+            Operation::PostStream | Operation::Import => {
+                let _: ciborium::Value = ciborium::from_reader(&mut *stream)?;
+                ChunkReader::new(stream).skip()?;
+            }
+            _ => {
+                let _: ciborium::Value = ciborium::from_reader(stream)?;
+            }
         }
         Ok(())
     }
-    fn process<S: std::io::Read + std::io::Write>(
-        &mut self,
+    fn process<S: std::io::Read + std::io::Write + Peer>(
+        &self,
         mut stream: S,
+        mut session: Session,
     ) -> Result<bool, Box<dyn std::error::Error>> {
-        while let Ok(operation) = ciborium::from_reader(&mut stream) {
-            match operation {
-                Operation::Post => {
-                    self.post(&mut stream)?;
-                }
-                Operation::Read => {
-                    self.read(&mut stream)?;
-                }
-                Operation::Relabel => {
-                    self.relabel(&mut stream)?;
-                }
-                Operation::Version => {
-                    self.version(&mut stream)?;
-                }
-                Operation::Status => {
-                    self.status(&mut stream)?;
-                }
-                Operation::Log => {
-                    self.log(&mut stream)?;
-                }
-                Operation::ViewBoard => {
-                    self.view_board(&mut stream)?;
-                }
-                Operation::GetInfo => {
-                    self.get_info(&mut stream)?;
+        // The server of the default namespace until another one is selected by the handshake.
+        let mut server = self.clone();
+        let mut reply = true;
+        loop {
+            let operation = match Self::next_operation(&mut stream) {
+                Ok(Some(operation)) => operation,
+                Ok(None) => break,
+                Err(response) => {
+                    ciborium::into_writer(&response, &mut stream)?;
+                    break;
                 }
+            };
+            if !session.is_authenticated()
+                && !matches!(operation, Operation::Handshake | Operation::NoReply)
+            {
+                logging::warn("Rejected an operation of an unauthenticated client.".to_string());
+                Self::discard_arguments(&mut stream, &operation)?;
+                let response = Response::Error {
+                    kind: ErrorKind::Unauthorized,
+                    message: "A token is required by the server.".to_string(),
+                };
+                ciborium::into_writer(&response, &mut stream)?;
+                break;
+            }
+            if let Some(role) = session.role().filter(|role| !role.permits(&operation)) {
+                logging::warn(format!(
+                    "Rejected {operation:?} of {} with the {role} role.",
+                    session.user().unwrap_or("an anonymous client")
+                ));
+                let response = Response::Error {
+                    kind: ErrorKind::PermissionDenied,
+                    message: format!("{operation:?} is not allowed for the {role} role."),
+                };
+                Self::discard_arguments(&mut stream, &operation)?;
+                ciborium::into_writer(&response, &mut stream)?;
+                break;
+            }
+            let replied = std::mem::replace(&mut reply, true);
+            let acknowledge = replied && session.has(capability::ACKNOWLEDGE);
+            let mut exit = false;
+            let mut close = false;
+            // Operations returning a value send it by themselves. The others are acknowledged only if requested.
+            let (result, ack) = match operation {
+                Operation::Post => (server.post(&mut stream), Some(acknowledge)),
+                Operation::Read => (server.read(&mut stream), None),
+                Operation::Relabel => (server.relabel(&mut stream), Some(acknowledge)),
+                Operation::Version => (server.version(&mut stream, &session), None),
+                Operation::Status => (server.status(&mut stream, &session), None),
+                Operation::Log => (server.log(&mut stream, &session), None),
+                Operation::ViewBoard => (server.view_board(&mut stream, &session), None),
+                Operation::GetInfo => (server.get_info(&mut stream, &session), None),
                 Operation::ClearRevisions => {
-                    self.clear_revisions(&mut stream)?;
-                }
-                Operation::Remove => {
-                    self.remove(&mut stream)?;
-                }
-                Operation::Archive => {
-                    self.archive(&mut stream)?;
-                }
-                Operation::Load => {
-                    self.load(&mut stream)?;
+                    (server.clear_revisions(&mut stream), Some(acknowledge))
                 }
-                Operation::ListArchive => {
-                    self.list_archive(&mut stream)?;
+                Operation::Remove => (server.remove(&mut stream), Some(acknowledge)),
+                Operation::Archive => (server.archive(&mut stream), Some(acknowledge)),
+                Operation::Load => (server.load(&mut stream, false), Some(acknowledge)),
+                Operation::ListArchive => (server.list_archive(&mut stream, &session), None),
+                Operation::RenameArchive => (server.rename_archive(&mut stream), Some(acknowledge)),
+                Operation::DeleteArchive => (server.delete_archive(&mut stream), Some(acknowledge)),
+                Operation::Dump => (server.dump(&mut stream), Some(acknowledge)),
+                Operation::Restore => (
+                    server.restore(&mut stream, &session, false),
+                    Some(acknowledge),
+                ),
+                Operation::ClearLog => (server.clear_log(), Some(acknowledge)),
+                Operation::Reset => (server.reset(), Some(acknowledge)),
+                Operation::Terminate => {
+                    exit = true;
+                    (server.reset_all(), Some(acknowledge))
                 }
-                Operation::RenameArchive => {
-                    self.rename_archive(&mut stream)?;
+                Operation::NoReply => {
+                    reply = false;
+                    continue;
                 }
-                Operation::DeleteArchive => {
-                    self.delete_archive(&mut stream)?;
+                Operation::Handshake => {
+                    match self.handshake(&mut stream, &mut session, !replied)? {
+                        Some(selected) => server = selected,
+                        None => break,
+                    }
+                    continue;
                 }
-                Operation::Dump => {
-                    self.dump(&mut stream)?;
+                Operation::PostStream => (server.post_stream(&mut stream), Some(acknowledge)),
+                Operation::ReadStream => (server.read_stream(&mut stream), None),
+                Operation::WaitRead => (server.wait_read(&mut stream), None),
+                Operation::Query => (server.query(&mut stream), None),
+                Operation::SetRetention => (server.set_retention(&mut stream), Some(acknowledge)),
+                Operation::VerifyArchive => (server.verify_archive(&mut stream, &session), None),
+                Operation::DeleteFromArchive => {
+                    (server.delete_from_archive(&mut stream), Some(acknowledge))
                 }
-                Operation::Restore => {
-                    self.reset()?;
-                    self.restore(&mut stream)?;
+                Operation::CompactArchive => {
+                    (server.compact_archive(&mut stream), Some(acknowledge))
                 }
-                Operation::ClearLog => {
-                    self.clear_log()?;
+                Operation::Unload => (server.unload(&mut stream), Some(acknowledge)),
+                Operation::ListLoaded => (server.list_loaded(&mut stream, &session), None),
+                Operation::LoadSelected => (server.load(&mut stream, true), Some(acknowledge)),
+                Operation::RestoreSelected => (
+                    server.restore(&mut stream, &session, true),
+                    Some(acknowledge),
+                ),
+                Operation::ArchiveInfo => (server.archive_info(&mut stream), None),
+                Operation::Export => (server.export(&mut stream), None),
+                Operation::Import => (server.import(&mut stream), Some(acknowledge)),
+                Operation::Watch => {
+                    close = true;
+                    (server.watch(&mut stream), None)
                 }
-                Operation::Reset => {
-                    self.reset()?;
+            };
+            match result {
+                Ok(()) => {
+                    if ack == Some(true) {
+                        ciborium::into_writer(&Response::Ok, &mut stream)?;
+                    }
                 }
-                Operation::Terminate => {
-                    self.reset()?;
-                    if !LISTEN_ADDR.to_socket_addrs().is_ok() {
-                        if std::path::Path::new(&*LISTEN_ADDR).exists() {
-                            std::fs::remove_file(&*LISTEN_ADDR)?;
-                        }
+                Err(err) => {
+                    logging::error(err.to_string());
+                    if ack.unwrap_or(true) {
+                        ciborium::into_writer(&error_response(&*err), &mut stream)?;
+                    }
+                    if is_fatal(&*err) {
+                        break;
                     }
-                    return Ok(true);
                 }
-            };
+            }
+            if exit {
+                return Ok(true);
+            }
+            if close {
+                break;
+            }
         }
         Ok(false)
     }
-    fn get_tag<S: std::io::Read + std::io::Write>(
+    /// Reads the next operation. Returns `None` if the connection is closed, or the response to be sent if the request is not understood. In the latter case, the connection has to be closed since the arguments cannot be skipped.
+    fn next_operation<S: std::io::Read>(stream: &mut S) -> Result<Option<Operation>, Response> {
+        let value: ciborium::Value = match ciborium::from_reader(stream) {
+            Ok(value) => value,
+            Err(ciborium::de::Error::Io(_)) => return Ok(None),
+            Err(err) => {
+                logging::warn(format!("Malformed request: {err}"));
+                return Err(Response::Error {
+                    kind: ErrorKind::Protocol,
+                    message: format!("Malformed request: {err}"),
+                });
+            }
+        };
+        value.deserialized().map(Some).map_err(|_| {
+            let name = match &value {
+                ciborium::Value::Text(name) => name.clone(),
+                value => format!("{value:?}"),
+            };
+            logging::warn(format!("Unknown operation: {name}."));
+            Response::Error {
+                kind: ErrorKind::UnknownOperation,
+                message: format!("Unknown operation: {name}."),
+            }
+        })
+    }
+    /// Sends the value of an operation. Legacy clients expect the value without a preceding response.
+    fn send<S: std::io::Read + std::io::Write, T: serde::Serialize>(
+        stream: &mut S,
+        session: &Session,
+        value: &T,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut buf = Cursor::new(vec![]);
+        if !session.is_legacy() {
+            ciborium::into_writer(&Response::Ok, &mut buf)?;
+        }
+        ciborium::into_writer(value, &mut buf)?;
+        buf.set_position(0);
+        io::copy(&mut buf, stream)?;
+        Ok(())
+    }
+    /// Returns the server working on the namespace selected by the client, or `None` if the client is rejected. If `quiet`, the negotiated handshake is not sent back.
+    fn handshake<S: std::io::Read + std::io::Write>(
         &self,
+        stream: &mut S,
+        session: &mut Session,
+        quiet: bool,
+    ) -> Result<Option<Self>, Box<dyn std::error::Error>> {
+        let client: Handshake = ciborium::from_reader(&mut *stream)?;
+        logging::debug(format!(
+            "(handshake) protocol_version: {}, capabilities: {:#x}.",
+            client.protocol_version, client.capabilities
+        ));
+        if let Err(message) = session.authenticate(client.token.as_deref()) {
+            logging::warn(format!("Rejected a client: {message}"));
+            let response = Response::Error {
+                kind: ErrorKind::Unauthorized,
+                message,
+            };
+            ciborium::into_writer(&response, stream)?;
+            return Ok(None);
+        }
+        if let (Some(user), Some(role)) = (session.user(), session.role()) {
+            logging::debug(format!("(handshake) user: {user}, role: {role}."));
+        }
+        let namespace = client
+            .namespace
+            .as_deref()
+            .filter(|namespace| !namespace.is_empty());
+        // A read-only client cannot create a namespace, which would only hold an empty board.
+        let server = match self.select(namespace, session.allows(Role::Writer)) {
+            Ok(server) => server,
+            Err(err) => {
+                logging::warn(format!("Rejected a client: {err}"));
+                let response = Response::Error {
+                    kind: error_kind(&err),
+                    message: err.to_string(),
+                };
+                ciborium::into_writer(&response, stream)?;
+                return Ok(None);
+            }
+        };
+        if let Some(namespace) = namespace {
+            logging::debug(format!("(handshake) namespace: {namespace}."));
+        }
+        match session.negotiate(client) {
+            Ok(_) if quiet => Ok(Some(server)),
+            Ok(handshake) => {
+                let mut buf = Cursor::new(vec![]);
+                ciborium::into_writer(&Response::Ok, &mut buf)?;
+                ciborium::into_writer(&handshake, &mut buf)?;
+                buf.set_position(0);
+                io::copy(&mut buf, stream)?;
+                Ok(Some(server))
+            }
+            Err(message) => {
+                logging::warn(format!("Rejected a client: {message}"));
+                let response = Response::Error {
+                    kind: ErrorKind::Protocol,
+                    message,
+                };
+                ciborium::into_writer(&response, stream)?;
+                Ok(None)
+            }
+        }
+    }
+    fn get_tag(
+        board: &BulletinBoard,
         operation: &str,
         title: &String,
         tag: Option<String>,
-        stream: Option<&mut S>,
     ) -> Result<String, Box<dyn std::error::Error>> {
         match tag {
             Some(tag) => Ok(tag),
             None => {
-                let tags = self.bulletinboard.find_tags(title);
+                let tags = board.find_tags(title);
                 match tags.len() {
-                    0 => {
-                        if let Some(stream) = stream {
-                            ciborium::into_writer(&Response::NotFound, stream)?;
-                        }
-                        Err(Box::new(BulletinError::new(
-                            operation,
-                            "Not found.".to_string(),
-                            title.clone(),
-                            "NA".to_string(),
-                            None,
-                        )))
-                    }
+                    0 => Err(Box::new(Self::not_found(
+                        operation,
+                        title.clone(),
+                        "NA".to_string(),
+                    ))),
                     1 => Ok(tags[0].clone()),
-                    _ => {
-                        if let Some(stream) = stream {
-                            ciborium::into_writer(&Response::NotUnique(tags.clone()), stream)?;
-                        }
-                        Err(Box::new(BulletinError::new(
-                            operation,
-                            "Found multiple entries having the same name.".to_string(),
-                            title.clone(),
-                            "NA".to_string(),
-                            None,
-                        )))
-                    }
+                    _ => Err(Box::new(NotUniqueError::new(
+                        operation,
+                        title.clone(),
+                        tags,
+                    ))),
                 }
             }
         }
     }
+    fn not_found(operation: &str, title: String, tag: String) -> BulletinError {
+        BulletinError::new(operation, "Not found.".to_string(), title, tag, None)
+            .with_kind(ErrorKind::NotFound)
+    }
     fn post<S: std::io::Read + std::io::Write>(
-        &mut self,
+        &self,
         stream: &mut S,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        let (title, tag, data): (String, String, ByteBuf) = ciborium::from_reader(&mut *stream)?;
+        let PostArgs(title, tag, data, attributes) = ciborium::from_reader(&mut *stream)?;
         logging::debug(format!("(post) title: {title}, tag: {tag}."));
-        let bulletin = Bulletin::from_data(data.to_vec());
-        self.bulletinboard
-            .post(title.clone(), tag.clone(), bulletin)
-            .map_err(|err| BulletinError::new("post", err.to_string(), title, tag, None))?;
+        let mut bulletin = Bulletin::from_data(data.to_vec());
+        bulletin.attributes = attributes;
+        let revision = self.insert(&title, &tag, bulletin).map_err(|err| {
+            BulletinError::new("post", err.to_string(), title.clone(), tag.clone(), None)
+                .with_kind(error_kind(&err))
+        })?;
+        self.watchers.notify(Event::Posted {
+            title,
+            tag,
+            revision,
+        });
         Ok(())
     }
+    /// Posts a bulletin, locking the board only to insert it. Large data is written to a file and the data is written to the journal before, and the journal is flushed after. Posts to the same key are serialized by the lock of the key.
+    fn insert(&self, title: &str, tag: &str, mut bulletin: Bulletin) -> Result<u64, io::Error> {
+        let (dir, key_lock, writer) = {
+            let board = self.bulletinboard.read().unwrap();
+            (
+                board.dir().to_string(),
+                board.key_lock(title, tag),
+                board.journal_writer(),
+            )
+        };
+        let _key_guard = key_lock.lock().unwrap();
+        if bulletin.datasize >= *FILE_THRETHOLD
+            && matches!(bulletin.data, BulletinBackend::Memory(_))
+        {
+            bulletin.save_to_file(&dir)?;
+        }
+        let data = match writer.map(|writer| writer.write(&bulletin)).transpose() {
+            Ok(data) => data,
+            Err(err) => {
+                bulletin.clear()?;
+                return Err(err);
+            }
+        };
+        let (revision, flush) = {
+            let mut board = self.bulletinboard.write().unwrap();
+            let revision = board.post(title.to_string(), tag.to_string(), bulletin, data)?;
+            (revision, board.flush())
+        };
+        if let Some(flush) = flush {
+            flush.wait()?;
+        }
+        Ok(revision)
+    }
     fn read<S: std::io::Read + std::io::Write>(
-        &mut self,
+        &self,
         stream: &mut S,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let (title, tag, revisions): (String, Option<String>, Vec<u64>) =
             ciborium::from_reader(&mut *stream)?;
         logging::debug(format!("(read) title: {title}, tag: {tag:?}."));
-        let tag = self.get_tag("read", &title, tag, Some(&mut *stream))?;
         let mut buf = Cursor::new(vec![]);
-
-        if let Some(bulletins) = self.bulletinboard.take(title.clone(), tag.clone()) {
+        let mut hot = vec![];
+        let tag = {
+            let board = self.bulletinboard.read().unwrap();
+            let tag = Self::get_tag(&board, "read", &title, tag)?;
+            let bulletins = board
+                .get(title.clone(), tag.clone())
+                .ok_or_else(|| Self::not_found("read", title.clone(), tag.clone()))?;
             if revisions.is_empty() {
-                if let Some(bulletin) = bulletins.last_mut() {
-                    ciborium::into_writer(&Response::Ok, &mut buf)?;
-                    let data = bulletin.get()?;
-                    ciborium::into_writer(&ByteBuf::from(data), &mut buf)?;
-                    bulletin.close();
-                } else {
-                    ciborium::into_writer(&Response::NotFound, stream)?;
-                    return Err(Box::new(BulletinError::new(
-                        "read",
-                        "Not found.".to_string(),
-                        title,
-                        tag,
-                        None,
-                    )));
+                let (revision, bulletin) = bulletins
+                    .latest()
+                    .ok_or_else(|| Self::not_found("read", title.clone(), tag.clone()))?;
+                ciborium::into_writer(&Response::Ok, &mut buf)?;
+                let data = bulletin.get()?;
+                ciborium::into_writer(&ByteBuf::from(data), &mut buf)?;
+                if bulletin.is_hot() {
+                    hot.push(revision);
                 }
             } else {
                 for revision in revisions {
-                    if let Some(bulletin) = bulletins.get_mut::<usize>(revision.try_into().unwrap())
-                    {
-                        ciborium::into_writer(&Response::Ok, &mut buf)?;
-                        let data = bulletin.get()?;
-                        ciborium::into_writer(&ByteBuf::from(data), &mut buf)?;
-                        bulletin.close();
-                    } else {
-                        ciborium::into_writer(&Response::NotFound, stream)?;
-                        return Err(Box::new(BulletinError::new(
+                    let bulletin = bulletins.get(revision).ok_or_else(|| {
+                        BulletinError::new(
                             "read",
                             "Not found.".to_string(),
-                            title,
-                            tag,
-                            None,
-                        )));
+                            title.clone(),
+                            tag.clone(),
+                            Some(revision),
+                        )
+                        .with_kind(ErrorKind::NotFound)
+                    })?;
+                    ciborium::into_writer(&Response::Ok, &mut buf)?;
+                    let data = bulletin.get()?;
+                    ciborium::into_writer(&ByteBuf::from(data), &mut buf)?;
+                    if bulletin.is_hot() {
+                        hot.push(revision);
                     }
                 }
             }
-        } else {
-            ciborium::into_writer(&Response::NotFound, stream)?;
-            return Err(Box::new(BulletinError::new(
-                "read",
-                "Not found.".to_string(),
-                title,
-                tag,
-                None,
-            )));
+            tag
         };
         buf.set_position(0);
         io::copy(&mut buf, stream)?;
+        if !hot.is_empty() {
+            let promoted =
+                self.bulletinboard
+                    .write()
+                    .unwrap()
+                    .promote(title.clone(), tag.clone(), hot);
+            if let Err(err) = promoted {
+                logging::error(
+                    BulletinError::new("read", err.to_string(), title, tag, None).to_string(),
+                );
+            }
+        }
 
         Ok(())
     }
+    fn post_stream<S: std::io::Read + std::io::Write>(
+        &self,
+        stream: &mut S,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let PostStreamArgs(title, tag, attributes) = ciborium::from_reader(&mut *stream)?;
+        logging::debug(format!("(post_stream) title: {title}, tag: {tag}."));
+        let mut chunks = ChunkReader::new(stream);
+        let dir = self.bulletinboard.read().unwrap().dir().to_string();
+        let mut bulletin = match Bulletin::from_reader(&mut chunks, &dir) {
+            Ok(bulletin) => bulletin,
+            Err(err) => {
+                chunks.skip()?;
+                return Err(Box::new(
+                    BulletinError::new("post_stream", err.to_string(), title, tag, None)
+                        .with_kind(error_kind(&err)),
+                ));
+            }
+        };
+        bulletin.attributes = attributes;
+        let revision = self.insert(&title, &tag, bulletin).map_err(|err| {
+            BulletinError::new(
+                "post_stream",
+                err.to_string(),
+                title.clone(),
+                tag.clone(),
+                None,
+            )
+            .with_kind(error_kind(&err))
+        })?;
+        self.watchers.notify(Event::Posted {
+            title,
+            tag,
+            revision,
+        });
+        Ok(())
+    }
+    fn import<S: std::io::Read + std::io::Write>(
+        &self,
+        stream: &mut S,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (title, tag, filename): (String, String, String) = ciborium::from_reader(&mut *stream)?;
+        logging::debug(format!(
+            "(import) title: {title}, tag: {tag}, filename: {filename}."
+        ));
+        let mut chunks = ChunkReader::new(stream);
+        let mut data = vec![];
+        if let Err(err) = chunks.read_to_end(&mut data) {
+            chunks.skip()?;
+            return Err(Box::new(
+                BulletinError::new("import", err.to_string(), title, tag, None)
+                    .with_kind(error_kind(&err)),
+            ));
+        }
+        let arrays = import::read_file(&filename, &data).map_err(|err| {
+            BulletinError::new("import", err.to_string(), title.clone(), tag.clone(), None)
+                .with_kind(error_kind(&err))
+        })?;
+        for (name, data) in arrays {
+            let title = match name {
+                Some(name) => format!("{title}/{name}"),
+                None => title.clone(),
+            };
+            let revision = self
+                .insert(&title, &tag, Bulletin::from_data(data))
+                .map_err(|err| {
+                    BulletinError::new("import", err.to_string(), title.clone(), tag.clone(), None)
+                        .with_kind(error_kind(&err))
+                })?;
+            self.watchers.notify(Event::Posted {
+                title,
+                tag: tag.clone(),
+                revision,
+            });
+        }
+        Ok(())
+    }
+    fn read_stream<S: std::io::Read + std::io::Write>(
+        &self,
+        stream: &mut S,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (title, tag, revision): (String, Option<String>, Option<u64>) =
+            ciborium::from_reader(&mut *stream)?;
+        logging::debug(format!(
+            "(read_stream) title: {title}, tag: {tag:?}, revision: {revision:?}."
+        ));
+        // The board is unlocked while the data is sent.
+        let mut reader = {
+            let board = self.bulletinboard.read().unwrap();
+            let tag = Self::get_tag(&board, "read_stream", &title, tag)?;
+            let bulletins = board
+                .get(title.clone(), tag.clone())
+                .ok_or_else(|| Self::not_found("read_stream", title.clone(), tag.clone()))?;
+            let bulletin = match revision {
+                Some(revision) => bulletins.get(revision),
+                None => bulletins.latest().map(|(_, bulletin)| bulletin),
+            }
+            .ok_or_else(|| {
+                BulletinError::new(
+                    "read_stream",
+                    "Not found.".to_string(),
+                    title.clone(),
+                    tag.clone(),
+                    revision,
+                )
+                .with_kind(ErrorKind::NotFound)
+            })?;
+            bulletin.reader().map_err(|err| {
+                BulletinError::new("read_stream", err.to_string(), title.clone(), tag, revision)
+                    .with_kind(error_kind(&err))
+            })?
+        };
+        ciborium::into_writer(&Response::Ok, &mut *stream)?;
+        let response = match write_chunks(&mut reader, stream)? {
+            Ok(()) => Response::Ok,
+            Err(err) => {
+                logging::error(format!("Failed to send {title}: {err}"));
+                error_response(&err)
+            }
+        };
+        ciborium::into_writer(&response, stream)?;
+        Ok(())
+    }
+    fn watch<S: std::io::Read + std::io::Write + Peer>(
+        &self,
+        stream: &mut S,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (filter, with_data): (WatchFilter, bool) = ciborium::from_reader(&mut *stream)?;
+        logging::debug(format!(
+            "(watch) title: {}, tag: {:?}, prefix: {}, with_data: {with_data}.",
+            filter.title, filter.tag, filter.prefix
+        ));
+        let subscription = self.watchers.subscribe();
+        ciborium::into_writer(&Response::Ok, &mut *stream)?;
+        // Blocks until the server is terminated or the client closes the connection, which is checked every second while no event arrives.
+        loop {
+            let event = match subscription.recv_timeout(Duration::from_secs(1)) {
+                Ok(event) => event,
+                Err(mpsc::RecvTimeoutError::Timeout) if !stream.is_closed() => continue,
+                Err(_) => break,
+            };
+            if !filter.accepts(&event) {
+                continue;
+            }
+            let data = match &event {
+                Event::Posted {
+                    title,
+                    tag,
+                    revision,
+                } if with_data => {
+                    let board = self.bulletinboard.read().unwrap();
+                    board
+                        .get(title.clone(), tag.clone())
+                        .and_then(|bulletins| bulletins.get(*revision))
+                        .and_then(|bulletin| bulletin.get().ok())
+                        .map(ByteBuf::from)
+                }
+                _ => None,
+            };
+            let mut buf = Cursor::new(vec![]);
+            ciborium::into_writer(&event, &mut buf)?;
+            ciborium::into_writer(&data, &mut buf)?;
+            buf.set_position(0);
+            if io::copy(&mut buf, stream).is_err() {
+                break;
+            }
+        }
+        Ok(())
+    }
+    fn wait_read<S: std::io::Read + std::io::Write>(
+        &self,
+        stream: &mut S,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (title, tag, after_revision, timeout): (String, Option<String>, Option<u64>, u64) =
+            ciborium::from_reader(&mut *stream)?;
+        logging::debug(format!(
+            "(wait_read) title: {title}, tag: {tag:?}, after_revision: {after_revision:?}, timeout: {timeout}."
+        ));
+        let deadline = Instant::now() + Duration::from_millis(timeout);
+        // Subscribe before looking at the board so that no post is missed in between.
+        let subscription = self.watchers.subscribe();
+        let filter = WatchFilter {
+            title: title.clone(),
+            tag: tag.clone(),
+            prefix: false,
+        };
+        loop {
+            {
+                let board = self.bulletinboard.read().unwrap();
+                let tag = match &tag {
+                    Some(tag) => Some(tag.clone()),
+                    None => match Self::get_tag(&board, "wait_read", &title, None) {
+                        Ok(tag) => Some(tag),
+                        Err(err) if err.is::<NotUniqueError>() => return Err(err),
+                        Err(_) => None,
+                    },
+                };
+                let latest = tag.and_then(|tag| board.get(title.clone(), tag)?.latest());
+                if let Some((revision, bulletin)) = latest
+                    && after_revision.is_none_or(|after| revision > after)
+                {
+                    let mut buf = Cursor::new(vec![]);
+                    ciborium::into_writer(&Response::Ok, &mut buf)?;
+                    ciborium::into_writer(&revision, &mut buf)?;
+                    ciborium::into_writer(&ByteBuf::from(bulletin.get()?), &mut buf)?;
+                    drop(board);
+                    buf.set_position(0);
+                    io::copy(&mut buf, stream)?;
+                    return Ok(());
+                }
+            }
+            loop {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                match subscription.recv_timeout(remaining) {
+                    Ok(event) => {
+                        if filter.accepts(&event) {
+                            break;
+                        }
+                    }
+                    Err(_) => {
+                        return Err(Box::new(
+                            BulletinError::new(
+                                "wait_read",
+                                "Timed out.".to_string(),
+                                title,
+                                tag.unwrap_or("NA".to_string()),
+                                after_revision,
+                            )
+                            .with_kind(ErrorKind::Timeout),
+                        ));
+                    }
+                }
+            }
+        }
+    }
     fn relabel<S: std::io::Read + std::io::Write>(
-        &mut self,
+        &self,
         stream: &mut S,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let (title_from, tag_from, title_to, tag_to): (
@@ -424,81 +1264,195 @@ impl BBServer {
         logging::debug(format!(
             "(relabel) title_from: {title_from}, tag_from: {tag_from:?}, title_to: {title_to:?}, tag_to: {tag_to:?}."
         ));
-        let tag_from = self.get_tag("read", &title_from, tag_from, Some(&mut *stream))?;
+        let mut board = self.bulletinboard.write().unwrap();
+        let tag_from = Self::get_tag(&board, "relabel", &title_from, tag_from)?;
+        board
+            .relabel(
+                title_from.clone(),
+                tag_from.clone(),
+                title_to.clone(),
+                tag_to.clone(),
+            )
+            .map_err(|err| {
+                BulletinError::new(
+                    "relabel",
+                    err.to_string(),
+                    title_from.clone(),
+                    tag_from.clone(),
+                    None,
+                )
+                .with_kind(error_kind(&err))
+            })?;
+        self.watchers.notify(Event::Relabeled {
+            title: title_to.unwrap_or(title_from.clone()),
+            tag: tag_to.unwrap_or(tag_from.clone()),
+            title_from,
+            tag_from,
+        });
+        Ok(())
+    }
+    fn set_retention<S: std::io::Read + std::io::Write>(
+        &self,
+        stream: &mut S,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (title, tag, retention): (String, Option<String>, Option<Retention>) =
+            ciborium::from_reader(&mut *stream)?;
+        logging::debug(format!(
+            "(set_retention) title: {title}, tag: {tag:?}, retention: {retention:?}."
+        ));
         self.bulletinboard
-            .relabel(title_from, tag_from, title_to, tag_to)?;
+            .write()
+            .unwrap()
+            .set_retention(title.clone(), tag.clone(), retention)
+            .map_err(|err| {
+                BulletinError::new(
+                    "set_retention",
+                    err.to_string(),
+                    title,
+                    tag.unwrap_or_default(),
+                    None,
+                )
+                .with_kind(error_kind(&err))
+            })?;
         Ok(())
     }
     fn version<S: std::io::Read + std::io::Write>(
         &self,
         stream: &mut S,
+        session: &Session,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        logging::debug(format!("(version)."));
+        logging::debug("(version).".to_string());
         let version = env!("CARGO_PKG_VERSION").to_string();
-        ciborium::into_writer(&version, stream)?;
+        Self::send(stream, session, &version)?;
         Ok(())
     }
     fn status<S: std::io::Read + std::io::Write>(
         &self,
         stream: &mut S,
+        session: &Session,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        logging::debug(format!("(status)."));
-        let status = self.bulletinboard.status();
-        ciborium::into_writer(&status, stream)?;
+        logging::debug("(status).".to_string());
+        let status = self.bulletinboard.read().unwrap().status();
+        let (
+            datasize,
+            memory_used,
+            memory_used_percent,
+            n_bulletins,
+            n_files,
+            n_archives,
+            n_evicted,
+            n_promoted,
+            ..,
+        ) = status;
+        match session.protocol_version() {
+            4.. => Self::send(stream, session, &status)?,
+            3 => Self::send(
+                stream,
+                session,
+                &(
+                    datasize,
+                    memory_used,
+                    memory_used_percent,
+                    n_bulletins,
+                    n_files,
+                    n_archives,
+                    n_evicted,
+                    n_promoted,
+                ),
+            )?,
+            _ => Self::send(
+                stream,
+                session,
+                &(
+                    datasize,
+                    memory_used,
+                    memory_used_percent,
+                    n_bulletins,
+                    n_files,
+                    n_archives,
+                ),
+            )?,
+        }
         Ok(())
     }
     fn log<S: std::io::Read + std::io::Write>(
         &self,
         stream: &mut S,
+        session: &Session,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        logging::debug(format!("(log)."));
+        logging::debug("(log).".to_string());
         let log = if Path::new(&*LOG_FILE).exists() {
             std::fs::read_to_string(&*LOG_FILE)?
         } else {
             "No logs yet.\n".to_string()
         };
-        ciborium::into_writer(&log, stream)?;
+        Self::send(stream, session, &log)?;
         Ok(())
     }
     fn view_board<S: std::io::Read + std::io::Write>(
         &self,
         stream: &mut S,
+        session: &Session,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        logging::debug(format!("(view_board)."));
-        let board = self.bulletinboard.view();
-        ciborium::into_writer(&board, stream)?;
+        logging::debug("(view_board).".to_string());
+        let board = self.bulletinboard.read().unwrap().view();
+        Self::send(stream, session, &board)?;
+        Ok(())
+    }
+    fn query<S: std::io::Read + std::io::Write>(
+        &self,
+        stream: &mut S,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let query: Query = ciborium::from_reader(&mut *stream)?;
+        logging::debug(format!("(query) {query:?}."));
+        if query.limit == Some(0) {
+            return Err(Box::new(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "The limit must be positive.",
+            )));
+        }
+        let filter = Filter::new(&query)?;
+        let page = self.bulletinboard.read().unwrap().query(&query, &filter);
+        let mut buf = Cursor::new(vec![]);
+        ciborium::into_writer(&Response::Ok, &mut buf)?;
+        ciborium::into_writer(&page, &mut buf)?;
+        buf.set_position(0);
+        io::copy(&mut buf, stream)?;
         Ok(())
     }
     fn get_info<S: std::io::Read + std::io::Write>(
         &self,
         stream: &mut S,
+        session: &Session,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let (title, tag): (String, Option<String>) = ciborium::from_reader(&mut *stream)?;
         logging::debug(format!("(get_info) title: {title}, tag: {tag:?}."));
-        let tag = self.get_tag("get_info", &title, tag, Some(&mut *stream))?;
-        match self.bulletinboard.get_info(title.clone(), tag.clone()) {
-            Some(info) => {
-                let mut buf = Cursor::new(vec![]);
-                ciborium::into_writer(&Response::Ok, &mut buf)?;
-                ciborium::into_writer(&info, &mut buf)?;
-                buf.set_position(0);
-                io::copy(&mut buf, stream)?;
-            }
-            None => {
-                ciborium::into_writer(&Response::NotFound, stream)?;
-                return Err(Box::new(BulletinError::new(
-                    "get_info",
-                    "Not found.".to_string(),
-                    title,
-                    tag,
-                    None,
-                )));
-            }
+        let info = {
+            let board = self.bulletinboard.read().unwrap();
+            let tag = Self::get_tag(&board, "get_info", &title, tag)?;
+            board
+                .get_info(title.clone(), tag.clone())
+                .ok_or_else(|| Self::not_found("get_info", title, tag))?
+        };
+        let mut buf = Cursor::new(vec![]);
+        ciborium::into_writer(&Response::Ok, &mut buf)?;
+        if session.has_attributes() {
+            ciborium::into_writer(&info, &mut buf)?;
+        } else {
+            let info: Vec<_> = info
+                .into_iter()
+                .map(|(revision, datasize, timestamp, backend, _)| {
+                    (revision, datasize, timestamp, backend)
+                })
+                .collect();
+            ciborium::into_writer(&info, &mut buf)?;
         }
+        buf.set_position(0);
+        io::copy(&mut buf, stream)?;
         Ok(())
     }
     fn clear_revisions<S: std::io::Read + std::io::Write>(
-        &mut self,
+        &self,
         stream: &mut S,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let (title, tag, revisions): (String, Option<String>, Vec<u64>) =
@@ -506,42 +1460,33 @@ impl BBServer {
         logging::debug(format!(
             "(clear_revisions) title: {title}, tag: {tag:?}, revisions: {revisions:?}."
         ));
-        let tag = self.get_tag("clear_revisions", &title, tag, None::<&mut S>)?;
-        self.bulletinboard
+        let mut board = self.bulletinboard.write().unwrap();
+        let tag = Self::get_tag(&board, "clear_revisions", &title, tag)?;
+        board
             .clear_revisions(title.clone(), tag.clone(), revisions)
             .map_err(|err| {
-                Box::new(BulletinError::new(
-                    "clear_revisions",
-                    err.to_string(),
-                    title,
-                    tag,
-                    None,
-                ))
+                BulletinError::new("clear_revisions", err.to_string(), title, tag, None)
+                    .with_kind(error_kind(&err))
             })?;
         Ok(())
     }
     fn remove<S: std::io::Read + std::io::Write>(
-        &mut self,
+        &self,
         stream: &mut S,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let (title, tag): (String, Option<String>) = ciborium::from_reader(stream)?;
         logging::debug(format!("(remove) title: {title}, tag: {tag:?}."));
-        let tag = self.get_tag("remove", &title, tag, None::<&mut S>)?;
-        self.bulletinboard
-            .remove(title.clone(), tag.clone())
-            .map_err(|err| {
-                Box::new(BulletinError::new(
-                    "remove",
-                    err.to_string(),
-                    title,
-                    tag,
-                    None,
-                ))
-            })?;
+        let mut board = self.bulletinboard.write().unwrap();
+        let tag = Self::get_tag(&board, "remove", &title, tag)?;
+        board.remove(title.clone(), tag.clone()).map_err(|err| {
+            BulletinError::new("remove", err.to_string(), title.clone(), tag.clone(), None)
+                .with_kind(error_kind(&err))
+        })?;
+        self.watchers.notify(Event::Removed { title, tag });
         Ok(())
     }
     fn archive<S: std::io::Read + std::io::Write>(
-        &mut self,
+        &self,
         stream: &mut S,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let (acv_name, title, tag): (String, String, Option<String>) =
@@ -549,156 +1494,485 @@ impl BBServer {
         logging::debug(format!(
             "(arvhive) archive_name: {acv_name}, title: {title}, tag: {tag:?}."
         ));
-        if acv_name.len() == 0 {
-            return Err(Box::new(ArchiveError::new(
-                "archive",
-                "Wrong archive name.".to_string(),
-                acv_name.clone(),
-            )));
+        if acv_name.is_empty() {
+            return Err(Box::new(
+                ArchiveError::new(
+                    "archive",
+                    "Wrong archive name.".to_string(),
+                    acv_name.clone(),
+                )
+                .with_kind(ErrorKind::InvalidArgument),
+            ));
         }
-        let tag = self.get_tag("archive", &title, tag, None::<&mut S>)?;
-        self.bulletinboard
+        let mut board = self.bulletinboard.write().unwrap();
+        let tag = Self::get_tag(&board, "archive", &title, tag)?;
+        board
             .archive(acv_name, title.clone(), tag.clone())
             .map_err(|err| {
-                Box::new(BulletinError::new(
-                    "archive",
-                    err.to_string(),
-                    title,
-                    tag,
-                    None,
-                ))
+                BulletinError::new("archive", err.to_string(), title, tag, None)
+                    .with_kind(error_kind(&*err))
             })?;
         Ok(())
     }
     fn load<S: std::io::Read + std::io::Write>(
-        &mut self,
+        &self,
+        stream: &mut S,
+        selected: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (acv_name, selection): (String, Selection) = if selected {
+            ciborium::from_reader(stream)?
+        } else {
+            (ciborium::from_reader(stream)?, Selection::default())
+        };
+        logging::debug(format!(
+            "(load) archive_name: {acv_name}, selection: {selection:?}."
+        ));
+        if acv_name.is_empty() {
+            return Err(Box::new(
+                ArchiveError::new("load", "Wrong archive name.".to_string(), acv_name.clone())
+                    .with_kind(ErrorKind::InvalidArgument),
+            ));
+        }
+        self.bulletinboard
+            .write()
+            .unwrap()
+            .load(acv_name.clone(), &selection)
+            .map_err(|err| {
+                ArchiveError::new("load", err.to_string(), acv_name).with_kind(error_kind(&err))
+            })?;
+        Ok(())
+    }
+    fn unload<S: std::io::Read + std::io::Write>(
+        &self,
         stream: &mut S,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let acv_name: String = ciborium::from_reader(stream)?;
-        logging::debug(format!("(load) archive_name: {acv_name}."));
-        if acv_name.len() == 0 {
-            return Err(Box::new(ArchiveError::new(
-                "load",
-                "Wrong archive name.".to_string(),
-                acv_name.clone(),
-            )));
+        logging::debug(format!("(unload) archive_name: {acv_name}."));
+        if acv_name.is_empty() {
+            return Err(Box::new(
+                ArchiveError::new(
+                    "unload",
+                    "Wrong archive name.".to_string(),
+                    acv_name.clone(),
+                )
+                .with_kind(ErrorKind::InvalidArgument),
+            ));
         }
         self.bulletinboard
-            .load(acv_name.clone())
-            .map_err(|err| ArchiveError::new("load", err.to_string(), acv_name))?;
+            .write()
+            .unwrap()
+            .unload(acv_name.clone())
+            .map_err(|err| {
+                ArchiveError::new("unload", err.to_string(), acv_name).with_kind(error_kind(&err))
+            })?;
+        Ok(())
+    }
+    fn list_loaded<S: std::io::Read + std::io::Write>(
+        &self,
+        stream: &mut S,
+        session: &Session,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        logging::debug("(list_loaded).".to_string());
+        let list = self.bulletinboard.read().unwrap().list_loaded();
+        Self::send(stream, session, &list)?;
         Ok(())
     }
     fn list_archive<S: std::io::Read + std::io::Write>(
         &self,
         stream: &mut S,
+        session: &Session,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        logging::debug(format!("(list_archive)."));
-        match self.bulletinboard.list_archive() {
+        logging::debug("(list_archive).".to_string());
+        let list = self.bulletinboard.read().unwrap().list_archive();
+        match list {
             Ok(list) => {
-                ciborium::into_writer(&list, stream)?;
+                Self::send(stream, session, &list)?;
             }
             Err(_) => {
                 let empty: Vec<String> = vec![];
-                ciborium::into_writer(&empty, stream)?;
+                Self::send(stream, session, &empty)?;
             }
         }
         Ok(())
     }
     fn rename_archive<S: std::io::Read + std::io::Write>(
-        &mut self,
+        &self,
         stream: &mut S,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let (acv_from, acv_to): (String, String) = ciborium::from_reader(stream)?;
         logging::debug(format!("(rename_archive) from: {acv_from}, to: {acv_to}."));
-        if acv_from.len() == 0 {
-            return Err(Box::new(ArchiveError::new(
-                "rename_archive",
-                "Wrong archive name.".to_string(),
-                acv_from.clone(),
-            )));
+        if acv_from.is_empty() {
+            return Err(Box::new(
+                ArchiveError::new(
+                    "rename_archive",
+                    "Wrong archive name.".to_string(),
+                    acv_from.clone(),
+                )
+                .with_kind(ErrorKind::InvalidArgument),
+            ));
         }
-        if acv_to.len() == 0 {
-            return Err(Box::new(ArchiveError::new(
-                "rename_archive",
-                "Wrong archive name.".to_string(),
-                acv_to.clone(),
-            )));
+        if acv_to.is_empty() {
+            return Err(Box::new(
+                ArchiveError::new(
+                    "rename_archive",
+                    "Wrong archive name.".to_string(),
+                    acv_to.clone(),
+                )
+                .with_kind(ErrorKind::InvalidArgument),
+            ));
         }
-        self.archive_manipulations.push((acv_from, Some(acv_to)));
+        self.check_unloaded_elsewhere(&acv_from)
+            .and_then(|()| {
+                self.bulletinboard
+                    .write()
+                    .unwrap()
+                    .rename_archive(acv_from.clone(), acv_to.clone())
+            })
+            .map_err(|err| {
+                ArchiveError::new("rename_archive", err.to_string(), acv_from.clone())
+                    .with_kind(error_kind(&err))
+            })?;
+        logging::info(format!("Moved archive: {acv_from} => {acv_to}."));
         Ok(())
     }
     fn delete_archive<S: std::io::Read + std::io::Write>(
-        &mut self,
+        &self,
         stream: &mut S,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let acv_name: String = ciborium::from_reader(stream)?;
         logging::debug(format!("(delete_archive) archive_name: {acv_name}."));
-        if acv_name.len() == 0 {
-            return Err(Box::new(ArchiveError::new(
-                "delete_archive",
-                "Wrong archive name.".to_string(),
-                acv_name.clone(),
-            )));
+        if acv_name.is_empty() {
+            return Err(Box::new(
+                ArchiveError::new(
+                    "delete_archive",
+                    "Wrong archive name.".to_string(),
+                    acv_name.clone(),
+                )
+                .with_kind(ErrorKind::InvalidArgument),
+            ));
         }
-        self.archive_manipulations.push((acv_name, None));
+        self.check_unloaded_elsewhere(&acv_name)
+            .and_then(|()| {
+                self.bulletinboard
+                    .write()
+                    .unwrap()
+                    .delete_archive(acv_name.clone())
+            })
+            .map_err(|err| {
+                ArchiveError::new("delete_archive", err.to_string(), acv_name.clone())
+                    .with_kind(error_kind(&err))
+            })?;
+        logging::info(format!("Deleted archive: {acv_name}."));
         Ok(())
     }
     fn dump<S: std::io::Read + std::io::Write>(
-        &mut self,
+        &self,
         stream: &mut S,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let acv_name: String = ciborium::from_reader(stream)?;
         logging::debug(format!("(dump) archive_name: {acv_name}."));
-        if acv_name.len() == 0 {
-            return Err(Box::new(ArchiveError::new(
-                "dump",
-                "Wrong archive name.".to_string(),
-                acv_name.clone(),
-            )));
+        if acv_name.is_empty() {
+            return Err(Box::new(
+                ArchiveError::new("dump", "Wrong archive name.".to_string(), acv_name.clone())
+                    .with_kind(ErrorKind::InvalidArgument),
+            ));
         }
-        self.bulletinboard.dump(acv_name)?;
+        self.bulletinboard.write().unwrap().dump(acv_name)?;
         Ok(())
     }
-    fn restore<S: std::io::Read + std::io::Write>(
-        &mut self,
+    fn archive_info<S: std::io::Read + std::io::Write>(
+        &self,
+        stream: &mut S,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let acv_name: String = ciborium::from_reader(&mut *stream)?;
+        logging::debug(format!("(archive_info) archive_name: {acv_name}."));
+        if acv_name.is_empty() {
+            return Err(Box::new(
+                ArchiveError::new(
+                    "archive_info",
+                    "Wrong archive name.".to_string(),
+                    acv_name.clone(),
+                )
+                .with_kind(ErrorKind::InvalidArgument),
+            ));
+        }
+        let info = self
+            .bulletinboard
+            .read()
+            .unwrap()
+            .archive_info(acv_name.clone())
+            .map_err(|err| {
+                ArchiveError::new("archive_info", err.to_string(), acv_name)
+                    .with_kind(error_kind(&err))
+            })?;
+        let mut buf = Cursor::new(vec![]);
+        ciborium::into_writer(&Response::Ok, &mut buf)?;
+        ciborium::into_writer(&info, &mut buf)?;
+        buf.set_position(0);
+        io::copy(&mut buf, stream)?;
+        Ok(())
+    }
+    fn export<S: std::io::Read + std::io::Write>(
+        &self,
+        stream: &mut S,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let acv_name: Option<String> = ciborium::from_reader(&mut *stream)?;
+        logging::debug(format!("(export) archive_name: {acv_name:?}."));
+        if acv_name
+            .as_ref()
+            .is_some_and(|acv_name| acv_name.is_empty())
+        {
+            return Err(Box::new(
+                ArchiveError::new(
+                    "export",
+                    "Wrong archive name.".to_string(),
+                    acv_name.unwrap(),
+                )
+                .with_kind(ErrorKind::InvalidArgument),
+            ));
+        }
+        // The NPZ file is written to the temporary directory, so that the board is unlocked while it is sent.
+        let filename = format!("{}/export-{}.npz", *TMP_DIR, Uuid::new_v4());
+        let exported = fs::create_dir_all(&*TMP_DIR)
+            .and_then(|()| fs::File::create(&filename))
+            .and_then(|file| {
+                self.bulletinboard
+                    .read()
+                    .unwrap()
+                    .export(acv_name.clone(), io::BufWriter::new(file))
+            });
+        let manifest = match exported {
+            Ok((_, manifest)) => manifest,
+            Err(err) => {
+                fs::remove_file(&filename).ok();
+                return Err(match acv_name {
+                    Some(acv_name) => Box::new(
+                        ArchiveError::new("export", err.to_string(), acv_name)
+                            .with_kind(error_kind(&err)),
+                    ),
+                    None => Box::new(err),
+                });
+            }
+        };
+        let sent = fs::File::open(&filename).map(|mut file| {
+            ciborium::into_writer(&Response::Ok, &mut *stream)?;
+            ciborium::into_writer(&manifest, &mut *stream)?;
+            write_chunks(&mut file, stream)
+        });
+        fs::remove_file(&filename)?;
+        let response = match sent?? {
+            Ok(()) => Response::Ok,
+            Err(err) => {
+                logging::error(format!("Failed to send the export: {err}"));
+                error_response(&err)
+            }
+        };
+        ciborium::into_writer(&response, stream)?;
+        Ok(())
+    }
+    fn verify_archive<S: std::io::Read + std::io::Write>(
+        &self,
+        stream: &mut S,
+        session: &Session,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (acv_name, repair): (String, bool) = ciborium::from_reader(&mut *stream)?;
+        logging::debug(format!(
+            "(verify_archive) archive_name: {acv_name}, repair: {repair}."
+        ));
+        if acv_name.is_empty() {
+            return Err(Box::new(
+                ArchiveError::new(
+                    "verify_archive",
+                    "Wrong archive name.".to_string(),
+                    acv_name.clone(),
+                )
+                .with_kind(ErrorKind::InvalidArgument),
+            ));
+        }
+        // Repairing rewrites the index, which may drop revisions.
+        if repair && !session.allows(Role::Admin) {
+            return Err(Box::new(
+                ArchiveError::new(
+                    "verify_archive",
+                    "Repairing an archive is only allowed for the admin role.".to_string(),
+                    acv_name,
+                )
+                .with_kind(ErrorKind::PermissionDenied),
+            ));
+        }
+        let report = if repair {
+            self.bulletinboard
+                .write()
+                .unwrap()
+                .verify_archive(acv_name.clone(), repair)
+        } else {
+            self.bulletinboard
+                .read()
+                .unwrap()
+                .verify_archive(acv_name.clone(), repair)
+        }
+        .map_err(|err| {
+            ArchiveError::new("verify_archive", err.to_string(), acv_name.clone())
+                .with_kind(error_kind(&err))
+        })?;
+        if !report.is_intact() {
+            logging::warn(format!("Archive {acv_name} is damaged."));
+        }
+        if report.repaired {
+            logging::notice(format!("Archive {acv_name} is repaired."));
+        }
+        let mut buf = Cursor::new(vec![]);
+        ciborium::into_writer(&Response::Ok, &mut buf)?;
+        ciborium::into_writer(&report, &mut buf)?;
+        buf.set_position(0);
+        io::copy(&mut buf, stream)?;
+        Ok(())
+    }
+    fn delete_from_archive<S: std::io::Read + std::io::Write>(
+        &self,
+        stream: &mut S,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (acv_name, title, tag, revisions): (String, String, Option<String>, Option<Vec<u64>>) =
+            ciborium::from_reader(stream)?;
+        logging::debug(format!(
+            "(delete_from_archive) archive_name: {acv_name}, title: {title}, tag: {tag:?}, revisions: {revisions:?}."
+        ));
+        if acv_name.is_empty() {
+            return Err(Box::new(
+                ArchiveError::new(
+                    "delete_from_archive",
+                    "Wrong archive name.".to_string(),
+                    acv_name.clone(),
+                )
+                .with_kind(ErrorKind::InvalidArgument),
+            ));
+        }
+        self.check_unloaded_elsewhere(&acv_name)
+            .and_then(|()| {
+                self.bulletinboard.write().unwrap().delete_from_archive(
+                    acv_name.clone(),
+                    title,
+                    tag,
+                    revisions,
+                )
+            })
+            .map_err(|err| {
+                ArchiveError::new("delete_from_archive", err.to_string(), acv_name)
+                    .with_kind(error_kind(&err))
+            })?;
+        Ok(())
+    }
+    fn compact_archive<S: std::io::Read + std::io::Write>(
+        &self,
         stream: &mut S,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let acv_name: String = ciborium::from_reader(stream)?;
-        logging::debug(format!("(restore) archive_name: {acv_name}."));
-        if acv_name.len() == 0 {
-            return Err(Box::new(ArchiveError::new(
-                "restore",
-                "Wrong archive name.".to_string(),
-                acv_name.clone(),
-            )));
+        logging::debug(format!("(compact_archive) archive_name: {acv_name}."));
+        if acv_name.is_empty() {
+            return Err(Box::new(
+                ArchiveError::new(
+                    "compact_archive",
+                    "Wrong archive name.".to_string(),
+                    acv_name.clone(),
+                )
+                .with_kind(ErrorKind::InvalidArgument),
+            ));
+        }
+        self.check_unloaded_elsewhere(&acv_name)
+            .and_then(|()| {
+                self.bulletinboard
+                    .write()
+                    .unwrap()
+                    .compact_archive(acv_name.clone())
+            })
+            .map_err(|err| {
+                ArchiveError::new("compact_archive", err.to_string(), acv_name)
+                    .with_kind(error_kind(&err))
+            })?;
+        Ok(())
+    }
+    fn restore<S: std::io::Read + std::io::Write>(
+        &self,
+        stream: &mut S,
+        session: &Session,
+        selected: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (acv_name, selection, merge): (String, Selection, bool) = if selected {
+            ciborium::from_reader(stream)?
+        } else {
+            (ciborium::from_reader(stream)?, Selection::default(), false)
+        };
+        logging::debug(format!(
+            "(restore) archive_name: {acv_name}, selection: {selection:?}, merge: {merge}."
+        ));
+        if acv_name.is_empty() {
+            return Err(Box::new(
+                ArchiveError::new(
+                    "restore",
+                    "Wrong archive name.".to_string(),
+                    acv_name.clone(),
+                )
+                .with_kind(ErrorKind::InvalidArgument),
+            ));
+        }
+        // Restoring without merging replaces the whole board.
+        if !merge && !session.allows(Role::Admin) {
+            return Err(Box::new(
+                ArchiveError::new(
+                    "restore",
+                    "Restoring without merging is only allowed for the admin role.".to_string(),
+                    acv_name,
+                )
+                .with_kind(ErrorKind::PermissionDenied),
+            ));
+        }
+        let flush = {
+            let mut board = self.bulletinboard.write().unwrap();
+            board
+                .restore(acv_name.clone(), &selection, merge)
+                .map_err(|err| {
+                    ArchiveError::new("restore", err.to_string(), acv_name)
+                        .with_kind(error_kind(&*err))
+                })?;
+            board.flush()
+        };
+        if let Some(flush) = flush {
+            flush.wait()?;
+        }
+        if !merge {
+            logging::notice("Server restarted.".to_string());
         }
-        self.bulletinboard
-            .restore(acv_name.clone())
-            .map_err(|err| ArchiveError::new("restore", err.to_string(), acv_name))?;
         Ok(())
     }
     fn clear_log(&self) -> Result<(), Box<dyn std::error::Error>> {
-        logging::debug(format!("(clear_log)."));
+        logging::debug("(clear_log).".to_string());
         if Path::new(&*LOG_FILE).exists() {
             fs::remove_file(&*LOG_FILE)?;
         }
         Ok(())
     }
-    fn reset(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        logging::debug(format!("(restore/reset/exit)."));
-        self.bulletinboard.reset()?;
-        for (name_from, name_to) in self.archive_manipulations.drain(..) {
-            match name_to {
-                Some(name_to) => {
-                    self.bulletinboard
-                        .rename_archive(name_from.clone(), name_to.clone())?;
-                    logging::info(format!("Moved archive: {name_from} => {name_to}."));
-                }
-                None => {
-                    self.bulletinboard.delete_archive(name_from.clone())?;
-                    logging::info(format!("Deleted archive: {name_from}."));
-                }
-            }
+    fn reset(&self) -> Result<(), Box<dyn std::error::Error>> {
+        logging::debug("(restore/reset).".to_string());
+        let mut board = self.bulletinboard.write().unwrap();
+        board.reset()?;
+        match board.namespace() {
+            Some(namespace) => logging::notice(format!("Namespace {namespace} is reset.")),
+            None => logging::notice("Server restarted.".to_string()),
+        }
+        Ok(())
+    }
+    /// Resets the boards of all the namespaces before the server exits.
+    fn reset_all(&self) -> Result<(), Box<dyn std::error::Error>> {
+        logging::debug("(exit).".to_string());
+        let boards: Vec<_> = self
+            .namespaces
+            .lock()
+            .unwrap()
+            .values()
+            .map(|(board, _)| board.clone())
+            .collect();
+        for board in boards {
+            board.write().unwrap().reset()?;
         }
         logging::notice("Server restarted.".to_string());
         Ok(())
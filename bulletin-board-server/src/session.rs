@@ -0,0 +1,109 @@
+use crate::TOKENS;
+use crate::permission::Role;
+use bulletin_board_common::*;
+
+/// Capabilities supported by this server.
+const CAPABILITIES: u64 = capability::ACKNOWLEDGE | capability::QUIET_HANDSHAKE;
+
+/// State of a connection negotiated by the handshake.
+pub struct Session {
+    handshake: Handshake,
+    user: Option<String>,
+    role: Option<Role>,
+}
+
+impl Session {
+    /// Session of a client that did not send a handshake (v0.3 or earlier). Such a client does not read acknowledgements.
+    ///
+    /// If the role is None, the client has to be authenticated by a token in the handshake.
+    pub fn legacy(role: Option<Role>) -> Self {
+        Self {
+            handshake: Handshake {
+                protocol_version: 0,
+                capabilities: 0,
+                token: None,
+                namespace: None,
+            },
+            user: None,
+            role,
+        }
+    }
+    /// Names the user of a connection that is not authenticated by a token.
+    pub fn with_user(mut self, user: String) -> Self {
+        self.user = Some(user);
+        self
+    }
+    /// Authenticates a client by the token sent in the handshake, which gives the role of the token. A token is not required if the session already has a role.
+    pub fn authenticate(&mut self, token: Option<&str>) -> Result<(), String> {
+        let mut user = None;
+        if let Some(token) = token {
+            // All the tokens are compared so that the response time does not tell which one matched.
+            for (name, expected, role) in TOKENS.iter() {
+                if constant_time_eq(expected.as_bytes(), token.as_bytes()) {
+                    user = Some((name.clone(), *role));
+                }
+            }
+        }
+        match user {
+            Some((user, role)) => {
+                self.user = Some(user);
+                self.role = Some(role);
+                Ok(())
+            }
+            None if self.role.is_some() => Ok(()),
+            None if token.is_some() => Err("Invalid token.".to_string()),
+            None => Err("A token is required by the server.".to_string()),
+        }
+    }
+    /// Negotiates the protocol with a client. Returns the handshake to be sent back, or the reason of the rejection.
+    pub fn negotiate(&mut self, client: Handshake) -> Result<Handshake, String> {
+        if client.protocol_version < MIN_PROTOCOL_VERSION
+            || client.protocol_version > PROTOCOL_VERSION
+        {
+            return Err(format!(
+                "Unsupported protocol version {}. The server supports versions from {} to {}.",
+                client.protocol_version, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION
+            ));
+        }
+        // The selected namespace is sent back so that the client can confirm it.
+        self.handshake = Handshake::new(client.capabilities & CAPABILITIES)
+            .with_namespace(client.namespace.filter(|namespace| !namespace.is_empty()));
+        self.handshake.protocol_version = client.protocol_version;
+        Ok(self.handshake.clone())
+    }
+    /// Legacy clients read the value of an operation without a preceding [`Response`].
+    pub fn is_legacy(&self) -> bool {
+        self.handshake.protocol_version == 0
+    }
+    /// Clients of protocol version 2 or later read the attributes of revisions.
+    pub fn has_attributes(&self) -> bool {
+        self.handshake.protocol_version >= 2
+    }
+    pub fn protocol_version(&self) -> u32 {
+        self.handshake.protocol_version
+    }
+    pub fn has(&self, capability: u64) -> bool {
+        self.handshake.has(capability)
+    }
+    pub fn is_authenticated(&self) -> bool {
+        self.role.is_some()
+    }
+    pub fn role(&self) -> Option<Role> {
+        self.role
+    }
+    /// Whether the client has the role or a higher one, for the variants of an operation that need more than the operation itself.
+    pub fn allows(&self, role: Role) -> bool {
+        self.role.is_some_and(|own| own >= role)
+    }
+    /// Name of the user authenticated by a token or the peer credentials.
+    pub fn user(&self) -> Option<&str> {
+        self.user.as_deref()
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
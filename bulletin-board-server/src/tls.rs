@@ -0,0 +1,70 @@
+use crate::{TLS_CA, TLS_CERT, TLS_KEY};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use rustls::{ClientConfig, ClientConnection, RootCertStore, ServerConfig, StreamOwned};
+use std::io;
+use std::net::TcpStream;
+use std::sync::Arc;
+
+fn invalid(path: &str, err: impl std::fmt::Display) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, format!("{path}: {err}"))
+}
+
+fn provider() -> Arc<rustls::crypto::CryptoProvider> {
+    Arc::new(rustls::crypto::ring::default_provider())
+}
+
+fn read_certs(path: &str) -> Result<Vec<CertificateDer<'static>>, io::Error> {
+    let certs = CertificateDer::pem_file_iter(path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|err| invalid(path, err))?;
+    if certs.is_empty() {
+        return Err(invalid(path, "No certificate is found."));
+    }
+    Ok(certs)
+}
+
+/// Configuration of the TCP listener. Returns None if TLS is not enabled by `BB_TLS_CERT` and `BB_TLS_KEY`.
+pub fn server_config() -> Result<Option<Arc<ServerConfig>>, io::Error> {
+    let (cert, key) = match (&*TLS_CERT, &*TLS_KEY) {
+        (Some(cert), Some(key)) => (cert, key),
+        (None, None) => return Ok(None),
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Both BB_TLS_CERT and BB_TLS_KEY are required for TLS.",
+            ));
+        }
+    };
+    let certs = read_certs(cert)?;
+    let key = PrivateKeyDer::from_pem_file(key).map_err(|err| invalid(key, err))?;
+    let config = ServerConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()
+        .map_err(io::Error::other)?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|err| invalid(cert, err))?;
+    Ok(Some(Arc::new(config)))
+}
+
+/// Connects to the server itself over TLS as `localhost`. The certificate is verified against `BB_TLS_CA`, or the certificate of the server if it is not set.
+pub fn connect_local(
+    stream: TcpStream,
+) -> Result<StreamOwned<ClientConnection, TcpStream>, io::Error> {
+    let ca = TLS_CA
+        .as_ref()
+        .or(TLS_CERT.as_ref())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "BB_TLS_CERT is not set."))?;
+    let mut roots = RootCertStore::empty();
+    for cert in read_certs(ca)? {
+        roots.add(cert).map_err(|err| invalid(ca, err))?;
+    }
+    let config = ClientConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()
+        .map_err(io::Error::other)?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    let name = ServerName::try_from("localhost").unwrap();
+    let conn = ClientConnection::new(Arc::new(config), name).map_err(io::Error::other)?;
+    Ok(StreamOwned::new(conn, stream))
+}
@@ -0,0 +1,106 @@
+use bulletin_board_common::Event;
+use rustls::{ServerConnection, StreamOwned};
+use std::io;
+use std::net::TcpStream;
+#[cfg(target_family = "unix")]
+use std::os::unix::net::UnixStream;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::time::Duration;
+
+/// Connections subscribing to the changes of the board.
+#[derive(Default)]
+pub struct Watchers {
+    senders: Mutex<Vec<(u64, Sender<Event>)>>,
+    next_id: AtomicU64,
+}
+
+impl Watchers {
+    pub fn subscribe(&self) -> Subscription<'_> {
+        let (sender, receiver) = mpsc::channel();
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.senders.lock().unwrap().push((id, sender));
+        Subscription {
+            watchers: self,
+            id,
+            receiver,
+        }
+    }
+    /// Sends the event to all the watchers. Watchers whose connections are closed are dropped.
+    pub fn notify(&self, event: Event) {
+        self.senders
+            .lock()
+            .unwrap()
+            .retain(|(_, sender)| sender.send(event.clone()).is_ok());
+    }
+    /// Drops all the watchers, which ends their connections.
+    pub fn clear(&self) {
+        self.senders.lock().unwrap().clear();
+    }
+}
+
+/// Receiver of the events, which is removed from the [`Watchers`] when dropped.
+pub struct Subscription<'a> {
+    watchers: &'a Watchers,
+    id: u64,
+    receiver: Receiver<Event>,
+}
+
+impl Subscription<'_> {
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<Event, RecvTimeoutError> {
+        self.receiver.recv_timeout(timeout)
+    }
+}
+
+impl Drop for Subscription<'_> {
+    fn drop(&mut self) {
+        self.watchers
+            .senders
+            .lock()
+            .unwrap()
+            .retain(|(id, _)| *id != self.id);
+    }
+}
+
+/// Connection whose closure by the client can be checked without blocking.
+pub trait Peer {
+    /// Whether the client has closed the connection or sent data, which it does not do while it waits for the server.
+    fn is_closed(&self) -> bool;
+}
+
+impl Peer for TcpStream {
+    fn is_closed(&self) -> bool {
+        if self.set_nonblocking(true).is_err() {
+            return true;
+        }
+        let result = self.peek(&mut [0]);
+        if self.set_nonblocking(false).is_err() {
+            return true;
+        }
+        !matches!(result, Err(err) if err.kind() == io::ErrorKind::WouldBlock)
+    }
+}
+
+#[cfg(target_family = "unix")]
+impl Peer for UnixStream {
+    fn is_closed(&self) -> bool {
+        use std::os::fd::AsRawFd;
+        let mut buf = [0u8];
+        let ret = unsafe {
+            libc::recv(
+                self.as_raw_fd(),
+                buf.as_mut_ptr() as *mut libc::c_void,
+                1,
+                libc::MSG_PEEK | libc::MSG_DONTWAIT,
+            )
+        };
+        ret >= 0 || io::Error::last_os_error().kind() != io::ErrorKind::WouldBlock
+    }
+}
+
+impl Peer for StreamOwned<ServerConnection, TcpStream> {
+    fn is_closed(&self) -> bool {
+        self.sock.is_closed()
+    }
+}